@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use reqwest::{Client, StatusCode};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -110,6 +112,26 @@ impl YadiskClient {
         Self::handle_response(response).await
     }
 
+    /// Sets one custom property of a resource; `None` removes it.
+    pub async fn set_custom_property(
+        &self,
+        path: &str,
+        key: &str,
+        value: Option<&str>,
+    ) -> Result<Resource, YadiskError> {
+        let mut url = self.endpoint("/v1/disk/resources")?;
+        url.query_pairs_mut().append_pair("path", path);
+        let body = serde_json::json!({ "custom_properties": { key: value } });
+        let response = self
+            .http
+            .patch(url)
+            .header("Authorization", self.auth_header_value())
+            .json(&body)
+            .send()
+            .await?;
+        Self::handle_response(response).await
+    }
+
     pub async fn move_resource(
         &self,
         from: &str,
@@ -181,6 +203,76 @@ impl YadiskClient {
         Ok(Some(Self::handle_response(response).await?))
     }
 
+    /// Lists the trash. Entries carry `origin_path` and `deleted`, telling
+    /// where each one was deleted from and when.
+    pub async fn list_trash(
+        &self,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<ResourceList, YadiskError> {
+        let mut url = self.endpoint("/v1/disk/trash/resources")?;
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("path", "trash:/");
+            if let Some(limit) = limit {
+                query.append_pair("limit", &limit.to_string());
+            }
+            if let Some(offset) = offset {
+                query.append_pair("offset", &offset.to_string());
+            }
+        }
+        let response = self
+            .http
+            .get(url)
+            .header("Authorization", self.auth_header_value())
+            .send()
+            .await?;
+        let payload: ResourceListResponse = Self::handle_response(response).await?;
+        payload.embedded.ok_or(YadiskError::MissingEmbedded)
+    }
+
+    pub async fn list_trash_all(&self, page_size: u32) -> Result<Vec<Resource>, YadiskError> {
+        let page_size = page_size.max(1);
+        let mut offset = 0u32;
+        let mut items = Vec::new();
+        loop {
+            let page = self.list_trash(Some(page_size), Some(offset)).await?;
+            if page.items.is_empty() {
+                break;
+            }
+            offset = offset.saturating_add(page.items.len() as u32);
+            let total = page.total;
+            items.extend(page.items);
+            if offset >= total {
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    /// Restores `trash_path` (a `trash:/` path from `list_trash`) to where it
+    /// was deleted from.
+    pub async fn restore_from_trash(
+        &self,
+        trash_path: &str,
+        overwrite: bool,
+    ) -> Result<Option<TransferLink>, YadiskError> {
+        let mut url = self.endpoint("/v1/disk/trash/resources/restore")?;
+        url.query_pairs_mut()
+            .append_pair("path", trash_path)
+            .append_pair("overwrite", if overwrite { "true" } else { "false" });
+        let response = self
+            .http
+            .put(url)
+            .header("Authorization", self.auth_header_value())
+            .send()
+            .await?;
+        if response.status() == StatusCode::CREATED {
+            return Ok(None);
+        }
+        Ok(Some(Self::handle_response(response).await?))
+    }
+
     pub async fn get_operation_status(
         &self,
         operation_url: &str,
@@ -431,6 +523,23 @@ pub struct Resource {
     pub resource_id: Option<String>,
     #[serde(default)]
     pub md5: Option<String>,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+    /// Where a trash entry was deleted from; only set in trash listings.
+    #[serde(default)]
+    pub origin_path: Option<String>,
+    /// When a trash entry was deleted; only set in trash listings.
+    #[serde(default)]
+    pub deleted: Option<String>,
+    #[serde(default)]
+    pub custom_properties: Option<HashMap<String, serde_json::Value>>,
+}
+
+impl Resource {
+    /// A custom property of the resource, if it is set to a string.
+    pub fn custom_property(&self, key: &str) -> Option<&str> {
+        self.custom_properties.as_ref()?.get(key)?.as_str()
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
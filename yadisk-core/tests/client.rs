@@ -1,5 +1,5 @@
 use serde_json::json;
-use wiremock::matchers::{header, method, path, query_param};
+use wiremock::matchers::{body_json, header, method, path, query_param};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 use yadisk_core::{ApiErrorClass, OperationStatus, ResourceType, YadiskClient};
 
@@ -284,6 +284,74 @@ async fn delete_resource_returns_operation_link() {
     );
 }
 
+#[tokio::test]
+async fn list_trash_reports_origin_paths() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/disk/trash/resources"))
+        .and(query_param("path", "trash:/"))
+        .and(query_param("limit", "100"))
+        .and(query_param("offset", "0"))
+        .and(header("authorization", "OAuth test-token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "_embedded": {
+                "limit": 100,
+                "offset": 0,
+                "total": 1,
+                "items": [
+                    {
+                        "path": "trash:/A.txt_1f2e",
+                        "name": "A.txt",
+                        "type": "file",
+                        "origin_path": "disk:/Docs/A.txt",
+                        "deleted": "2026-10-18T12:00:00+00:00"
+                    }
+                ]
+            }
+        })))
+        .mount(&server)
+        .await;
+
+    let client = YadiskClient::with_base_url(&server.uri(), "test-token").unwrap();
+    let items = client.list_trash_all(100).await.unwrap();
+
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].path, "trash:/A.txt_1f2e");
+    assert_eq!(items[0].origin_path.as_deref(), Some("disk:/Docs/A.txt"));
+    assert!(items[0].deleted.is_some());
+}
+
+#[tokio::test]
+async fn restore_from_trash_returns_operation_link() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("PUT"))
+        .and(path("/v1/disk/trash/resources/restore"))
+        .and(query_param("path", "trash:/Docs_1f2e"))
+        .and(query_param("overwrite", "false"))
+        .and(header("authorization", "OAuth test-token"))
+        .respond_with(ResponseTemplate::new(202).set_body_json(json!({
+            "href": "https://cloud-api.yandex.net/v1/disk/operations/4",
+            "method": "GET",
+            "templated": false
+        })))
+        .mount(&server)
+        .await;
+
+    let client = YadiskClient::with_base_url(&server.uri(), "test-token").unwrap();
+    let link = client
+        .restore_from_trash("trash:/Docs_1f2e", false)
+        .await
+        .unwrap()
+        .expect("expected operation link for 202");
+
+    assert_eq!(
+        link.href.as_str(),
+        "https://cloud-api.yandex.net/v1/disk/operations/4"
+    );
+}
+
 #[tokio::test]
 async fn get_operation_status_parses_response() {
     let server = MockServer::start().await;
@@ -575,3 +643,36 @@ async fn base_url_with_path_prefix_is_preserved() {
     let info = client.get_disk_info().await.unwrap();
     assert_eq!(info.total_space, 500);
 }
+
+#[tokio::test]
+async fn set_custom_property_patches_resource() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("PATCH"))
+        .and(path("/v1/disk/resources"))
+        .and(query_param("path", "/Docs/link"))
+        .and(header("authorization", "OAuth test-token"))
+        .and(body_json(json!({
+            "custom_properties": { "yadisk_symlink": "../target.txt" }
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "path": "disk:/Docs/link",
+            "name": "link",
+            "type": "file",
+            "custom_properties": { "yadisk_symlink": "../target.txt", "rating": 5 }
+        })))
+        .mount(&server)
+        .await;
+
+    let client = YadiskClient::with_base_url(&server.uri(), "test-token").unwrap();
+    let resource = client
+        .set_custom_property("/Docs/link", "yadisk_symlink", Some("../target.txt"))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        resource.custom_property("yadisk_symlink"),
+        Some("../target.txt")
+    );
+    assert_eq!(resource.custom_property("rating"), None);
+}
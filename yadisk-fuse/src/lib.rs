@@ -1,8 +1,14 @@
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use thiserror::Error;
+use yadiskd::sync::delete_guard::{
+    DELETE_BURST_WINDOW, DeleteBurst, DeleteLimits, hold_local_delete,
+};
 use yadiskd::sync::index::{
     FileState, IndexError, IndexStore, ItemInput, ItemRecord, ItemType, StateMeta, StateRecord,
 };
-use yadiskd::sync::queue::{Operation, OperationKind};
+use yadiskd::sync::queue::{ON_DEMAND_PRIORITY, Operation, OperationKind};
 
 pub const XATTR_STATE: &str = "user.yadisk.state";
 
@@ -14,15 +20,30 @@ pub enum FuseBridgeError {
     NotFound(String),
     #[error("invalid path: {0}")]
     InvalidPath(String),
+    #[error("deletion held for confirmation: {0}")]
+    DeleteHeld(String),
 }
 
 pub struct YadiskFuseBridge {
     index: IndexStore,
+    delete_limits: Option<DeleteLimits>,
+    delete_burst: Mutex<DeleteBurst>,
 }
 
 impl YadiskFuseBridge {
     pub fn new(index: IndexStore) -> Self {
-        Self { index }
+        Self {
+            index,
+            delete_limits: None,
+            delete_burst: Mutex::new(DeleteBurst::new(DELETE_BURST_WINDOW)),
+        }
+    }
+
+    /// Holds mass deletions made through the mount for confirmation, as the
+    /// daemon does for the sync folder.
+    pub fn with_delete_guard(mut self, limits: DeleteLimits) -> Self {
+        self.delete_limits = Some(limits);
+        self
     }
 
     pub async fn getattr(
@@ -77,13 +98,34 @@ impl YadiskFuseBridge {
                     payload: None,
                     attempt: 0,
                     retry_at: None,
-                    priority: 80,
+                    priority: ON_DEMAND_PRIORITY,
                 })
                 .await?;
         }
         Ok(())
     }
 
+    /// Whether another file next to `path` is still only in the cloud, so
+    /// reading ahead after `path` was opened could fetch anything.
+    pub async fn has_cloud_only_siblings(&self, path: &str) -> Result<bool, FuseBridgeError> {
+        let Some(item) = self.index.get_item_by_path(path).await? else {
+            return Ok(false);
+        };
+        let Some(parent) = item.parent_path.as_deref() else {
+            return Ok(false);
+        };
+        for sibling in self.index.list_children(parent).await? {
+            if sibling.id == item.id || sibling.item_type != ItemType::File {
+                continue;
+            }
+            let state = self.index.get_state(sibling.id).await?;
+            if state.is_none_or(|state| state.state == FileState::CloudOnly) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
     pub async fn create_file(&self, path: &str, modified: i64) -> Result<(), FuseBridgeError> {
         self.upsert_file(path, 0, modified).await?;
         Ok(())
@@ -205,13 +247,114 @@ impl YadiskFuseBridge {
         Ok(())
     }
 
+    /// Copies `from` to `to` as a server-side copy. The caller has already
+    /// copied the cached content; sources with changes the server has not
+    /// seen yet are uploaded from that content instead.
+    pub async fn copy(&self, from: &str, to: &str) -> Result<(), FuseBridgeError> {
+        if from == "/"
+            || to == "/"
+            || from == to
+            || to.starts_with(&(format!("{}/", from.trim_end_matches('/'))))
+        {
+            return Err(FuseBridgeError::InvalidPath(format!("{from} -> {to}")));
+        }
+        let source = self.collect_tree(from).await?;
+        if source.is_empty() {
+            return Err(FuseBridgeError::NotFound(from.to_string()));
+        }
+        self.ensure_parent_dirs(to).await?;
+        self.index.delete_ops_by_prefix(to).await?;
+
+        let upload_instead = source.iter().any(|(item, state)| {
+            is_local_only(item) || state.as_ref().is_some_and(|state| state.dirty)
+        });
+        let mut copied_paths = Vec::with_capacity(source.len());
+        for (item, _) in &source {
+            let new_path = replace_prefix(&item.path, from, to)?;
+            let existing = self.index.get_item_by_path(&new_path).await?;
+            let previous = match &existing {
+                Some(existing) => self.index.get_state(existing.id).await?,
+                None => None,
+            };
+            let new_item = self
+                .index
+                .upsert_item(&ItemInput {
+                    path: new_path.clone(),
+                    parent_path: parent_path(&new_path),
+                    name: leaf_name(&new_path),
+                    item_type: item.item_type.clone(),
+                    size: item.size,
+                    modified: item.modified,
+                    hash: item.hash.clone(),
+                    resource_id: existing.as_ref().and_then(|item| item.resource_id.clone()),
+                    last_synced_hash: existing
+                        .as_ref()
+                        .and_then(|item| item.last_synced_hash.clone()),
+                    last_synced_modified: existing
+                        .as_ref()
+                        .and_then(|item| item.last_synced_modified),
+                })
+                .await?;
+            self.set_pending_state(&new_item, previous.as_ref()).await?;
+            copied_paths.push((new_path, item.item_type.clone()));
+        }
+
+        if upload_instead {
+            for (path, kind) in copied_paths {
+                match kind {
+                    ItemType::Dir => {
+                        self.index
+                            .enqueue_op(&Operation {
+                                kind: OperationKind::Mkdir,
+                                path,
+                                payload: None,
+                                attempt: 0,
+                                retry_at: None,
+                                priority: 55,
+                            })
+                            .await?;
+                    }
+                    ItemType::File => {
+                        self.enqueue_upload(&path).await?;
+                    }
+                }
+            }
+        } else {
+            let payload = serde_json::json!({
+                "from": from,
+                "path": to,
+                "overwrite": true
+            })
+            .to_string();
+            self.index
+                .enqueue_op(&Operation {
+                    kind: OperationKind::Copy,
+                    path: to.to_string(),
+                    payload: Some(payload),
+                    attempt: 0,
+                    retry_at: None,
+                    priority: 60,
+                })
+                .await?;
+        }
+        Ok(())
+    }
+
     pub async fn unlink_or_rmdir(&self, path: &str) -> Result<(), FuseBridgeError> {
         let tree = self.collect_tree(path).await?;
         if tree.is_empty() {
             return Err(FuseBridgeError::NotFound(path.to_string()));
         }
-        self.index.delete_ops_by_prefix(path).await?;
         let remote_known = !is_local_only(&tree[0].0);
+        // A held deletion leaves the index alone, so rejecting it in the
+        // daemon finds the files to bring back.
+        if remote_known
+            && let Some(limits) = self.delete_limits
+            && hold_local_delete(&self.index, limits, &self.delete_burst, path, now_unix()).await?
+        {
+            return Err(FuseBridgeError::DeleteHeld(path.to_string()));
+        }
+        self.index.delete_ops_by_prefix(path).await?;
         self.delete_tree_now(&tree).await?;
         if remote_known {
             self.index
@@ -414,6 +557,13 @@ fn ancestor_dirs(path: &str) -> Vec<String> {
     out
 }
 
+fn now_unix() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
 fn leaf_name(path: &str) -> String {
     path.split('/').next_back().unwrap_or(path).to_string()
 }
@@ -615,6 +765,78 @@ mod tests {
         assert_eq!(moved.resource_id.as_deref(), Some("rid-a"));
     }
 
+    #[tokio::test]
+    async fn copy_synced_file_queues_server_side_copy() {
+        let bridge = make_bridge().await;
+        bridge
+            .index
+            .upsert_item(&ItemInput {
+                path: "/Templates".into(),
+                parent_path: Some("/".into()),
+                name: "Templates".into(),
+                item_type: ItemType::Dir,
+                size: None,
+                modified: Some(100),
+                hash: None,
+                resource_id: Some("rid-templates".into()),
+                last_synced_hash: None,
+                last_synced_modified: Some(100),
+            })
+            .await
+            .unwrap();
+        let record = bridge
+            .index
+            .upsert_item(&ItemInput {
+                path: "/Templates/A.txt".into(),
+                parent_path: Some("/Templates".into()),
+                name: "A.txt".into(),
+                item_type: ItemType::File,
+                size: Some(1),
+                modified: Some(100),
+                hash: Some("abc".into()),
+                resource_id: Some("rid-a".into()),
+                last_synced_hash: Some("abc".into()),
+                last_synced_modified: Some(100),
+            })
+            .await
+            .unwrap();
+        bridge
+            .index
+            .set_state(record.id, FileState::Cached, true, None)
+            .await
+            .unwrap();
+        bridge.create_file("/Templates/B.txt", 101).await.unwrap();
+
+        bridge
+            .copy("/Templates/A.txt", "/Templates/B.txt")
+            .await
+            .unwrap();
+
+        let ops = dequeue_all(&bridge.index).await;
+        assert_eq!(ops.len(), 1);
+        assert_eq!(ops[0].kind, OperationKind::Copy);
+        assert_eq!(ops[0].path, "/Templates/B.txt");
+        assert_eq!(
+            ops[0].touched_paths(),
+            vec!["/Templates/B.txt", "/Templates/A.txt"]
+        );
+        let copied = bridge
+            .index
+            .get_item_by_path("/Templates/B.txt")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(copied.hash.as_deref(), Some("abc"));
+        assert!(
+            bridge
+                .index
+                .get_item_by_path("/Templates/A.txt")
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
     #[tokio::test]
     async fn unlink_local_only_file_drops_item_without_delete_op() {
         let bridge = make_bridge().await;
@@ -674,4 +896,116 @@ mod tests {
                 .is_none()
         );
     }
+
+    #[tokio::test]
+    async fn mass_unlink_through_mount_is_held_for_confirmation() {
+        let bridge = make_bridge().await.with_delete_guard(DeleteLimits {
+            max_files: 2,
+            max_percent: 0,
+        });
+        for name in ["a", "b", "c"] {
+            let path = format!("/Docs/{name}.txt");
+            let item = bridge
+                .index
+                .upsert_item(&ItemInput {
+                    path: path.clone(),
+                    parent_path: Some("/Docs".into()),
+                    name: format!("{name}.txt"),
+                    item_type: ItemType::File,
+                    size: Some(1),
+                    modified: Some(100),
+                    hash: Some("h".into()),
+                    resource_id: Some(format!("rid-{name}")),
+                    last_synced_hash: Some("h".into()),
+                    last_synced_modified: Some(100),
+                })
+                .await
+                .unwrap();
+            bridge
+                .index
+                .set_state(item.id, FileState::Cached, false, None)
+                .await
+                .unwrap();
+        }
+
+        bridge.unlink_or_rmdir("/Docs/a.txt").await.unwrap();
+        bridge.unlink_or_rmdir("/Docs/b.txt").await.unwrap();
+        let held = bridge.unlink_or_rmdir("/Docs/c.txt").await;
+        assert!(matches!(held, Err(FuseBridgeError::DeleteHeld(_))));
+
+        // The deletes already queued in the burst are held with it.
+        assert!(dequeue_all(&bridge.index).await.is_empty());
+        let mut pending: Vec<_> = bridge
+            .index
+            .list_pending_deletes()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|held| held.path)
+            .collect();
+        pending.sort();
+        assert_eq!(pending, ["/Docs/a.txt", "/Docs/b.txt", "/Docs/c.txt"]);
+        assert!(
+            bridge
+                .index
+                .get_item_by_path("/Docs/c.txt")
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn siblings_are_worth_prefetching_only_while_some_are_cloud_only() {
+        let bridge = make_bridge().await;
+        let mut ids = Vec::new();
+        for name in ["a.jpg", "b.jpg"] {
+            let record = bridge
+                .index
+                .upsert_item(&ItemInput {
+                    path: format!("/Photos/{name}"),
+                    parent_path: Some("/Photos".into()),
+                    name: name.into(),
+                    item_type: ItemType::File,
+                    size: Some(1),
+                    modified: Some(100),
+                    hash: None,
+                    resource_id: None,
+                    last_synced_hash: None,
+                    last_synced_modified: None,
+                })
+                .await
+                .unwrap();
+            bridge
+                .index
+                .set_state(record.id, FileState::CloudOnly, false, None)
+                .await
+                .unwrap();
+            ids.push(record.id);
+        }
+
+        assert!(
+            bridge
+                .has_cloud_only_siblings("/Photos/a.jpg")
+                .await
+                .unwrap()
+        );
+        bridge
+            .index
+            .set_state(ids[1], FileState::Cached, false, None)
+            .await
+            .unwrap();
+        assert!(
+            !bridge
+                .has_cloud_only_siblings("/Photos/a.jpg")
+                .await
+                .unwrap()
+        );
+        assert!(
+            !bridge
+                .has_cloud_only_siblings("/Photos/gone.jpg")
+                .await
+                .unwrap()
+        );
+    }
 }
@@ -10,6 +10,7 @@ mod app {
     use std::ffi::OsStr;
     use std::fs::OpenOptions;
     use std::path::{Path, PathBuf};
+    use std::sync::mpsc::{SyncSender, sync_channel};
     use std::sync::{Arc, Condvar, Mutex};
     use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
@@ -17,10 +18,13 @@ mod app {
         FileAttr, FileType, Filesystem, MountOption, ReplyAttr, ReplyCreate, ReplyData,
         ReplyDirectory, ReplyEmpty, ReplyEntry, ReplyOpen, ReplyWrite, Request,
     };
-    use libc::{EIO, EISDIR, ENOENT, ENOTDIR, O_ACCMODE, O_RDWR, O_TRUNC, O_WRONLY};
+    use libc::{
+        EIO, EISDIR, ENOENT, ENOTDIR, EOPNOTSUPP, EPERM, O_ACCMODE, O_RDWR, O_TRUNC, O_WRONLY,
+    };
     use tokio::runtime::Runtime;
     use yadisk_fuse::{FuseBridgeError, YadiskFuseBridge};
     use yadisk_integrations::ids::{DBUS_INTERFACE_SYNC, DBUS_NAME_SYNC, DBUS_OBJECT_PATH_SYNC};
+    use yadiskd::sync::delete_guard::DeleteLimits;
     use yadiskd::sync::index::{FileState, IndexStore, ItemType};
     use yadiskd::sync::paths::cache_path_for;
     use zbus::blocking::{Connection, Proxy, connection::Builder as ConnectionBuilder};
@@ -29,6 +33,8 @@ mod app {
     const MAX_INODE_CACHE_ENTRIES: usize = 100_000;
     const DBUS_HEALTHCHECK_TIMEOUT: Duration = Duration::from_secs(2);
     const DBUS_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(10);
+    /// Folders with a read-ahead request waiting at once.
+    const PREFETCH_QUEUE_LEN: usize = 16;
 
     struct InodeMap {
         next: u64,
@@ -221,6 +227,72 @@ mod app {
         }
     }
 
+    /// Passes read-ahead requests to the daemon from a single thread over one
+    /// D-Bus connection. Opens in a folder that already has a request waiting
+    /// only update it to the latest file; requests beyond the queue are
+    /// dropped.
+    struct Prefetcher {
+        sender: SyncSender<String>,
+        waiting: Arc<Mutex<HashMap<String, String>>>,
+    }
+
+    impl Prefetcher {
+        fn start() -> Self {
+            let (sender, receiver) = sync_channel::<String>(PREFETCH_QUEUE_LEN);
+            let waiting: Arc<Mutex<HashMap<String, String>>> = Arc::default();
+            let requests = Arc::clone(&waiting);
+            std::thread::spawn(move || {
+                let mut connection = None;
+                for folder in receiver {
+                    let Some(path) = requests
+                        .lock()
+                        .ok()
+                        .and_then(|mut requests| requests.remove(&folder))
+                    else {
+                        continue;
+                    };
+                    if connection.is_none() {
+                        connection = DbusDownloader::connection(DBUS_DOWNLOAD_TIMEOUT);
+                    }
+                    let Some(conn) = &connection else {
+                        continue;
+                    };
+                    let sent = Proxy::new(
+                        conn,
+                        DBUS_NAME_SYNC,
+                        DBUS_OBJECT_PATH_SYNC,
+                        DBUS_INTERFACE_SYNC,
+                    )
+                    .and_then(|proxy| proxy.call_method("PrefetchSiblings", &(path)));
+                    if sent.is_err() {
+                        // Reconnect next time in case the daemon restarted.
+                        connection = None;
+                    }
+                }
+            });
+            Self { sender, waiting }
+        }
+
+        /// Asks the daemon to read ahead after `path` was opened.
+        fn request(&self, path: String) {
+            let folder = Path::new(&path)
+                .parent()
+                .and_then(|parent| parent.to_str())
+                .unwrap_or("/")
+                .to_string();
+            let Ok(mut waiting) = self.waiting.lock() else {
+                return;
+            };
+            if let Some(latest) = waiting.get_mut(&folder) {
+                *latest = path;
+                return;
+            }
+            if self.sender.try_send(folder.clone()).is_ok() {
+                waiting.insert(folder, path);
+            }
+        }
+    }
+
     #[derive(Default)]
     struct HandleMap {
         next: u64,
@@ -262,15 +334,25 @@ mod app {
         inodes: Arc<Mutex<InodeMap>>,
         handles: Arc<Mutex<HandleMap>>,
         downloader: DbusDownloader,
+        prefetcher: Prefetcher,
         state_notify: Arc<(Mutex<()>, Condvar)>,
     }
 
     impl YadiskFuseFs {
-        fn new(rt: Runtime, index: IndexStore, cache_root: PathBuf) -> Self {
+        fn new(
+            rt: Runtime,
+            index: IndexStore,
+            cache_root: PathBuf,
+            delete_limits: Option<DeleteLimits>,
+        ) -> Self {
             let state_notify = Arc::new((Mutex::new(()), Condvar::new()));
             let inodes = Arc::new(Mutex::new(InodeMap::new()));
             let handles = Arc::new(Mutex::new(HandleMap::default()));
-            let bridge = Arc::new(YadiskFuseBridge::new(index.clone()));
+            let mut bridge = YadiskFuseBridge::new(index.clone());
+            if let Some(limits) = delete_limits {
+                bridge = bridge.with_delete_guard(limits);
+            }
+            let bridge = Arc::new(bridge);
 
             // Background thread: listen for D-Bus state_changed signals and
             // wake any FUSE threads waiting in ensure_downloaded.
@@ -312,6 +394,7 @@ mod app {
                 inodes,
                 handles,
                 downloader: DbusDownloader::new(),
+                prefetcher: Prefetcher::start(),
                 state_notify,
             }
         }
@@ -724,6 +807,18 @@ mod app {
                 reply.error(err);
                 return;
             }
+            if !writable {
+                // Viewers step through a folder file by file; let the daemon
+                // fetch the next ones without holding up this open.
+                let remote = Self::normalize_remote(&path);
+                if self
+                    .rt
+                    .block_on(self.bridge.has_cloud_only_siblings(&remote))
+                    .unwrap_or(true)
+                {
+                    self.prefetcher.request(remote);
+                }
+            }
             let fh = if writable {
                 match self.handles.lock() {
                     Ok(mut handles) => handles.allocate(path),
@@ -1080,12 +1175,15 @@ mod app {
                 reply.error(EISDIR);
                 return;
             }
-            if std::fs::metadata(&cache_path).is_ok() {
-                let _ = std::fs::remove_file(&cache_path);
-            }
             match self.rt.block_on(self.bridge.unlink_or_rmdir(&path)) {
-                Ok(()) => reply.ok(),
+                Ok(()) => {
+                    if std::fs::metadata(&cache_path).is_ok() {
+                        let _ = std::fs::remove_file(&cache_path);
+                    }
+                    reply.ok()
+                }
                 Err(FuseBridgeError::NotFound(_)) => reply.error(ENOENT),
+                Err(FuseBridgeError::DeleteHeld(_)) => reply.error(EPERM),
                 Err(_) => reply.error(EIO),
             }
         }
@@ -1110,12 +1208,15 @@ mod app {
                 reply.error(ENOTDIR);
                 return;
             }
-            if std::fs::metadata(&cache_path).is_ok() {
-                let _ = std::fs::remove_dir_all(&cache_path);
-            }
             match self.rt.block_on(self.bridge.unlink_or_rmdir(&path)) {
-                Ok(()) => reply.ok(),
+                Ok(()) => {
+                    if std::fs::metadata(&cache_path).is_ok() {
+                        let _ = std::fs::remove_dir_all(&cache_path);
+                    }
+                    reply.ok()
+                }
                 Err(FuseBridgeError::NotFound(_)) => reply.error(ENOENT),
+                Err(FuseBridgeError::DeleteHeld(_)) => reply.error(EPERM),
                 Err(_) => reply.error(EIO),
             }
         }
@@ -1151,6 +1252,67 @@ mod app {
                 Err(_) => reply.error(EIO),
             }
         }
+
+        fn copy_file_range(
+            &mut self,
+            _req: &Request<'_>,
+            ino_in: u64,
+            _fh_in: u64,
+            offset_in: i64,
+            ino_out: u64,
+            fh_out: u64,
+            offset_out: i64,
+            len: u64,
+            _flags: u32,
+            reply: ReplyWrite,
+        ) {
+            let Some(from) = self.path_from_ino(ino_in) else {
+                reply.error(ENOENT);
+                return;
+            };
+            let Some(to) = self.path_for_handle(ino_out, fh_out) else {
+                reply.error(ENOENT);
+                return;
+            };
+            let (from_cache, to_cache) = match (self.cache_path(&from), self.cache_path(&to)) {
+                (Ok(from_cache), Ok(to_cache)) => (from_cache, to_cache),
+                _ => {
+                    reply.error(EIO);
+                    return;
+                }
+            };
+            // Only a whole cached file copied into an empty one becomes a
+            // server-side copy. EOPNOTSUPP (unlike ENOSYS) makes the kernel
+            // fall back to read/write for this call only.
+            let source_len = match std::fs::metadata(&from_cache) {
+                Ok(meta) if meta.is_file() => meta.len(),
+                _ => {
+                    reply.error(EOPNOTSUPP);
+                    return;
+                }
+            };
+            let target_empty = std::fs::metadata(&to_cache)
+                .map(|meta| meta.len() == 0)
+                .unwrap_or(true);
+            if offset_in != 0
+                || offset_out != 0
+                || len < source_len
+                || !target_empty
+                || source_len > u32::MAX as u64
+            {
+                reply.error(EOPNOTSUPP);
+                return;
+            }
+            if std::fs::copy(&from_cache, &to_cache).is_err() {
+                reply.error(EIO);
+                return;
+            }
+            match self.rt.block_on(self.bridge.copy(&from, &to)) {
+                Ok(()) => reply.written(source_len as u32),
+                Err(FuseBridgeError::NotFound(_)) => reply.error(ENOENT),
+                Err(_) => reply.error(EIO),
+            }
+        }
     }
 
     pub fn run() -> anyhow::Result<()> {
@@ -1170,7 +1332,7 @@ mod app {
                     .join("yadisk-gtk")
             });
         std::fs::create_dir_all(&mountpoint)?;
-        let fs = YadiskFuseFs::new(rt, index, cache_root);
+        let fs = YadiskFuseFs::new(rt, index, cache_root, delete_limits_from_env());
         let options = vec![
             MountOption::FSName("yadisk-fuse".to_string()),
             MountOption::DefaultPermissions,
@@ -1179,6 +1341,35 @@ mod app {
         Ok(())
     }
 
+    /// The daemon's mass deletion limits, read from the same variables.
+    fn delete_limits_from_env() -> Option<DeleteLimits> {
+        let var = |name: &str| std::env::var(name).ok();
+        let enabled = var("YADISK_MASS_DELETE_GUARD").is_none_or(|value| {
+            matches!(
+                value.trim().to_ascii_lowercase().as_str(),
+                "1" | "true" | "yes" | "on"
+            )
+        });
+        if !enabled {
+            return None;
+        }
+        let number = |name: &str, default: u64| {
+            var(name)
+                .and_then(|value| value.parse::<u64>().ok())
+                .filter(|value| *value > 0)
+                .unwrap_or(default)
+        };
+        let defaults = DeleteLimits::default();
+        Some(DeleteLimits {
+            max_files: number("YADISK_MASS_DELETE_MAX_FILES", defaults.max_files),
+            max_percent: number(
+                "YADISK_MASS_DELETE_MAX_PERCENT",
+                u64::from(defaults.max_percent),
+            )
+            .min(100) as u8,
+        })
+    }
+
     fn parse_mountpoint() -> anyhow::Result<PathBuf> {
         let mut args = std::env::args().skip(1);
         while let Some(arg) = args.next() {
@@ -2,6 +2,8 @@ use std::cell::{Cell, RefCell};
 use std::collections::BTreeSet;
 use std::process::Command;
 use std::rc::Rc;
+use std::sync::Once;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
@@ -23,6 +25,11 @@ use crate::service_control::{
     oauth_credentials_configured, query_daemon_service_status, recent_daemon_journal,
     run_service_action,
 };
+use crate::settings::read_settings_snapshot;
+use crate::sync_client::{
+    ConflictChoice, ConflictEntry, PendingDelete, SyncClient, VersionInfo, watch_conflict_added,
+    watch_pending_deletes_changed,
+};
 use crate::ui_model::UiModel;
 
 const ACTION_AUTH_START: &str = "action-auth-start";
@@ -108,6 +115,9 @@ struct Widgets {
     account_label: gtk4::Label,
     daemon_badge: gtk4::Label,
     daemon_label: gtk4::Label,
+    conflicts_badge: gtk4::Label,
+    conflicts_label: gtk4::Label,
+    conflicts_list: gtk4::Box,
     integration_badge: gtk4::Label,
     integration_label: gtk4::Label,
     integration_commands_box: gtk4::Box,
@@ -197,7 +207,16 @@ fn build_window(app: &libadwaita::Application, start_tab: Option<String>) {
     refresh_button.add_css_class("flat");
     refresh_button.set_tooltip_text(Some(tr("Refresh status").as_str()));
     let widgets_for_refresh = Rc::clone(&widgets);
-    refresh_button.connect_clicked(move |_| refresh_ui_async(&widgets_for_refresh));
+    let signals = watch_sync_signals();
+    // A new window loads everything once, whatever arrived before it.
+    signals.conflicts_changed.store(true, Ordering::SeqCst);
+    signals
+        .pending_deletes_changed
+        .store(true, Ordering::SeqCst);
+    refresh_button.connect_clicked(move |_| {
+        refresh_ui_async(&widgets_for_refresh);
+        signals.conflicts_changed.store(true, Ordering::SeqCst);
+    });
 
     let title_label = gtk4::Label::new(Some(product_name()));
     title_label.add_css_class("app-title");
@@ -243,11 +262,86 @@ fn build_window(app: &libadwaita::Application, start_tab: Option<String>) {
         });
     }
 
+    {
+        // `ConflictAdded` arrives on a D-Bus thread; the flag hands it over to
+        // the GTK main loop, which reloads the list.
+        let widgets_for_conflicts = Rc::clone(&widgets);
+        let weak_window = window.downgrade();
+        glib::timeout_add_local(Duration::from_millis(500), move || {
+            if weak_window.upgrade().is_none() {
+                return glib::ControlFlow::Break;
+            }
+            if signals.conflicts_changed.swap(false, Ordering::SeqCst) {
+                refresh_conflicts_async(&widgets_for_conflicts);
+            }
+            glib::ControlFlow::Continue
+        });
+    }
+
+    {
+        // Same hand-over for `PendingDeletesChanged`; a held mass deletion
+        // is put in front of the user right away.
+        let prompt_open = Rc::new(Cell::new(false));
+        let weak_window = window.downgrade();
+        glib::timeout_add_local(Duration::from_millis(500), move || {
+            let Some(window) = weak_window.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            if !prompt_open.get()
+                && signals
+                    .pending_deletes_changed
+                    .swap(false, Ordering::SeqCst)
+            {
+                let prompt_open = Rc::clone(&prompt_open);
+                spawn_blocking(
+                    || SyncClient::connect().and_then(|client| client.list_pending_deletes()),
+                    move |result| {
+                        if let Ok(pending) = result
+                            && !pending.is_empty()
+                        {
+                            prompt_pending_deletes(&window, &pending, prompt_open);
+                        }
+                    },
+                );
+            }
+            glib::ControlFlow::Continue
+        });
+    }
+
     wire_actions(app, &stack, Rc::clone(&widgets), &window);
     restore_transient_dialog(&window, &stack, Rc::clone(&widgets));
     window.present();
 }
 
+/// Flags raised from the D-Bus signal threads and taken by the open window.
+struct SyncSignals {
+    conflicts_changed: AtomicBool,
+    pending_deletes_changed: AtomicBool,
+}
+
+/// Starts the signal threads with the first window; later windows, such as
+/// the one rebuilt after a language change, share them.
+fn watch_sync_signals() -> &'static SyncSignals {
+    static SIGNALS: SyncSignals = SyncSignals {
+        conflicts_changed: AtomicBool::new(true),
+        pending_deletes_changed: AtomicBool::new(true),
+    };
+    static WATCH: Once = Once::new();
+    WATCH.call_once(|| {
+        std::thread::spawn(|| {
+            watch_conflict_added(|| SIGNALS.conflicts_changed.store(true, Ordering::SeqCst));
+        });
+        std::thread::spawn(|| {
+            watch_pending_deletes_changed(|| {
+                SIGNALS
+                    .pending_deletes_changed
+                    .store(true, Ordering::SeqCst)
+            });
+        });
+    });
+    &SIGNALS
+}
+
 fn build_pages(
     stack: &gtk4::Stack,
     overview_auth_badge: gtk4::Label,
@@ -319,6 +413,33 @@ fn build_pages(
     ));
     stack.add_titled(&sync, Some("sync"), tr("Sync Status").as_str());
 
+    let (conflicts, conflicts_content) = page_shell();
+    conflicts_content.append(&page_heading("dialog-warning-symbolic", "Conflicts"));
+    conflicts_content.append(&page_description(
+        "Files changed both on this computer and in the cloud. Choose which version to keep.",
+    ));
+    let conflicts_card = section_card();
+    let conflicts_header = section_header("Open conflicts");
+    let conflicts_badge = status_badge();
+    conflicts_header.append(&conflicts_badge);
+    conflicts_card.append(&conflicts_header);
+    let conflicts_label = body_label();
+    conflicts_card.append(&conflicts_label);
+    let conflicts_list = gtk4::Box::new(gtk4::Orientation::Vertical, 12);
+    let conflicts_scroller = gtk4::ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .min_content_height(320)
+        .vexpand(true)
+        .child(&conflicts_list)
+        .build();
+    conflicts_card.append(&conflicts_scroller);
+    conflicts_content.append(&conflicts_card);
+    conflicts_content.append(&note_card(
+        "Keep both",
+        "Keep both leaves the local version next to the cloud one under a \"(conflict ...)\" name. Dismiss hides the conflict without changing any files.",
+    ));
+    stack.add_titled(&conflicts, Some("conflicts"), tr("Conflicts").as_str());
+
     let (integrations, integrations_content) = page_shell();
     integrations_content.append(&page_heading("folder-symbolic", "Files Integration"));
     integrations_content.append(&page_description(
@@ -431,6 +552,9 @@ fn build_pages(
         account_label,
         daemon_badge,
         daemon_label,
+        conflicts_badge,
+        conflicts_label,
+        conflicts_list,
         integration_badge,
         integration_label,
         integration_commands_box,
@@ -1678,6 +1802,253 @@ fn refresh_ui_async(widgets: &Rc<Widgets>) {
     });
 }
 
+fn refresh_conflicts_async(widgets: &Rc<Widgets>) {
+    let widgets = Rc::clone(widgets);
+    spawn_blocking(
+        || SyncClient::connect().and_then(|client| client.list_conflicts()),
+        move |result| apply_conflicts(&widgets, result),
+    );
+}
+
+fn apply_conflicts(widgets: &Rc<Widgets>, result: Result<Vec<ConflictEntry>>) {
+    let list = &widgets.conflicts_list;
+    while let Some(child) = list.first_child() {
+        list.remove(&child);
+    }
+    match result {
+        Ok(conflicts) if conflicts.is_empty() => {
+            update_badge(&widgets.conflicts_badge, crate::ui_model::UiStatus::Ready);
+            widgets.conflicts_label.set_text(
+                tr("No conflicts. Files changed on both sides will appear here.").as_str(),
+            );
+        }
+        Ok(conflicts) => {
+            update_badge(
+                &widgets.conflicts_badge,
+                crate::ui_model::UiStatus::NeedsSetup,
+            );
+            widgets
+                .conflicts_badge
+                .set_text(&format!("{}: {}", tr("Open"), conflicts.len()));
+            widgets
+                .conflicts_label
+                .set_text(tr("Compare the versions, then keep one of them or both.").as_str());
+            for conflict in conflicts {
+                list.append(&conflict_row(widgets, &conflict));
+            }
+        }
+        Err(err) => {
+            update_badge(&widgets.conflicts_badge, crate::ui_model::UiStatus::Unknown);
+            widgets
+                .conflicts_label
+                .set_text(&format!("{}: {err}", tr("Conflict list is unavailable")));
+        }
+    }
+}
+
+fn conflict_row(widgets: &Rc<Widgets>, conflict: &ConflictEntry) -> gtk4::Box {
+    let row = gtk4::Box::new(gtk4::Orientation::Vertical, 8);
+    row.add_css_class("conflict-row");
+    let title = gtk4::Label::new(Some(conflict.path.trim_start_matches("disk:")));
+    title.add_css_class("heading");
+    title.set_wrap(true);
+    title.set_halign(gtk4::Align::Start);
+    title.set_xalign(0.0);
+    row.append(&title);
+    let versions = body_label();
+    versions.set_text(&format!(
+        "{}: {}\n{}: {}",
+        tr("Local version"),
+        describe_version(conflict.local),
+        tr("Cloud version"),
+        describe_version(conflict.remote)
+    ));
+    row.append(&versions);
+    if conflict.awaits_decision() {
+        let note = body_label();
+        note.add_css_class("note-text");
+        note.set_text(tr("The local version has not been uploaded yet.").as_str());
+        row.append(&note);
+    }
+
+    let actions = action_row();
+    let btn_compare = gtk4::Button::with_label(tr("Open both").as_str());
+    if conflict.awaits_decision() {
+        // Only the local version is on disk; there is nothing to compare with.
+        btn_compare.set_sensitive(false);
+        btn_compare.set_tooltip_text(Some(
+            tr("The cloud version is not in the sync folder yet.").as_str(),
+        ));
+    }
+    let compare_conflict = conflict.clone();
+    let label_for_compare = widgets.conflicts_label.clone();
+    btn_compare.connect_clicked(move |_| {
+        let conflict = compare_conflict.clone();
+        let label = label_for_compare.clone();
+        spawn_blocking(
+            move || open_conflict_versions(&conflict),
+            move |result: Result<()>| {
+                if let Err(err) = result {
+                    label.set_text(&format!("{}: {err}", tr("Failed to open files")));
+                }
+            },
+        );
+    });
+    actions.append(&btn_compare);
+    for (label, choice) in [
+        ("Keep local", Some(ConflictChoice::KeepLocal)),
+        ("Keep cloud", Some(ConflictChoice::KeepRemote)),
+        ("Keep both", Some(ConflictChoice::KeepBoth)),
+        ("Dismiss", None),
+    ] {
+        let button = gtk4::Button::with_label(tr(label).as_str());
+        if choice == Some(ConflictChoice::KeepLocal) {
+            button.add_css_class("suggested-action");
+        }
+        let id = conflict.id;
+        let widgets = Rc::clone(widgets);
+        button.connect_clicked(move |button| {
+            button.set_sensitive(false);
+            let widgets = Rc::clone(&widgets);
+            spawn_blocking(
+                move || {
+                    let client = SyncClient::connect()?;
+                    match choice {
+                        Some(choice) => client.resolve_conflict(id, choice),
+                        None => client.dismiss_conflict(id),
+                    }
+                },
+                move |result: Result<()>| {
+                    if let Err(err) = result {
+                        widgets
+                            .conflicts_label
+                            .set_text(&format!("{}: {err}", tr("Failed to resolve conflict")));
+                        return;
+                    }
+                    refresh_conflicts_async(&widgets);
+                },
+            );
+        });
+        actions.append(&button);
+    }
+    row.append(&actions);
+    row
+}
+
+/// Asks whether a mass deletion the daemon held back should go through.
+fn prompt_pending_deletes(
+    window: &libadwaita::ApplicationWindow,
+    pending: &[PendingDelete],
+    prompt_open: Rc<Cell<bool>>,
+) {
+    let files: u64 = pending.iter().map(|held| held.files).sum();
+    let mut body = format!(
+        "{}\n\n{}: {files}",
+        tr(
+            "Sync paused the deletion of many files at once. This happens when a disk is unmounted or a folder is wiped by mistake. Delete them everywhere, or keep them and bring them back?"
+        ),
+        tr("Files to delete"),
+    );
+    for held in pending.iter().take(5) {
+        let origin = if held.from_cloud {
+            tr("Removed in the cloud")
+        } else {
+            tr("Removed on this computer")
+        };
+        body.push_str(&format!(
+            "\n{} ({origin}, {})",
+            held.path.trim_start_matches("disk:"),
+            held.files
+        ));
+    }
+    if pending.len() > 5 {
+        body.push_str(&format!("\n{}: {}", tr("Other folders"), pending.len() - 5));
+    }
+
+    let dialog = libadwaita::MessageDialog::builder()
+        .transient_for(window)
+        .heading(tr("Many files are about to be deleted").as_str())
+        .body(body.as_str())
+        .build();
+    dialog.add_response("later", tr("Decide later").as_str());
+    dialog.add_response("delete", tr("Delete").as_str());
+    dialog.add_response("keep", tr("Keep files").as_str());
+    dialog.set_default_response(Some("keep"));
+    dialog.set_close_response("later");
+    dialog.set_response_appearance("delete", libadwaita::ResponseAppearance::Destructive);
+    dialog.set_response_appearance("keep", libadwaita::ResponseAppearance::Suggested);
+    let window_for_response = window.clone();
+    let prompt_open_for_response = Rc::clone(&prompt_open);
+    dialog.connect_response(None, move |dialog, response| {
+        dialog.hide();
+        let prompt_open = Rc::clone(&prompt_open_for_response);
+        let confirm = match response {
+            "delete" => true,
+            "keep" => false,
+            _ => {
+                prompt_open.set(false);
+                return;
+            }
+        };
+        let window = window_for_response.clone();
+        spawn_blocking(
+            move || {
+                let client = SyncClient::connect()?;
+                if confirm {
+                    client.confirm_pending_deletes()
+                } else {
+                    client.reject_pending_deletes()
+                }
+            },
+            move |result: Result<u32>| {
+                prompt_open.set(false);
+                if let Err(err) = result {
+                    show_text_dialog(
+                        &window,
+                        tr("Failed to apply the decision").as_str(),
+                        &err.to_string(),
+                    );
+                }
+            },
+        );
+    });
+    prompt_open.set(true);
+    dialog.present();
+}
+
+fn describe_version(version: Option<VersionInfo>) -> String {
+    let Some(version) = version else {
+        return tr("unknown");
+    };
+    let modified = glib::DateTime::from_unix_local(version.modified)
+        .and_then(|time| time.format("%x %X"))
+        .map(|text| text.to_string())
+        .unwrap_or_else(|_| tr("unknown"));
+    format!("{}, {modified}", glib::format_size(version.size))
+}
+
+fn open_conflict_versions(conflict: &ConflictEntry) -> Result<()> {
+    let settings = read_settings_snapshot();
+    let paths: Vec<_> = conflict
+        .compare_paths(
+            std::path::Path::new(&settings.sync_root),
+            &settings.remote_root,
+        )
+        .into_iter()
+        .filter(|path| path.exists())
+        .collect();
+    if paths.is_empty() {
+        anyhow::bail!("{}", tr("No version of this file is available locally"));
+    }
+    for path in paths {
+        Command::new("xdg-open")
+            .arg(&path)
+            .spawn()
+            .with_context(|| format!("failed to open {}", path.display()))?;
+    }
+    Ok(())
+}
+
 fn localized_bool(value: bool) -> String {
     if value { tr("Yes") } else { tr("No") }
 }
@@ -1913,6 +2284,7 @@ fn install_css() {
          .status-needs { background: alpha(#ffb25c, 0.20); color: @warning_fg_color; }\n\
          .status-error { background: alpha(@error_bg_color, 0.30); color: @error_fg_color; }\n\
          .status-unknown { background: alpha(@window_fg_color, 0.12); color: alpha(@window_fg_color, 0.88); }\n\
+         .conflict-row { padding: 12px 14px; border-radius: 14px; background: alpha(@window_fg_color, 0.03); border: 1px solid alpha(#ffb25c, 0.22); }\n\
          .accent { color: #cf3616; }\n",
     );
     if let Some(display) = gtk4::gdk::Display::default() {
@@ -6,6 +6,8 @@ mod integration_control;
 mod service_control;
 mod settings;
 mod state;
+#[cfg(feature = "gtk-ui")]
+mod sync_client;
 mod ui_model;
 
 use std::io::Write;
@@ -42,6 +44,7 @@ enum CliMode {
     Gtk,
     GtkWelcome,
     GtkSync,
+    GtkConflicts,
     GtkIntegrations,
     GtkSettings,
     GtkDiagnostics,
@@ -72,6 +75,7 @@ where
             "--gtk" => CliMode::Gtk,
             "--tab-welcome" | "--tab-account" => CliMode::GtkWelcome,
             "--tab-sync" => CliMode::GtkSync,
+            "--tab-conflicts" => CliMode::GtkConflicts,
             "--tab-integrations" => CliMode::GtkIntegrations,
             "--tab-settings" => CliMode::GtkSettings,
             "--tab-diagnostics" => CliMode::GtkDiagnostics,
@@ -105,6 +109,7 @@ fn main() -> anyhow::Result<()> {
         CliMode::Gtk
             | CliMode::GtkWelcome
             | CliMode::GtkSync
+            | CliMode::GtkConflicts
             | CliMode::GtkIntegrations
             | CliMode::GtkSettings
             | CliMode::GtkDiagnostics
@@ -112,6 +117,7 @@ fn main() -> anyhow::Result<()> {
         let tab = match mode {
             CliMode::GtkWelcome => Some("welcome".to_string()),
             CliMode::GtkSync => Some("sync".to_string()),
+            CliMode::GtkConflicts => Some("conflicts".to_string()),
             CliMode::GtkIntegrations => Some("integrations".to_string()),
             CliMode::GtkSettings => Some("settings".to_string()),
             CliMode::GtkDiagnostics => Some("diagnostics".to_string()),
@@ -172,6 +178,7 @@ fn main() -> anyhow::Result<()> {
         CliMode::Gtk
         | CliMode::GtkWelcome
         | CliMode::GtkSync
+        | CliMode::GtkConflicts
         | CliMode::GtkIntegrations
         | CliMode::GtkSettings
         | CliMode::GtkDiagnostics => unreachable!("gtk mode returns early"),
@@ -306,7 +313,7 @@ fn run_post_auth_steps_cli() {
 
 fn print_help() {
     println!(
-        "Usage: yadisk-ui [--status | --start-auth | --cancel-auth | --logout | --start-daemon | --stop-daemon | --restart-daemon | --enable-autostart | --disable-autostart | --check-integrations | --install-integrations-guided | --install-integrations-auto | --show-settings | --diagnostics | --gtk | --tab-welcome | --tab-sync | --tab-integrations | --tab-settings | --tab-diagnostics]\n(note: in default build with no flags, GTK window starts)"
+        "Usage: yadisk-ui [--status | --start-auth | --cancel-auth | --logout | --start-daemon | --stop-daemon | --restart-daemon | --enable-autostart | --disable-autostart | --check-integrations | --install-integrations-guided | --install-integrations-auto | --show-settings | --diagnostics | --gtk | --tab-welcome | --tab-sync | --tab-conflicts | --tab-integrations | --tab-settings | --tab-diagnostics]\n(note: in default build with no flags, GTK window starts)"
     );
 }
 
@@ -401,4 +408,11 @@ mod tests {
         let mode = parse_cli_mode(vec!["yadisk-ui".to_string(), "--tab-sync".to_string()]).unwrap();
         assert_eq!(mode, CliMode::GtkSync);
     }
+
+    #[test]
+    fn parses_tab_conflicts_mode() {
+        let mode =
+            parse_cli_mode(vec!["yadisk-ui".to_string(), "--tab-conflicts".to_string()]).unwrap();
+        assert_eq!(mode, CliMode::GtkConflicts);
+    }
 }
@@ -4,6 +4,7 @@ use crate::ui_model::{UiModel, UiStatus};
 pub enum Route {
     Welcome,
     SyncStatus,
+    Conflicts,
     Integrations,
     Settings,
     Diagnostics,
@@ -35,10 +36,11 @@ impl Default for AppState {
     }
 }
 
-pub fn sidebar_routes() -> [Route; 5] {
+pub fn sidebar_routes() -> [Route; 6] {
     [
         Route::Welcome,
         Route::SyncStatus,
+        Route::Conflicts,
         Route::Integrations,
         Route::Settings,
         Route::Diagnostics,
@@ -104,6 +106,7 @@ mod tests {
             [
                 Route::Welcome,
                 Route::SyncStatus,
+                Route::Conflicts,
                 Route::Integrations,
                 Route::Settings,
                 Route::Diagnostics,
@@ -0,0 +1,338 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use yadisk_integrations::ids::{DBUS_INTERFACE_SYNC, DBUS_NAME_SYNC, DBUS_OBJECT_PATH_SYNC};
+use zbus::blocking::{Connection, Proxy, connection::Builder as ConnectionBuilder};
+
+const SYNC_ACTION_TIMEOUT: Duration = Duration::from_secs(15);
+const SIGNAL_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+type ConflictDetailsRow = (u64, String, String, String, i64, i64, i64, i64);
+type PendingDeleteRow = (String, String, i64, i64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionInfo {
+    pub size: u64,
+    pub modified: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictEntry {
+    pub id: u64,
+    pub path: String,
+    pub renamed_local: String,
+    pub reason: String,
+    pub local: Option<VersionInfo>,
+    pub remote: Option<VersionInfo>,
+}
+
+impl ConflictEntry {
+    fn from_row(row: ConflictDetailsRow) -> Self {
+        let (
+            id,
+            path,
+            renamed_local,
+            reason,
+            local_size,
+            local_modified,
+            remote_size,
+            remote_modified,
+        ) = row;
+        Self {
+            id,
+            path,
+            renamed_local,
+            reason,
+            local: version_from_wire(local_size, local_modified),
+            remote: version_from_wire(remote_size, remote_modified),
+        }
+    }
+
+    /// Conflicts left to the user still hold the local version at `path`;
+    /// the cloud version has not been downloaded next to it.
+    pub fn awaits_decision(&self) -> bool {
+        self.renamed_local == self.path
+    }
+
+    /// Files in the sync folder to open side by side: the cloud version at
+    /// `path` and the local one under its conflict name.
+    pub fn compare_paths(&self, sync_root: &Path, remote_root: &str) -> Vec<PathBuf> {
+        let mut paths = vec![self.path.as_str()];
+        if !self.awaits_decision() {
+            paths.push(self.renamed_local.as_str());
+        }
+        paths
+            .into_iter()
+            .filter_map(|path| local_path_for(sync_root, remote_root, path))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictChoice {
+    KeepLocal,
+    KeepRemote,
+    KeepBoth,
+}
+
+impl ConflictChoice {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ConflictChoice::KeepLocal => "keep-local",
+            ConflictChoice::KeepRemote => "keep-remote",
+            ConflictChoice::KeepBoth => "keep-both",
+        }
+    }
+}
+
+/// A deletion the daemon held back because it removes too much at once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingDelete {
+    pub path: String,
+    /// Removed in the cloud rather than on this computer.
+    pub from_cloud: bool,
+    pub files: u64,
+}
+
+impl PendingDelete {
+    fn from_row(row: PendingDeleteRow) -> Self {
+        let (path, origin, files, _held_at) = row;
+        Self {
+            path,
+            from_cloud: origin == "cloud",
+            files: u64::try_from(files).unwrap_or(0),
+        }
+    }
+}
+
+/// A session-bus connection to the daemon's Sync1 interface, opened once and
+/// shared by every call made through the client.
+pub struct SyncClient {
+    proxy: Proxy<'static>,
+}
+
+impl SyncClient {
+    pub fn connect() -> Result<Self> {
+        let connection = connect_sync(SYNC_ACTION_TIMEOUT)?;
+        let proxy = sync_proxy(&connection)?;
+        Ok(Self { proxy })
+    }
+
+    pub fn list_conflicts(&self) -> Result<Vec<ConflictEntry>> {
+        self.with_proxy(|proxy| {
+            let rows: Vec<ConflictDetailsRow> = proxy
+                .call("ListConflictDetails", &())
+                .context("ListConflictDetails failed")?;
+            Ok(rows.into_iter().map(ConflictEntry::from_row).collect())
+        })
+    }
+
+    pub fn resolve_conflict(&self, id: u64, choice: ConflictChoice) -> Result<()> {
+        self.with_proxy(|proxy| {
+            proxy
+                .call::<_, _, ()>("ResolveConflict", &(id, choice.as_str()))
+                .context("ResolveConflict failed")
+        })
+    }
+
+    pub fn dismiss_conflict(&self, id: u64) -> Result<()> {
+        self.with_proxy(|proxy| {
+            proxy
+                .call::<_, _, ()>("DismissConflict", &(id,))
+                .context("DismissConflict failed")
+        })
+    }
+
+    pub fn list_pending_deletes(&self) -> Result<Vec<PendingDelete>> {
+        self.with_proxy(|proxy| {
+            let rows: Vec<PendingDeleteRow> = proxy
+                .call("ListPendingDeletes", &())
+                .context("ListPendingDeletes failed")?;
+            Ok(rows.into_iter().map(PendingDelete::from_row).collect())
+        })
+    }
+
+    pub fn confirm_pending_deletes(&self) -> Result<u32> {
+        self.with_proxy(|proxy| {
+            proxy
+                .call("ConfirmPendingDeletes", &())
+                .context("ConfirmPendingDeletes failed")
+        })
+    }
+
+    pub fn reject_pending_deletes(&self) -> Result<u32> {
+        self.with_proxy(|proxy| {
+            proxy
+                .call("RejectPendingDeletes", &())
+                .context("RejectPendingDeletes failed")
+        })
+    }
+
+    fn with_proxy<T>(&self, f: impl FnOnce(&Proxy<'static>) -> Result<T>) -> Result<T> {
+        f(&self.proxy)
+    }
+}
+
+/// Blocks the calling thread and calls `on_added` for every `ConflictAdded`
+/// signal, reconnecting while the daemon is not running.
+pub fn watch_conflict_added(on_added: impl FnMut()) {
+    watch_signal("ConflictAdded", on_added);
+}
+
+/// Like [`watch_conflict_added`], for `PendingDeletesChanged`.
+pub fn watch_pending_deletes_changed(on_changed: impl FnMut()) {
+    watch_signal("PendingDeletesChanged", on_changed);
+}
+
+fn watch_signal(name: &str, mut on_signal: impl FnMut()) {
+    loop {
+        let result = connect_sync(SYNC_ACTION_TIMEOUT).and_then(|connection| {
+            let proxy = sync_proxy(&connection)?;
+            let signals = proxy
+                .receive_signal(name)
+                .with_context(|| format!("failed to subscribe to {name}"))?;
+            // The daemon may have changed state while nobody listened.
+            on_signal();
+            for _ in signals {
+                on_signal();
+            }
+            Ok(())
+        });
+        if let Err(err) = result {
+            eprintln!("[yadisk-ui] Sync1 {name} watch failed: {err}");
+        }
+        std::thread::sleep(SIGNAL_RECONNECT_DELAY);
+    }
+}
+
+fn connect_sync(timeout: Duration) -> Result<Connection> {
+    ConnectionBuilder::session()
+        .context("failed to open session D-Bus builder for Sync1")?
+        .method_timeout(timeout)
+        .build()
+        .context("failed to connect to session D-Bus for Sync1")
+}
+
+fn sync_proxy(connection: &Connection) -> Result<Proxy<'static>> {
+    Proxy::new(
+        connection,
+        DBUS_NAME_SYNC,
+        DBUS_OBJECT_PATH_SYNC,
+        DBUS_INTERFACE_SYNC,
+    )
+    .context("failed to create Sync1 proxy")
+}
+
+fn version_from_wire(size: i64, modified: i64) -> Option<VersionInfo> {
+    Some(VersionInfo {
+        size: u64::try_from(size).ok()?,
+        modified: (modified >= 0).then_some(modified)?,
+    })
+}
+
+fn local_path_for(sync_root: &Path, remote_root: &str, path: &str) -> Option<PathBuf> {
+    let normalize = |value: &str| {
+        value
+            .strip_prefix("disk:")
+            .unwrap_or(value)
+            .trim_matches('/')
+            .to_string()
+    };
+    let root = normalize(remote_root);
+    let path = normalize(path);
+    let relative = if root.is_empty() || path == root {
+        path.strip_prefix(root.as_str()).unwrap_or_default()
+    } else {
+        path.strip_prefix(format!("{root}/").as_str())?
+    };
+    if relative.split('/').any(|segment| segment == "..") {
+        return None;
+    }
+    Some(sync_root.join(relative))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, renamed_local: &str) -> ConflictEntry {
+        ConflictEntry::from_row((
+            7,
+            path.to_string(),
+            renamed_local.to_string(),
+            "both-changed".to_string(),
+            12,
+            1_700_000_000,
+            -1,
+            -1,
+        ))
+    }
+
+    #[test]
+    fn unknown_versions_are_decoded_as_missing() {
+        let conflict = entry("/Docs/A.txt", "/Docs/A (conflict 1-x).txt");
+        assert_eq!(
+            conflict.local,
+            Some(VersionInfo {
+                size: 12,
+                modified: 1_700_000_000
+            })
+        );
+        assert_eq!(conflict.remote, None);
+    }
+
+    #[test]
+    fn compare_paths_cover_both_kept_copies() {
+        let conflict = entry("disk:/Docs/A.txt", "disk:/Docs/A (conflict 1-x).txt");
+        assert_eq!(
+            conflict.compare_paths(Path::new("/home/u/Yandex Disk"), "disk:/"),
+            vec![
+                PathBuf::from("/home/u/Yandex Disk/Docs/A.txt"),
+                PathBuf::from("/home/u/Yandex Disk/Docs/A (conflict 1-x).txt"),
+            ]
+        );
+        let undecided = entry("/Docs/A.txt", "/Docs/A.txt");
+        assert!(undecided.awaits_decision());
+        assert_eq!(
+            undecided.compare_paths(Path::new("/sync"), "disk:/"),
+            vec![PathBuf::from("/sync/Docs/A.txt")]
+        );
+    }
+
+    #[test]
+    fn compare_paths_respect_remote_root() {
+        let conflict = entry("disk:/Work/A.txt", "disk:/Work/A.txt");
+        assert_eq!(
+            conflict.compare_paths(Path::new("/sync"), "disk:/Work"),
+            vec![PathBuf::from("/sync/A.txt")]
+        );
+        assert!(
+            conflict
+                .compare_paths(Path::new("/sync"), "disk:/Other")
+                .is_empty()
+        );
+        assert!(
+            entry("/Workshop/A.txt", "/Workshop/A.txt")
+                .compare_paths(Path::new("/sync"), "disk:/Work")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn pending_delete_rows_are_decoded() {
+        let held = PendingDelete::from_row(("disk:/Photos".into(), "cloud".into(), 1200, 0));
+        assert!(held.from_cloud);
+        assert_eq!(held.files, 1200);
+        let local = PendingDelete::from_row(("/Docs".into(), "local".into(), -1, 0));
+        assert!(!local.from_cloud);
+        assert_eq!(local.files, 0);
+    }
+
+    #[test]
+    fn choices_use_daemon_names() {
+        assert_eq!(ConflictChoice::KeepLocal.as_str(), "keep-local");
+        assert_eq!(ConflictChoice::KeepRemote.as_str(), "keep-remote");
+        assert_eq!(ConflictChoice::KeepBoth.as_str(), "keep-both");
+    }
+}
@@ -101,7 +101,7 @@ fn map_auth_control_status(status: &str) -> UiStatus {
 
 fn map_daemon_control_status(status: &str) -> UiStatus {
     match status {
-        "running" | "busy" => UiStatus::Ready,
+        "running" | "busy" | "paused" => UiStatus::Ready,
         "offline" | "error" | "failed" => UiStatus::Error,
         "starting" | "stopping" | "pending" | "inactive" | "stopped" | "needs_setup" => {
             UiStatus::NeedsSetup
@@ -203,6 +203,7 @@ fn localize_state_label(state: &str) -> String {
         "cancelled" => tr("Cancelled"),
         "running" => tr("Running"),
         "busy" => tr("Busy"),
+        "paused" => tr("Paused"),
         "offline" => tr("Offline"),
         "starting" => tr("Starting"),
         "stopping" => tr("Stopping"),
@@ -285,6 +286,7 @@ mod tests {
     fn maps_daemon_control_status_values() {
         assert_eq!(map_daemon_control_status("running"), UiStatus::Ready);
         assert_eq!(map_daemon_control_status("busy"), UiStatus::Ready);
+        assert_eq!(map_daemon_control_status("paused"), UiStatus::Ready);
         assert_eq!(map_daemon_control_status("offline"), UiStatus::Error);
         assert_eq!(map_daemon_control_status("inactive"), UiStatus::NeedsSetup);
         assert_eq!(map_daemon_control_status("failed"), UiStatus::Error);
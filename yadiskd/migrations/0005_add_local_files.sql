@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS local_files (
+    item_id INTEGER PRIMARY KEY REFERENCES items(id) ON DELETE CASCADE,
+    device INTEGER NOT NULL,
+    inode INTEGER NOT NULL,
+    size INTEGER NOT NULL,
+    modified INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_local_files_inode ON local_files(device, inode);
@@ -0,0 +1,4 @@
+ALTER TABLE conflicts ADD COLUMN status TEXT NOT NULL DEFAULT 'open';
+ALTER TABLE conflicts ADD COLUMN resolved_at INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_conflicts_status ON conflicts(status);
@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS failed_ops (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    path TEXT NOT NULL,
+    payload TEXT,
+    priority INTEGER NOT NULL DEFAULT 0,
+    attempts INTEGER NOT NULL,
+    last_error TEXT NOT NULL,
+    first_failed_at INTEGER NOT NULL,
+    last_failed_at INTEGER NOT NULL,
+    UNIQUE(kind, path)
+);
+
+CREATE INDEX IF NOT EXISTS idx_failed_ops_last_failed_at ON failed_ops(last_failed_at);
@@ -0,0 +1,4 @@
+ALTER TABLE ops_queue ADD COLUMN depends_on_kind TEXT;
+ALTER TABLE ops_queue ADD COLUMN depends_on_path TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_ops_queue_depends_on ON ops_queue(depends_on_kind, depends_on_path);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS ops_journal (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    path TEXT NOT NULL,
+    payload TEXT,
+    attempt INTEGER NOT NULL,
+    priority INTEGER NOT NULL DEFAULT 0,
+    started_at INTEGER NOT NULL,
+    UNIQUE(kind, path)
+);
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS trashed_items (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    path TEXT NOT NULL,
+    item_type TEXT,
+    resource_id TEXT,
+    deleted_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_trashed_items_path ON trashed_items(path);
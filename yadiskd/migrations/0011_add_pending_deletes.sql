@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS pending_deletes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    path TEXT NOT NULL UNIQUE,
+    origin TEXT NOT NULL,
+    files INTEGER NOT NULL,
+    held_at INTEGER NOT NULL
+);
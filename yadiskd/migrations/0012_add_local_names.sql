@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS local_names (
+    item_id INTEGER PRIMARY KEY REFERENCES items(id) ON DELETE CASCADE,
+    local_name TEXT NOT NULL
+);
@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS symlinks (
+    item_id INTEGER PRIMARY KEY REFERENCES items(id) ON DELETE CASCADE,
+    target TEXT NOT NULL
+);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS pin_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    pattern TEXT NOT NULL,
+    max_size INTEGER,
+    max_age_secs INTEGER,
+    created_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS auto_pins (
+    item_id INTEGER PRIMARY KEY REFERENCES items(id) ON DELETE CASCADE
+);
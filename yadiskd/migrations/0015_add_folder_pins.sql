@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS folder_pins (
+    item_id INTEGER PRIMARY KEY REFERENCES items(id) ON DELETE CASCADE,
+    pinned INTEGER NOT NULL
+);
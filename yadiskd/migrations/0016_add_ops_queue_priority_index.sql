@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_ops_queue_dequeue;
+
+CREATE INDEX IF NOT EXISTS idx_ops_queue_priority ON ops_queue(priority DESC, id ASC);
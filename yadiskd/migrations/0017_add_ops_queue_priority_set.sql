@@ -0,0 +1,5 @@
+ALTER TABLE ops_queue ADD COLUMN priority_set INTEGER NOT NULL DEFAULT 0;
+
+-- Claimed operations now keep their queue id in the journal; move older
+-- entries out of the way of those ids.
+UPDATE ops_journal SET id = -id;
@@ -0,0 +1,5 @@
+UPDATE items
+SET hash = lower(hash),
+    last_synced_hash = lower(last_synced_hash);
+
+CREATE INDEX IF NOT EXISTS idx_items_hash ON items(hash);
@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS local_baselines (
+    item_id INTEGER PRIMARY KEY REFERENCES items(id) ON DELETE CASCADE,
+    size INTEGER NOT NULL,
+    modified INTEGER NOT NULL
+);
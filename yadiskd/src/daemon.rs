@@ -2,7 +2,7 @@ use std::collections::{HashMap, HashSet};
 use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::Context;
 use md5::Context as Md5Context;
@@ -18,10 +18,25 @@ use zbus::connection::Builder as ConnectionBuilder;
 use zbus::object_server::SignalEmitter;
 
 use crate::dbus_api::{ControlDbusService, SyncDbusService};
+use crate::pause::{PauseState, watch_power_and_network};
 use crate::storage::TokenStorage;
+use crate::sync::conflict::{ConflictPolicies, ConflictPolicy};
+use crate::sync::delete_guard::{DeleteLimits, is_within};
 use crate::sync::engine::{EngineError, SyncEngine};
-use crate::sync::index::{FileState, IndexStore};
+use crate::sync::eviction::{EvictionCandidate, EvictionPolicy, free_space, parse_size};
+use crate::sync::index::{
+    DeleteOrigin, FileState, IndexStore, LocalBaseline, LocalFileIdentity, QueueSummary,
+};
 use crate::sync::local_watcher::{LocalEvent, start_notify_watcher};
+use crate::sync::merge::{DEFAULT_TEXT_MERGE_MAX_BYTES, TextMerge};
+use crate::sync::move_detect::{MoveDetector, identity_from_metadata};
+use crate::sync::paths::LocalNames;
+use crate::sync::prefetch::PrefetchPolicy;
+use crate::sync::queue::{OpFilter, OperationKind};
+use crate::sync::reconcile::{BaselineEntry, FingerprintCache, reconcile_local_tree};
+use crate::sync::settle::WriteSettle;
+use crate::sync::symlinks::SymlinkPolicy;
+use crate::sync::watch_health::WatchHealth;
 use crate::token_provider::TokenProvider;
 use crate::tray::{TraySyncState, start_status_tray};
 use yadisk_integrations::preferences::{load_ui_preferences, resolve_effective_language};
@@ -30,9 +45,15 @@ pub(crate) const DEFAULT_SYNC_DIR_NAME: &str = "Yandex Disk";
 const DEFAULT_REMOTE_ROOT: &str = "disk:/";
 const DEFAULT_CLOUD_POLL_SECS: u64 = 15;
 const DEFAULT_WORKER_LOOP_MS: u64 = 500;
+const DEFAULT_QUEUE_WORKERS: u64 = 4;
 const DEFAULT_EVICTION_SECS: u64 = 60;
 const DEFAULT_CACHE_MAX_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+const DEFAULT_RECONCILE_SECS: u64 = 300;
+const DEFAULT_WRITE_SETTLE_SECS: u64 = 3;
+const DEFAULT_WRITE_SETTLE_MAX_SECS: u64 = 600;
 const SHUTDOWN_TASK_TIMEOUT: Duration = Duration::from_secs(5);
+const WATCH_RESCAN_SETTLE: Duration = Duration::from_secs(2);
+const LOCAL_MOVE_PAIR_WINDOW: Duration = Duration::from_secs(2);
 
 #[derive(Clone, Debug)]
 pub struct DaemonConfig {
@@ -41,9 +62,25 @@ pub struct DaemonConfig {
     pub remote_root: String,
     pub cloud_poll_interval: Duration,
     pub worker_interval: Duration,
+    pub queue_workers: usize,
     pub eviction_interval: Duration,
-    pub cache_max_bytes: u64,
+    pub reconcile_interval: Duration,
+    pub write_settle: Duration,
+    pub write_settle_max: Duration,
+    pub conflict_policies: ConflictPolicies,
+    pub text_merge: Option<TextMerge>,
+    pub eviction: EvictionPolicy,
     pub enable_local_watcher: bool,
+    pub pause_on_metered: bool,
+    pub pause_on_battery: bool,
+    /// Delete for good instead of moving deleted files to the Yandex trash.
+    pub delete_permanently: bool,
+    /// Mass deletions above these limits wait for confirmation; `None`
+    /// turns the guard off.
+    pub delete_limits: Option<DeleteLimits>,
+    pub symlink_policy: SymlinkPolicy,
+    /// Read-ahead after a file is opened through FUSE; `None` turns it off.
+    pub prefetch: Option<PrefetchPolicy>,
 }
 
 impl DaemonConfig {
@@ -64,10 +101,95 @@ impl DaemonConfig {
             "YADISK_WORKER_LOOP_MS",
             DEFAULT_WORKER_LOOP_MS,
         ));
+        let queue_workers = read_u64_env("YADISK_QUEUE_WORKERS", DEFAULT_QUEUE_WORKERS) as usize;
         let eviction_interval =
             Duration::from_secs(read_u64_env("YADISK_EVICTION_SECS", DEFAULT_EVICTION_SECS));
-        let cache_max_bytes = read_u64_env("YADISK_CACHE_MAX_BYTES", DEFAULT_CACHE_MAX_BYTES);
+        let reconcile_interval = Duration::from_secs(read_u64_env(
+            "YADISK_RECONCILE_SECS",
+            DEFAULT_RECONCILE_SECS,
+        ));
+        let write_settle = Duration::from_secs(read_u64_env(
+            "YADISK_WRITE_SETTLE_SECS",
+            DEFAULT_WRITE_SETTLE_SECS,
+        ));
+        let write_settle_max = Duration::from_secs(read_u64_env(
+            "YADISK_WRITE_SETTLE_MAX_SECS",
+            DEFAULT_WRITE_SETTLE_MAX_SECS,
+        ));
+        let conflict_policy = match std::env::var("YADISK_CONFLICT_POLICY") {
+            Ok(value) if !value.trim().is_empty() => value
+                .parse::<ConflictPolicy>()
+                .map_err(anyhow::Error::msg)
+                .context("invalid YADISK_CONFLICT_POLICY")?,
+            _ => ConflictPolicy::default(),
+        };
+        let conflict_policies = ConflictPolicies::new(conflict_policy)
+            .parse_subtrees(&std::env::var("YADISK_CONFLICT_POLICY_SUBTREES").unwrap_or_default())
+            .map_err(anyhow::Error::msg)
+            .context("invalid YADISK_CONFLICT_POLICY_SUBTREES")?;
+        let text_merge = read_bool_env("YADISK_TEXT_MERGE", true).then(|| {
+            TextMerge::new(default_merge_base_dir())
+                .with_extensions(&std::env::var("YADISK_TEXT_MERGE_EXTENSIONS").unwrap_or_default())
+                .with_max_bytes(read_u64_env(
+                    "YADISK_TEXT_MERGE_MAX_BYTES",
+                    DEFAULT_TEXT_MERGE_MAX_BYTES,
+                ))
+        });
+        let mut eviction = EvictionPolicy::new(read_u64_env(
+            "YADISK_CACHE_MAX_BYTES",
+            DEFAULT_CACHE_MAX_BYTES,
+        ))
+        .parse_folder_quotas(&std::env::var("YADISK_CACHE_FOLDER_QUOTAS").unwrap_or_default())
+        .map_err(anyhow::Error::msg)
+        .context("invalid YADISK_CACHE_FOLDER_QUOTAS")?;
+        let max_idle_days = read_u64_env("YADISK_CACHE_MAX_IDLE_DAYS", 0);
+        if max_idle_days > 0 {
+            eviction = eviction.with_max_idle(Duration::from_secs(max_idle_days * 24 * 60 * 60));
+        }
+        if let Ok(value) = std::env::var("YADISK_CACHE_MIN_FREE")
+            && !value.trim().is_empty()
+        {
+            let min_free = parse_size(&value).context("invalid YADISK_CACHE_MIN_FREE")?;
+            eviction = eviction.with_min_free_bytes(min_free);
+        }
         let enable_local_watcher = read_bool_env("YADISK_ENABLE_LOCAL_WATCHER", true);
+        let pause_on_metered = read_bool_env("YADISK_PAUSE_ON_METERED", false);
+        let pause_on_battery = read_bool_env("YADISK_PAUSE_ON_BATTERY", false);
+        let delete_permanently = read_bool_env("YADISK_DELETE_PERMANENTLY", false);
+        let delete_limits = read_bool_env("YADISK_MASS_DELETE_GUARD", true).then(|| {
+            let defaults = DeleteLimits::default();
+            DeleteLimits {
+                max_files: read_u64_env("YADISK_MASS_DELETE_MAX_FILES", defaults.max_files),
+                max_percent: read_u64_env(
+                    "YADISK_MASS_DELETE_MAX_PERCENT",
+                    u64::from(defaults.max_percent),
+                )
+                .min(100) as u8,
+            }
+        });
+        let symlink_policy = match std::env::var("YADISK_SYMLINKS") {
+            Ok(value) if !value.trim().is_empty() => value
+                .parse::<SymlinkPolicy>()
+                .map_err(anyhow::Error::msg)
+                .context("invalid YADISK_SYMLINKS")?,
+            _ => SymlinkPolicy::default(),
+        };
+        let prefetch = if read_bool_env("YADISK_PREFETCH", true) {
+            let defaults = PrefetchPolicy::default();
+            let max_size = match std::env::var("YADISK_PREFETCH_MAX_SIZE") {
+                Ok(value) if !value.trim().is_empty() => {
+                    parse_size(&value).context("invalid YADISK_PREFETCH_MAX_SIZE")?
+                }
+                _ => defaults.max_size,
+            };
+            Some(PrefetchPolicy {
+                siblings: read_u64_env("YADISK_PREFETCH_SIBLINGS", defaults.siblings as u64)
+                    as usize,
+                max_size,
+            })
+        } else {
+            None
+        };
 
         Ok(Self {
             sync_root,
@@ -75,9 +197,21 @@ impl DaemonConfig {
             remote_root,
             cloud_poll_interval,
             worker_interval,
+            queue_workers,
             eviction_interval,
-            cache_max_bytes,
+            reconcile_interval,
+            write_settle,
+            write_settle_max,
+            conflict_policies,
+            text_merge,
+            eviction,
             enable_local_watcher,
+            pause_on_metered,
+            pause_on_battery,
+            delete_permanently,
+            delete_limits,
+            symlink_policy,
+            prefetch,
         })
     }
 }
@@ -101,6 +235,7 @@ pub struct DaemonRuntime {
     config: DaemonConfig,
     engine: Arc<SyncEngine>,
     auth_ready: Arc<AtomicBool>,
+    pause: Arc<PauseState>,
 }
 
 impl DaemonRuntime {
@@ -122,15 +257,43 @@ impl DaemonRuntime {
         let index = IndexStore::new_default()
             .await
             .context("failed to initialize index store")?;
-        let engine = Arc::new(
-            SyncEngine::new(client, index, config.cache_root.clone())
-                .with_token_provider(token_provider),
-        );
+        let mut engine = SyncEngine::new(client, index, config.cache_root.clone())
+            .with_token_provider(token_provider)
+            .with_conflict_policies(config.conflict_policies.clone())
+            .with_permanent_delete(config.delete_permanently)
+            .with_symlink_policy(config.symlink_policy)
+            .with_sync_root(config.sync_root.clone());
+        if let Some(text_merge) = &config.text_merge {
+            engine = engine.with_text_merge(text_merge.clone());
+        }
+        if let Some(limits) = config.delete_limits {
+            engine = engine.with_delete_guard(limits);
+        }
+        if let Some(prefetch) = config.prefetch {
+            engine = engine.with_prefetch(prefetch);
+        }
+        if !config.write_settle.is_zero() {
+            engine = engine.with_write_settle(WriteSettle {
+                sync_root: config.sync_root.clone(),
+                quiet: config.write_settle,
+                max_wait: config.write_settle_max,
+            });
+        }
+        match engine.recover_interrupted_ops().await {
+            Ok(report) if report.requeued > 0 || report.reset > 0 => eprintln!(
+                "[yadiskd] recovered after unclean shutdown: requeued={} reset={}",
+                report.requeued, report.reset
+            ),
+            Ok(_) => {}
+            Err(err) => eprintln!("[yadiskd] warning: failed to recover interrupted ops: {err}"),
+        }
+        let engine = Arc::new(engine);
 
         Ok(Self {
             config,
             engine,
             auth_ready,
+            pause: Arc::new(PauseState::default()),
         })
     }
 
@@ -163,7 +326,8 @@ impl DaemonRuntime {
                 ControlDbusService::with_engine_and_status(
                     Arc::clone(&self.engine),
                     Arc::clone(&daemon_status),
-                ),
+                )
+                .with_pause(Arc::clone(&self.pause)),
             )?
             .build()
             .await
@@ -172,7 +336,7 @@ impl DaemonRuntime {
         let (quit_tx, mut quit_rx) = mpsc::unbounded_channel::<()>();
         let (tray_state_tx, mut tray_state_rx) = mpsc::unbounded_channel::<TraySyncState>();
         let shutdown = CancellationToken::new();
-        let tray_controller = match start_status_tray(quit_tx.clone()) {
+        let tray_controller = match start_status_tray(quit_tx.clone(), Arc::clone(&self.pause)) {
             Ok(controller) => controller,
             Err(err) => {
                 eprintln!("[yadiskd] warning: failed to start status tray: {err}");
@@ -187,6 +351,12 @@ impl DaemonRuntime {
             })
         });
         let _ = tray_state_tx.send(TraySyncState::Normal);
+        let pause_monitor_handle = tokio::spawn(watch_power_and_network(
+            Arc::clone(&self.pause),
+            self.config.pause_on_metered,
+            self.config.pause_on_battery,
+            shutdown.child_token(),
+        ));
         let local_events_enabled = Arc::new(AtomicBool::new(false));
         let sync_root_available = Arc::new(AtomicBool::new(
             is_sync_root_available(&self.config.sync_root).await,
@@ -196,6 +366,10 @@ impl DaemonRuntime {
         let cloud_sync_error = Arc::new(AtomicBool::new(false));
         let cloud_space_low = Arc::new(AtomicBool::new(false));
         let network_available = Arc::new(AtomicBool::new(true));
+        // Cloud polling waits for the first local reconciliation pass so that
+        // offline edits are compared against the baseline from the last run.
+        let local_reconcile_ready = Arc::new(AtomicBool::new(!self.config.enable_local_watcher));
+        let watch_health = Arc::new(WatchHealth::new(self.config.sync_root.clone()));
         if !sync_root_available.load(Ordering::SeqCst) {
             eprintln!(
                 "[yadiskd] sync root is unavailable: {}",
@@ -208,6 +382,7 @@ impl DaemonRuntime {
             let watcher_sync_root = self.config.sync_root.clone();
             let sync_root_available_watcher = Arc::clone(&sync_root_available);
             let sync_root_generation_watcher = Arc::clone(&sync_root_generation);
+            let watch_health_watcher = Arc::clone(&watch_health);
             let shutdown_watcher = shutdown.child_token();
             Some(tokio::spawn(async move {
                 let mut watcher: Option<(
@@ -234,8 +409,16 @@ impl DaemonRuntime {
                         continue;
                     }
                     if watcher.is_none() {
-                        match start_notify_watcher(&watcher_sync_root) {
+                        match start_notify_watcher(
+                            &watcher_sync_root,
+                            Arc::clone(&watch_health_watcher),
+                        ) {
                             Ok(bundle) => {
+                                if watch_health_watcher.watch_limit_reached() {
+                                    eprintln!(
+                                        "[yadiskd] warning: inotify watch limit reached, relying on rescans for unwatched folders"
+                                    );
+                                }
                                 watcher = Some(bundle);
                                 warned = false;
                             }
@@ -291,12 +474,20 @@ impl DaemonRuntime {
         let cloud_space_low_cloud = Arc::clone(&cloud_space_low);
         let network_available_cloud = Arc::clone(&network_available);
         let auth_ready_cloud = Arc::clone(&self.auth_ready);
+        let local_reconcile_ready_cloud = Arc::clone(&local_reconcile_ready);
+        let pause_cloud = Arc::clone(&self.pause);
         let shutdown_cloud = shutdown.child_token();
         let cloud_handle = tokio::spawn(async move {
             loop {
                 if shutdown_cloud.is_cancelled() {
                     break;
                 }
+                if pause_cloud.is_paused() {
+                    if sleep_or_shutdown(&shutdown_cloud, Duration::from_secs(1)).await {
+                        break;
+                    }
+                    continue;
+                }
                 if !auth_ready_cloud.load(Ordering::SeqCst)
                     || !sync_root_available_cloud.load(Ordering::SeqCst)
                 {
@@ -305,6 +496,12 @@ impl DaemonRuntime {
                     }
                     continue;
                 }
+                if !local_reconcile_ready_cloud.load(Ordering::SeqCst) {
+                    if sleep_or_shutdown(&shutdown_cloud, Duration::from_secs(1)).await {
+                        break;
+                    }
+                    continue;
+                }
                 match engine_for_cloud
                     .sync_directory_incremental(&remote_root)
                     .await
@@ -345,42 +542,72 @@ impl DaemonRuntime {
             }
         });
 
-        let engine_for_worker = Arc::clone(&self.engine);
-        let worker_interval = self.config.worker_interval;
-        let sync_root_available_worker = Arc::clone(&sync_root_available);
-        let network_available_worker = Arc::clone(&network_available);
-        let auth_ready_worker = Arc::clone(&self.auth_ready);
-        let shutdown_worker = shutdown.child_token();
-        let worker_handle = tokio::spawn(async move {
-            loop {
-                if shutdown_worker.is_cancelled() {
-                    break;
-                }
-                if !auth_ready_worker.load(Ordering::SeqCst)
-                    || !sync_root_available_worker.load(Ordering::SeqCst)
-                {
-                    if sleep_or_shutdown(&shutdown_worker, worker_interval).await {
+        // Workers claim operations concurrently; the engine keeps operations on
+        // overlapping paths in queue order.
+        let mut worker_handles = Vec::with_capacity(self.config.queue_workers);
+        for _ in 0..self.config.queue_workers {
+            let engine_for_worker = Arc::clone(&self.engine);
+            let worker_interval = self.config.worker_interval;
+            let sync_root_available_worker = Arc::clone(&sync_root_available);
+            let network_available_worker = Arc::clone(&network_available);
+            let auth_ready_worker = Arc::clone(&self.auth_ready);
+            let pause_worker = Arc::clone(&self.pause);
+            let shutdown_worker = shutdown.child_token();
+            worker_handles.push(tokio::spawn(async move {
+                loop {
+                    if shutdown_worker.is_cancelled() {
                         break;
                     }
-                    continue;
-                }
-                if !network_available_worker.load(Ordering::SeqCst) {
-                    if sleep_or_shutdown(&shutdown_worker, Duration::from_secs(2)).await {
-                        break;
+                    if !auth_ready_worker.load(Ordering::SeqCst)
+                        || !sync_root_available_worker.load(Ordering::SeqCst)
+                    {
+                        if sleep_or_shutdown(&shutdown_worker, worker_interval).await {
+                            break;
+                        }
+                        continue;
                     }
-                    continue;
-                }
-                match engine_for_worker.run_once().await {
-                    Ok(true) => {
-                        eprintln!("[yadiskd] worker: processed queued operation");
+                    if !network_available_worker.load(Ordering::SeqCst) {
+                        if sleep_or_shutdown(&shutdown_worker, Duration::from_secs(2)).await {
+                            break;
+                        }
+                        continue;
                     }
-                    Ok(false) => {}
-                    Err(err) => {
-                        eprintln!("[yadiskd] worker error: {err}");
+                    // While paused only downloads someone is waiting for
+                    // (files opened through the mount) go ahead.
+                    let paused = pause_worker.is_paused();
+                    let result = if paused {
+                        engine_for_worker.run_once_on_demand().await
+                    } else {
+                        engine_for_worker.run_once().await
+                    };
+                    match result {
+                        Ok(true) => {
+                            eprintln!("[yadiskd] worker: processed queued operation");
+                            // More work is likely queued; take it right away.
+                            continue;
+                        }
+                        Ok(false) => {}
+                        Err(err) => {
+                            eprintln!("[yadiskd] worker error: {err}");
+                        }
+                    }
+                    let idle = if paused {
+                        Duration::from_secs(1).min(worker_interval)
+                    } else {
+                        worker_interval
+                    };
+                    if sleep_or_shutdown(&shutdown_worker, idle).await {
+                        break;
                     }
                 }
-                if sleep_or_shutdown(&shutdown_worker, worker_interval).await {
-                    break;
+            }));
+        }
+        let worker_handle = tokio::spawn(async move {
+            for handle in worker_handles {
+                if let Err(err) = handle.await
+                    && !err.is_cancelled()
+                {
+                    eprintln!("[yadiskd] worker exited with join error: {err}");
                 }
             }
         });
@@ -393,16 +620,23 @@ impl DaemonRuntime {
         let sync_root_available_materialize = Arc::clone(&sync_root_available);
         let materialize_refresh_requested_materialize = Arc::clone(&materialize_refresh_requested);
         let auth_ready_materialize = Arc::clone(&self.auth_ready);
+        let local_reconcile_ready_materialize = Arc::clone(&local_reconcile_ready);
+        let watch_health_materialize = Arc::clone(&watch_health);
+        let reconcile_enabled = self.config.enable_local_watcher;
+        let reconcile_interval = self.config.reconcile_interval;
         let shutdown_materialize = shutdown.child_token();
         let materialize_handle = tokio::spawn(async move {
             let mut initial_logged = false;
             let mut materialize_enabled = true;
             let mut previous_materialized_paths: HashSet<PathBuf> = HashSet::new();
+            let mut fingerprints = FingerprintCache::new();
+            let mut last_reconcile: Option<Instant> = None;
             loop {
                 if shutdown_materialize.is_cancelled() {
                     break;
                 }
                 if !materialize_enabled {
+                    local_reconcile_ready_materialize.store(true, Ordering::SeqCst);
                     if sleep_or_shutdown(&shutdown_materialize, Duration::from_secs(5)).await {
                         break;
                     }
@@ -420,8 +654,62 @@ impl DaemonRuntime {
                 if materialize_refresh_requested_materialize.swap(false, Ordering::SeqCst) {
                     initial_logged = false;
                     previous_materialized_paths.clear();
+                    fingerprints.clear();
+                    last_reconcile = None;
+                    watch_health_materialize.clear();
                     local_events_enabled_materialize.store(false, Ordering::SeqCst);
                 }
+                let dirty_scopes = if reconcile_enabled {
+                    watch_health_materialize.take_settled(WATCH_RESCAN_SETTLE)
+                } else {
+                    Vec::new()
+                };
+                // Reconcile before materializing so that files deleted while
+                // the daemon was stopped are not recreated as placeholders.
+                if reconcile_enabled && is_reconcile_due(last_reconcile, reconcile_interval) {
+                    match reconcile_local_changes(
+                        &engine_for_materialize,
+                        &materialize_sync_root,
+                        &materialize_cache_root,
+                        &materialize_remote_root,
+                        &materialize_sync_root,
+                        &mut fingerprints,
+                    )
+                    .await
+                    {
+                        Ok(0) => {}
+                        Ok(count) => {
+                            eprintln!("[yadiskd] local reconcile: {count} offline changes queued")
+                        }
+                        Err(err) => eprintln!("[yadiskd] local reconcile error: {err}"),
+                    }
+                    last_reconcile = Some(Instant::now());
+                    local_reconcile_ready_materialize.store(true, Ordering::SeqCst);
+                } else {
+                    // A full pass above already covers any dirty subtree.
+                    for scope in dirty_scopes {
+                        match reconcile_local_changes(
+                            &engine_for_materialize,
+                            &materialize_sync_root,
+                            &materialize_cache_root,
+                            &materialize_remote_root,
+                            &scope,
+                            &mut fingerprints,
+                        )
+                        .await
+                        {
+                            Ok(0) => {}
+                            Ok(count) => eprintln!(
+                                "[yadiskd] local rescan of {}: {count} missed changes queued",
+                                scope.display()
+                            ),
+                            Err(err) => {
+                                eprintln!("[yadiskd] local rescan error: {err}");
+                                watch_health_materialize.mark_dirty(&scope);
+                            }
+                        }
+                    }
+                }
                 match materialize_sync_tree(
                     &engine_for_materialize,
                     &materialize_sync_root,
@@ -431,6 +719,15 @@ impl DaemonRuntime {
                 .await
                 {
                     Ok(total_items) => {
+                        if let Err(err) = record_local_identities(
+                            &engine_for_materialize,
+                            &materialize_sync_root,
+                            &materialize_remote_root,
+                        )
+                        .await
+                        {
+                            eprintln!("[yadiskd] local identity tracking error: {err}");
+                        }
                         match collect_materialized_local_paths(
                             &engine_for_materialize,
                             &materialize_sync_root,
@@ -441,6 +738,7 @@ impl DaemonRuntime {
                             Ok(current_paths) => {
                                 if initial_logged
                                     && let Err(err) = prune_removed_materialized_paths(
+                                        &engine_for_materialize,
                                         &previous_materialized_paths,
                                         &current_paths,
                                         &materialize_sync_root,
@@ -487,6 +785,8 @@ impl DaemonRuntime {
         let sync_root_generation_storage = Arc::clone(&sync_root_generation);
         let local_events_enabled_storage = Arc::clone(&local_events_enabled);
         let materialize_refresh_requested_storage = Arc::clone(&materialize_refresh_requested);
+        let local_reconcile_ready_storage = Arc::clone(&local_reconcile_ready);
+        let reconcile_on_restore = self.config.enable_local_watcher;
         let shutdown_storage = shutdown.child_token();
         let storage_handle = tokio::spawn(async move {
             let mut known = sync_root_available_storage.load(Ordering::SeqCst);
@@ -518,6 +818,9 @@ impl DaemonRuntime {
                             );
                         }
                         local_events_enabled_storage.store(false, Ordering::SeqCst);
+                        if reconcile_on_restore {
+                            local_reconcile_ready_storage.store(false, Ordering::SeqCst);
+                        }
                         materialize_refresh_requested_storage.store(true, Ordering::SeqCst);
                         sync_root_generation_storage.fetch_add(1, Ordering::SeqCst);
                     } else {
@@ -542,8 +845,9 @@ impl DaemonRuntime {
 
         let engine_for_eviction = Arc::clone(&self.engine);
         let eviction_root = self.config.remote_root.clone();
+        let eviction_sync_root = self.config.sync_root.clone();
         let cache_root = self.config.cache_root.clone();
-        let cache_max_bytes = self.config.cache_max_bytes;
+        let eviction = self.config.eviction.clone();
         let eviction_interval = self.config.eviction_interval;
         let shutdown_eviction = shutdown.child_token();
         let eviction_handle = tokio::spawn(async move {
@@ -551,13 +855,17 @@ impl DaemonRuntime {
                 if shutdown_eviction.is_cancelled() {
                     break;
                 }
-                let _ = run_cache_eviction_once(
+                if let Err(err) = run_cache_eviction_once(
                     &engine_for_eviction,
+                    &eviction_sync_root,
                     &cache_root,
                     &eviction_root,
-                    cache_max_bytes,
+                    &eviction,
                 )
-                .await;
+                .await
+                {
+                    eprintln!("[yadiskd] cache eviction error: {err}");
+                }
                 if sleep_or_shutdown(&shutdown_eviction, eviction_interval).await {
                     break;
                 }
@@ -578,6 +886,8 @@ impl DaemonRuntime {
         let cloud_space_low_signal = Arc::clone(&cloud_space_low);
         let network_available_signal = Arc::clone(&network_available);
         let daemon_status_signal = Arc::clone(&daemon_status);
+        let watch_health_signal = Arc::clone(&watch_health);
+        let pause_signal = Arc::clone(&self.pause);
         let shutdown_signal = shutdown.child_token();
         let signal_handle = tokio::spawn(async move {
             let mut known_states: HashMap<String, &'static str> = HashMap::new();
@@ -585,7 +895,10 @@ impl DaemonRuntime {
             let mut known_tray_state: Option<TraySyncState> = None;
             let mut known_tray_language: Option<String> = None;
             let mut known_daemon_state: Option<(&'static str, &'static str)> = None;
+            let mut known_pause = None;
             let mut last_conflict_id = 0i64;
+            let mut known_queue: Option<(QueueSummary, bool)> = None;
+            let mut known_pending_deletes: Option<(u32, u64)> = None;
 
             loop {
                 if shutdown_signal.is_cancelled() {
@@ -640,28 +953,46 @@ impl DaemonRuntime {
                     let network_ready = network_available_signal.load(Ordering::SeqCst);
                     let cloud_error = cloud_sync_error_signal.load(Ordering::SeqCst);
                     let cloud_space_warn = cloud_space_low_signal.load(Ordering::SeqCst);
-                    let tray_state = effective_tray_state(
-                        &current_states,
-                        has_active_work,
-                        sync_root_ready,
-                        network_ready,
-                        cloud_error,
-                    );
+                    let pause_reason = pause_signal.current_reason();
+                    if known_pause != pause_reason {
+                        match pause_reason {
+                            Some(reason) => eprintln!("[yadiskd] sync {}", reason.message()),
+                            None if known_pause.is_some() => eprintln!("[yadiskd] sync resumed"),
+                            None => {}
+                        }
+                        known_pause = pause_reason;
+                    }
+                    let tray_state = if pause_reason.is_some() {
+                        TraySyncState::Paused
+                    } else {
+                        effective_tray_state(
+                            &current_states,
+                            has_active_work,
+                            sync_root_ready,
+                            network_ready,
+                            cloud_error,
+                        )
+                    };
                     if known_tray_state != Some(tray_state) || language_changed {
                         let _ = tray_state_tx_signal.send(tray_state);
                         known_tray_state = Some(tray_state);
                     }
-                    let daemon_state = if !sync_root_ready {
+                    let daemon_state = if let Some(reason) = pause_reason {
+                        ("paused", reason.message())
+                    } else if !sync_root_ready {
                         ("error", "sync root unavailable")
                     } else if !network_ready {
                         ("offline", "network unavailable")
                     } else if cloud_space_warn {
                         ("running", "cloud space low")
+                    } else if watch_health_signal.watch_limit_reached() {
+                        ("running", "inotify watch limit reached")
                     } else {
                         match tray_state {
                             TraySyncState::Normal => ("running", "idle"),
                             TraySyncState::Syncing => ("busy", "queued or active operations"),
                             TraySyncState::Error => ("error", "sync engine reported an error"),
+                            TraySyncState::Paused => ("paused", "paused"),
                         }
                     };
                     if known_daemon_state != Some(daemon_state) {
@@ -713,6 +1044,40 @@ impl DaemonRuntime {
                     }
                 }
 
+                if let Ok(summary) = engine_for_signals.queue_summary().await {
+                    let queue = (summary, engine_for_signals.is_queue_paused());
+                    if known_queue != Some(queue) {
+                        let pending = u32::try_from(summary.pending).unwrap_or(u32::MAX);
+                        if let Err(err) =
+                            SyncDbusService::queue_changed(&signal_emitter, pending, queue.1).await
+                        {
+                            eprintln!("[yadiskd] failed to emit queue_changed: {err}");
+                        } else {
+                            known_queue = Some(queue);
+                        }
+                    }
+                }
+
+                if let Ok(pending) = engine_for_signals.list_pending_deletes().await {
+                    let held = (
+                        u32::try_from(pending.len()).unwrap_or(u32::MAX),
+                        pending.iter().map(|held| held.files.max(0) as u64).sum(),
+                    );
+                    if known_pending_deletes != Some(held) {
+                        if let Err(err) = SyncDbusService::pending_deletes_changed(
+                            &signal_emitter,
+                            held.0,
+                            held.1,
+                        )
+                        .await
+                        {
+                            eprintln!("[yadiskd] failed to emit pending_deletes_changed: {err}");
+                        } else {
+                            known_pending_deletes = Some(held);
+                        }
+                    }
+                }
+
                 if sleep_or_shutdown(&shutdown_signal, Duration::from_secs(1)).await {
                     break;
                 }
@@ -730,13 +1095,32 @@ impl DaemonRuntime {
             let shutdown_local = shutdown.child_token();
             Some(tokio::spawn(async move {
                 let mut seen_uploads: HashMap<String, (u64, u128)> = HashMap::new();
+                let mut move_detector = MoveDetector::new(LOCAL_MOVE_PAIR_WINDOW);
                 loop {
+                    // Held paths expire on their own schedule; a steady stream
+                    // of unrelated events must not keep a delete back.
+                    let expiry = move_detector.next_expiry();
                     let event = tokio::select! {
                         _ = shutdown_local.cancelled() => break,
                         event = rx.recv() => match event {
                             Some(event) => event,
                             None => break,
                         },
+                        _ = tokio::time::sleep_until(
+                            expiry.unwrap_or_else(Instant::now).into()
+                        ), if expiry.is_some() =>
+                        {
+                            for path in move_detector.take_expired() {
+                                apply_local_event(
+                                    &engine_for_local,
+                                    &local_sync_root,
+                                    &local_cache_root,
+                                    LocalEvent::Delete { path },
+                                )
+                                .await;
+                            }
+                            continue;
+                        }
                     };
                     if !local_events_enabled_local.load(Ordering::SeqCst) {
                         continue;
@@ -744,23 +1128,31 @@ impl DaemonRuntime {
                     if !sync_root_available_local.load(Ordering::SeqCst) {
                         continue;
                     }
+                    let names = engine_for_local.local_names().await.unwrap_or_else(|err| {
+                        eprintln!("[yadiskd] local names error: {err}");
+                        LocalNames::default()
+                    });
+                    let event = local_event_to_remote(event, &names);
                     let event = normalize_local_event_for_remote_root(event, &local_remote_root);
                     if should_ignore_local_event(&event) {
                         continue;
                     }
+                    cancel_recreated_delete(&mut move_detector, &event);
                     match &event {
                         LocalEvent::Upload { path } => {
                             if should_skip_local_upload_event(
                                 &engine_for_local,
                                 &local_sync_root,
                                 &local_cache_root,
+                                &names,
                                 path,
                             )
                             .await
                             {
                                 continue;
                             }
-                            let Some(fp) = upload_fingerprint(&local_sync_root, path).await else {
+                            let Some(fp) = upload_fingerprint(&local_sync_root, &names, path).await
+                            else {
                                 continue;
                             };
                             if !should_process_upload_event(&mut seen_uploads, path, fp) {
@@ -778,24 +1170,39 @@ impl DaemonRuntime {
                                 continue;
                             }
                             seen_uploads.remove(path);
+                            if move_detector.consume_moved_away(path) {
+                                continue;
+                            }
+                            if let Ok(Some(identity)) =
+                                engine_for_local.local_identity_for_path(path).await
+                            {
+                                move_detector.hold_delete(path.clone(), identity);
+                                continue;
+                            }
                         }
                         LocalEvent::Move { from, to } => {
+                            move_detector.cancel_delete(from);
                             if let Some(fp) = seen_uploads.remove(from) {
                                 seen_uploads.insert(to.clone(), fp);
                             }
                         }
                         LocalEvent::Mkdir { .. } => {}
                     }
-                    eprintln!("[yadiskd] local event: {:?}", event);
-                    if let Err(err) =
-                        mirror_local_event_to_cache(&local_sync_root, &local_cache_root, &event)
-                            .await
-                    {
-                        eprintln!("[yadiskd] local cache mirror error: {err}");
-                    }
-                    if let Err(err) = engine_for_local.ingest_local_event(event).await {
-                        eprintln!("[yadiskd] local ingest error: {err}");
-                    }
+                    let event = pair_local_move(
+                        &engine_for_local,
+                        &local_sync_root,
+                        &names,
+                        &mut move_detector,
+                        event,
+                    )
+                    .await;
+                    apply_local_event(
+                        &engine_for_local,
+                        &local_sync_root,
+                        &local_cache_root,
+                        event,
+                    )
+                    .await;
                 }
             }))
         } else {
@@ -822,6 +1229,7 @@ impl DaemonRuntime {
         shutdown_task("storage", storage_handle).await;
         shutdown_task("eviction", eviction_handle).await;
         shutdown_task("signals", signal_handle).await;
+        shutdown_task("pause monitor", pause_monitor_handle).await;
         if let Some(handle) = watcher_handle {
             shutdown_task("watcher", handle).await;
         }
@@ -863,6 +1271,8 @@ async fn shutdown_task(name: &str, mut handle: JoinHandle<()>) {
 }
 
 include!("daemon_helpers.rs");
+include!("daemon_moves.rs");
+include!("daemon_reconcile.rs");
 
 #[cfg(test)]
 #[path = "daemon_tests.rs"]
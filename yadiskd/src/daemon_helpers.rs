@@ -151,6 +151,14 @@ fn default_cache_root() -> PathBuf {
         .join("yadisk-gtk")
 }
 
+fn default_merge_base_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(default_cache_root)
+        .join("yadisk-gtk")
+        .join("sync")
+        .join("bases")
+}
+
 fn read_u64_env(name: &str, default: u64) -> u64 {
     std::env::var(name)
         .ok()
@@ -174,11 +182,18 @@ fn read_bool_env(name: &str, default: bool) -> bool {
 async fn mirror_local_event_to_cache(
     sync_root: &Path,
     cache_root: &Path,
+    names: &LocalNames,
+    symlinks: SymlinkPolicy,
     event: &LocalEvent,
 ) -> anyhow::Result<()> {
     match event {
         LocalEvent::Upload { path } => {
-            let source = sync_path_for(sync_root, path)?;
+            let source = sync_path_for(sync_root, names, path)?;
+            if symlinks == SymlinkPolicy::Link
+                && mirror_symlink_to_cache(&source, cache_root, path).await?
+            {
+                return Ok(());
+            }
             let Ok(meta) = tokio::fs::metadata(&source).await else {
                 return Ok(());
             };
@@ -189,7 +204,8 @@ async fn mirror_local_event_to_cache(
             if let Some(parent) = target.parent() {
                 tokio::fs::create_dir_all(parent).await?;
             }
-            let _ = tokio::fs::copy(&source, &target).await?;
+            remove_cached_symlink(&target).await?;
+            let _ = copy_keeping_mtime(&source, &target).await?;
         }
         LocalEvent::Mkdir { path } => {
             let target = crate::sync::paths::cache_path_for(cache_root, path)?;
@@ -197,7 +213,7 @@ async fn mirror_local_event_to_cache(
         }
         LocalEvent::Delete { path } => {
             let target = crate::sync::paths::cache_path_for(cache_root, path)?;
-            if let Ok(meta) = tokio::fs::metadata(&target).await {
+            if let Ok(meta) = tokio::fs::symlink_metadata(&target).await {
                 if meta.is_dir() {
                     let _ = tokio::fs::remove_dir_all(&target).await;
                 } else {
@@ -211,14 +227,19 @@ async fn mirror_local_event_to_cache(
             if let Some(parent) = to_cache.parent() {
                 tokio::fs::create_dir_all(parent).await?;
             }
-            if tokio::fs::metadata(&from_cache).await.is_ok() {
+            if tokio::fs::symlink_metadata(&from_cache).await.is_ok() {
                 match tokio::fs::rename(&from_cache, &to_cache).await {
                     Ok(_) => {}
                     Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
                     Err(err) => return Err(err.into()),
                 }
             }
-            let local_target = sync_path_for(sync_root, to)?;
+            let local_target = sync_path_for(sync_root, names, to)?;
+            if symlinks == SymlinkPolicy::Link
+                && mirror_symlink_to_cache(&local_target, cache_root, to).await?
+            {
+                return Ok(());
+            }
             if let Ok(meta) = tokio::fs::metadata(&local_target).await {
                 if meta.is_dir() {
                     tokio::fs::create_dir_all(&to_cache).await?;
@@ -226,7 +247,8 @@ async fn mirror_local_event_to_cache(
                     if let Some(parent) = to_cache.parent() {
                         tokio::fs::create_dir_all(parent).await?;
                     }
-                    let _ = tokio::fs::copy(&local_target, &to_cache).await?;
+                    remove_cached_symlink(&to_cache).await?;
+                    let _ = copy_keeping_mtime(&local_target, &to_cache).await?;
                 }
             }
         }
@@ -234,15 +256,89 @@ async fn mirror_local_event_to_cache(
     Ok(())
 }
 
+/// Drops a symlink left in the cache so a copy does not write through it.
+async fn remove_cached_symlink(target: &Path) -> anyhow::Result<()> {
+    if tokio::fs::symlink_metadata(target)
+        .await
+        .is_ok_and(|meta| meta.is_symlink())
+    {
+        tokio::fs::remove_file(target).await?;
+    }
+    Ok(())
+}
+
+/// Recreates a local symlink in the cache, where the engine stores it as a
+/// link file. Returns `false` when `source` is not a symlink.
+async fn mirror_symlink_to_cache(
+    source: &Path,
+    cache_root: &Path,
+    path: &str,
+) -> anyhow::Result<bool> {
+    let Ok(link_target) = tokio::fs::read_link(source).await else {
+        return Ok(false);
+    };
+    let target = crate::sync::paths::cache_path_for(cache_root, path)?;
+    if let Some(parent) = target.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    match tokio::fs::symlink_metadata(&target).await {
+        Ok(meta) if meta.is_dir() => tokio::fs::remove_dir_all(&target).await?,
+        Ok(_) => tokio::fs::remove_file(&target).await?,
+        Err(_) => {}
+    }
+    tokio::fs::symlink(link_target, &target).await?;
+    Ok(true)
+}
+
+/// Whether the event adds a symlink the policy keeps out of the cloud:
+/// every symlink when skipping, and links to anything but a file when
+/// following them.
+async fn is_unsynced_symlink(
+    sync_root: &Path,
+    names: &LocalNames,
+    symlinks: SymlinkPolicy,
+    event: &LocalEvent,
+) -> bool {
+    let path = match event {
+        LocalEvent::Upload { path } => path,
+        LocalEvent::Move { to, .. } => to,
+        LocalEvent::Mkdir { .. } | LocalEvent::Delete { .. } => return false,
+    };
+    let Ok(local_path) = sync_path_for(sync_root, names, path) else {
+        return false;
+    };
+    let is_link = tokio::fs::symlink_metadata(&local_path)
+        .await
+        .is_ok_and(|meta| meta.is_symlink());
+    match symlinks {
+        _ if !is_link => false,
+        SymlinkPolicy::Skip => true,
+        SymlinkPolicy::Follow => !tokio::fs::metadata(&local_path)
+            .await
+            .is_ok_and(|meta| meta.is_file()),
+        SymlinkPolicy::Link => false,
+    }
+}
+
 async fn should_skip_local_upload_event(
     engine: &SyncEngine,
     sync_root: &Path,
     cache_root: &Path,
+    names: &LocalNames,
     path: &str,
 ) -> bool {
-    let Ok(local_path) = sync_path_for(sync_root, path) else {
+    let Ok(local_path) = sync_path_for(sync_root, names, path) else {
         return false;
     };
+    // A symlink recreated from a stored link file is already in the cloud.
+    if let Ok(local_link) = tokio::fs::read_link(&local_path).await {
+        let Ok(cache_path) = crate::sync::paths::cache_path_for(cache_root, path) else {
+            return false;
+        };
+        return tokio::fs::read_link(&cache_path)
+            .await
+            .is_ok_and(|cached_link| cached_link == local_link);
+    }
     let Ok(meta) = tokio::fs::metadata(&local_path).await else {
         return false;
     };
@@ -287,9 +383,13 @@ async fn file_md5_hex(path: &Path) -> anyhow::Result<String> {
     Ok(format!("{:x}", hasher.compute()))
 }
 
-async fn upload_fingerprint(sync_root: &Path, path: &str) -> Option<(u64, u128)> {
-    let local_path = sync_path_for(sync_root, path).ok()?;
-    let meta = tokio::fs::metadata(local_path).await.ok()?;
+async fn upload_fingerprint(
+    sync_root: &Path,
+    names: &LocalNames,
+    path: &str,
+) -> Option<(u64, u128)> {
+    let local_path = sync_path_for(sync_root, names, path).ok()?;
+    let meta = tokio::fs::symlink_metadata(local_path).await.ok()?;
     if meta.is_dir() {
         return None;
     }
@@ -359,6 +459,26 @@ fn normalize_local_event_for_remote_root(event: LocalEvent, remote_root: &str) -
     }
 }
 
+/// Rewrites an event seen under the sync root to the remote paths its local
+/// paths stand for.
+fn local_event_to_remote(event: LocalEvent, names: &LocalNames) -> LocalEvent {
+    match event {
+        LocalEvent::Upload { path } => LocalEvent::Upload {
+            path: names.remote_path(&path),
+        },
+        LocalEvent::Mkdir { path } => LocalEvent::Mkdir {
+            path: names.remote_path(&path),
+        },
+        LocalEvent::Delete { path } => LocalEvent::Delete {
+            path: names.remote_path(&path),
+        },
+        LocalEvent::Move { from, to } => LocalEvent::Move {
+            from: names.remote_path(&from),
+            to: names.remote_path(&to),
+        },
+    }
+}
+
 use crate::sync::paths::is_ignored_temporary_name;
 
 fn is_ignored_temporary_path(path: &str) -> bool {
@@ -396,11 +516,34 @@ async fn materialize_sync_tree(
         .await?
         .into_iter()
         .collect();
+    // Local deletions held back as a mass deletion stay deleted until the
+    // user decides.
+    let held: Vec<String> = engine
+        .list_pending_deletes()
+        .await?
+        .into_iter()
+        .filter(|held| held.origin == DeleteOrigin::Local)
+        .map(|held| held.path)
+        .collect();
+    let names = engine.local_names().await?;
+    let links: HashMap<String, String> = if engine.symlink_policy() == SymlinkPolicy::Link {
+        engine.link_targets().await?.into_iter().collect()
+    } else {
+        HashMap::new()
+    };
+    let baselines: HashMap<String, LocalBaseline> = engine
+        .list_local_baselines_by_prefix(remote_root)
+        .await?
+        .into_iter()
+        .collect();
     let mut touched_dirs = HashSet::new();
     touched_dirs.insert(sync_root.to_path_buf());
 
     for item in &items {
-        let local_path = sync_path_for(sync_root, &item.path)?;
+        if held.iter().any(|path| is_within(&item.path, path)) {
+            continue;
+        }
+        let local_path = sync_path_for(sync_root, &names, &item.path)?;
         if item.item_type == crate::sync::index::ItemType::Dir {
             tokio::fs::create_dir_all(&local_path).await?;
             touched_dirs.insert(local_path);
@@ -413,6 +556,15 @@ async fn materialize_sync_tree(
             tokio::fs::create_dir_all(parent).await?;
             touched_dirs.insert(parent.to_path_buf());
         }
+        let local_meta = tokio::fs::symlink_metadata(&local_path).await.ok();
+        if let Some(link_target) = links.get(&item.path) {
+            materialize_symlink(&local_path, local_meta.as_ref(), link_target).await?;
+            continue;
+        }
+        // Never write through a local symlink into whatever it points at.
+        if local_meta.as_ref().is_some_and(|meta| meta.is_symlink()) {
+            continue;
+        }
         let state = state_for_path(&states, &item.path);
         if tokio::fs::try_exists(&local_path).await? {
             match state {
@@ -427,13 +579,15 @@ async fn materialize_sync_tree(
                     }
                 }
                 Some(FileState::Cached) => {
-                    let local_meta = tokio::fs::metadata(&local_path).await?;
-                    if local_meta.len() == 0 {
-                        let cache_path = crate::sync::paths::cache_path_for(cache_root, &item.path)?;
-                        if tokio::fs::try_exists(&cache_path).await? {
-                            let _ = tokio::fs::copy(&cache_path, &local_path).await?;
-                        }
-                    }
+                    let cache_path = crate::sync::paths::cache_path_for(cache_root, &item.path)?;
+                    refresh_cached_copy(
+                        engine,
+                        &item.path,
+                        &cache_path,
+                        &local_path,
+                        baselines.get(&item.path),
+                    )
+                    .await?;
                 }
                 _ => {}
             }
@@ -443,8 +597,14 @@ async fn materialize_sync_tree(
         if matches!(state, Some(FileState::Cached)) {
             let cache_path = crate::sync::paths::cache_path_for(cache_root, &item.path)?;
             if tokio::fs::try_exists(&cache_path).await? {
-                match tokio::fs::copy(&cache_path, &local_path).await {
-                    Ok(_) => continue,
+                match copy_keeping_mtime(&cache_path, &local_path).await {
+                    Ok(_) => {
+                        let meta = tokio::fs::metadata(&local_path).await?;
+                        engine
+                            .record_local_baseline(&item.path, local_baseline(&meta))
+                            .await?;
+                        continue;
+                    }
                     Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
                     Err(err) => return Err(err.into()),
                 }
@@ -469,16 +629,82 @@ async fn materialize_sync_tree(
     Ok(items.len())
 }
 
+/// Brings the sync-folder copy of a cached file up to date with the cache.
+/// An empty placeholder, or a copy still exactly as the daemon last wrote
+/// it, is overwritten with a newer download; anything else holds local
+/// edits and is left for the upload to handle.
+async fn refresh_cached_copy(
+    engine: &SyncEngine,
+    path: &str,
+    cache_path: &Path,
+    local_path: &Path,
+    recorded: Option<&LocalBaseline>,
+) -> anyhow::Result<()> {
+    let local = tokio::fs::metadata(local_path).await?;
+    let Ok(cached) = tokio::fs::metadata(cache_path).await else {
+        return Ok(());
+    };
+    let local = local_baseline(&local);
+    let cached = local_baseline(&cached);
+    if local == cached {
+        if recorded != Some(&local) {
+            engine.record_local_baseline(path, local).await?;
+        }
+        return Ok(());
+    }
+    if local.size > 0 && recorded != Some(&local) {
+        return Ok(());
+    }
+    let _ = copy_keeping_mtime(cache_path, local_path).await?;
+    let written = tokio::fs::metadata(local_path).await?;
+    engine
+        .record_local_baseline(path, local_baseline(&written))
+        .await?;
+    Ok(())
+}
+
+fn local_baseline(meta: &std::fs::Metadata) -> LocalBaseline {
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    LocalBaseline {
+        size: meta.len(),
+        modified,
+    }
+}
+
+/// Puts a stored symlink in place of a missing file or an empty
+/// placeholder; a file the user filled in is left for the upload to handle.
+async fn materialize_symlink(
+    local_path: &Path,
+    local_meta: Option<&std::fs::Metadata>,
+    link_target: &str,
+) -> anyhow::Result<()> {
+    match local_meta {
+        None => {}
+        Some(meta) if meta.is_file() && meta.len() == 0 => {
+            tokio::fs::remove_file(local_path).await?;
+        }
+        Some(_) => return Ok(()),
+    }
+    tokio::fs::symlink(link_target, local_path).await?;
+    Ok(())
+}
+
 async fn collect_materialized_local_paths(
     engine: &SyncEngine,
     sync_root: &Path,
     remote_root: &str,
 ) -> anyhow::Result<HashSet<PathBuf>> {
     let items = engine.list_items_by_prefix(remote_root).await?;
+    let names = engine.local_names().await?;
     let mut paths = HashSet::with_capacity(items.len() * 2 + 1);
     paths.insert(sync_root.to_path_buf());
     for item in items {
-        let local_path = sync_path_for(sync_root, &item.path)?;
+        let local_path = sync_path_for(sync_root, &names, &item.path)?;
         paths.insert(local_path.clone());
         let mut parent = local_path.parent();
         while let Some(dir) = parent {
@@ -584,14 +810,25 @@ async fn move_materialized_path_to_trash(path: &Path) -> anyhow::Result<()> {
 }
 
 async fn prune_removed_materialized_paths(
+    engine: &SyncEngine,
     previous: &HashSet<PathBuf>,
     current: &HashSet<PathBuf>,
     sync_root: &Path,
     cache_root: &Path,
 ) -> anyhow::Result<()> {
+    // Nothing under a deletion held by the mass-delete guard is removed
+    // from disk before the user confirms it.
+    let names = engine.local_names().await?;
+    let held: Vec<PathBuf> = engine
+        .list_pending_deletes()
+        .await?
+        .iter()
+        .filter_map(|held| sync_path_for(sync_root, &names, &held.path).ok())
+        .collect();
     let mut stale: Vec<PathBuf> = previous
         .iter()
         .filter(|path| !current.contains(*path) && path.as_path() != sync_root)
+        .filter(|path| !held.iter().any(|held| path.starts_with(held)))
         .cloned()
         .collect();
     stale.sort_by_key(|path| std::cmp::Reverse(path.components().count()));
@@ -690,19 +927,18 @@ fn should_refresh_materialized_sync_root(
     }
 }
 
-fn sync_path_for(sync_root: &Path, remote_path: &str) -> anyhow::Result<PathBuf> {
-    let normalized = if let Some(rest) = remote_path.strip_prefix("disk:/") {
-        format!("/{}", rest.trim_start_matches('/'))
-    } else {
-        remote_path.to_string()
-    };
-
-    if !normalized.starts_with('/') {
+fn sync_path_for(
+    sync_root: &Path,
+    names: &LocalNames,
+    remote_path: &str,
+) -> anyhow::Result<PathBuf> {
+    if !remote_path.starts_with("disk:/") && !remote_path.starts_with('/') {
         anyhow::bail!("remote path must be absolute: {remote_path}");
     }
+    let relative = names.local_path(remote_path);
 
     let mut local = PathBuf::from(sync_root);
-    for component in Path::new(&normalized).components() {
+    for component in Path::new(&relative).components() {
         match component {
             Component::RootDir | Component::CurDir => {}
             Component::Normal(part) => local.push(part),
@@ -713,15 +949,20 @@ fn sync_path_for(sync_root: &Path, remote_path: &str) -> anyhow::Result<PathBuf>
     Ok(local)
 }
 
+/// Evicts unpinned cached files as the policy asks. Files with local changes
+/// that have not reached the cloud yet and files some process holds open are
+/// never evicted.
 async fn run_cache_eviction_once(
     engine: &SyncEngine,
+    sync_root: &Path,
     cache_root: &Path,
     remote_root: &str,
-    max_bytes: u64,
+    policy: &EvictionPolicy,
 ) -> anyhow::Result<()> {
+    if !policy.is_enabled() {
+        return Ok(());
+    }
     let mut candidates = Vec::new();
-    let mut total_bytes = 0u64;
-
     for (path, state, pinned, last_accessed) in engine
         .list_path_states_with_pin_by_prefix(remote_root)
         .await?
@@ -733,31 +974,136 @@ async fn run_cache_eviction_once(
         let Ok(metadata) = tokio::fs::metadata(&local_path).await else {
             continue;
         };
-        let size = metadata.len();
-        total_bytes = total_bytes.saturating_add(size);
         let modified = metadata
             .modified()
             .ok()
             .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
             .map(|d| d.as_secs() as i64)
             .unwrap_or(0);
-        candidates.push((path, local_path, size, last_accessed.unwrap_or(modified)));
+        candidates.push(EvictionCandidate {
+            path,
+            size: metadata.len(),
+            last_used: last_accessed.unwrap_or(modified),
+        });
     }
 
-    if total_bytes <= max_bytes {
+    let free_bytes = if policy.min_free_bytes > 0 {
+        let root = cache_root.to_path_buf();
+        tokio::task::spawn_blocking(move || free_space(&root))
+            .await?
+            .ok()
+    } else {
+        None
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    if policy.select(&candidates, free_bytes, now).is_empty() {
         return Ok(());
     }
 
-    candidates.sort_by_key(|entry| entry.3);
-    for (path, local_path, size, _) in candidates {
-        if total_bytes <= max_bytes {
-            break;
+    // Something has to go; leave out what must stay before choosing.
+    let uploads: Vec<String> = engine
+        .list_operations(&OpFilter {
+            kind: Some(OperationKind::Upload),
+            path_prefix: None,
+        })
+        .await?
+        .into_iter()
+        .map(|op| op.path)
+        .collect();
+    let sync_root = tokio::fs::canonicalize(sync_root)
+        .await
+        .unwrap_or_else(|_| sync_root.to_path_buf());
+    let cache_root = tokio::fs::canonicalize(cache_root)
+        .await
+        .unwrap_or_else(|_| cache_root.to_path_buf());
+    let (scan_sync, scan_cache) = (sync_root.clone(), cache_root.clone());
+    let open = tokio::task::spawn_blocking(move || {
+        let mut open = crate::sync::settle::open_files_under(&scan_sync);
+        open.extend(crate::sync::settle::open_files_under(&scan_cache));
+        open
+    })
+    .await?;
+    let names = engine.local_names().await?;
+    let mut evictable = Vec::with_capacity(candidates.len());
+    for candidate in candidates {
+        if uploads.iter().any(|path| is_within(&candidate.path, path)) {
+            continue;
         }
-        if tokio::fs::remove_file(&local_path).await.is_ok() {
-            total_bytes = total_bytes.saturating_sub(size);
-            let _ = engine.evict_path(&path).await;
+        let cache_path = crate::sync::paths::cache_path_for(&cache_root, &candidate.path)?;
+        let local_path = sync_path_for(&sync_root, &names, &candidate.path)?;
+        if open.contains(&cache_path) || open.contains(&local_path) {
+            continue;
+        }
+        if local_copy_differs(&local_path, &cache_path).await {
+            eprintln!(
+                "[yadiskd] not evicting {}: local changes not uploaded yet",
+                candidate.path
+            );
+            continue;
+        }
+        evictable.push(candidate);
+    }
+
+    for candidate in policy.select(&evictable, free_bytes, now) {
+        let cache_path = crate::sync::paths::cache_path_for(&cache_root, &candidate.path)?;
+        if tokio::fs::remove_file(&cache_path).await.is_ok() {
+            let _ = engine.evict_path(&candidate.path).await;
         }
     }
 
     Ok(())
 }
+
+/// Whether the copy in the sync folder holds edits the cached copy lacks;
+/// evicting would turn it into an empty placeholder.
+///
+/// Copies between the two trees keep the modification time, so matching size
+/// and mtime mean an unchanged copy. Only copies made before that, or touched
+/// since, are hashed; equal ones get the cached mtime so the next pass skips
+/// them.
+async fn local_copy_differs(local_path: &Path, cache_path: &Path) -> bool {
+    let Ok(local_meta) = tokio::fs::symlink_metadata(local_path).await else {
+        return false;
+    };
+    if !local_meta.is_file() || local_meta.len() == 0 {
+        return false;
+    }
+    let Ok(cache_meta) = tokio::fs::metadata(cache_path).await else {
+        return true;
+    };
+    if local_meta.len() != cache_meta.len() {
+        return true;
+    }
+    let Ok(cache_modified) = cache_meta.modified() else {
+        return true;
+    };
+    if local_meta.modified().is_ok_and(|modified| modified == cache_modified) {
+        return false;
+    }
+    match (file_md5_hex(local_path).await, file_md5_hex(cache_path).await) {
+        (Ok(local), Ok(cached)) if local == cached => {
+            let _ = set_modified(local_path, cache_modified).await;
+            false
+        }
+        _ => true,
+    }
+}
+
+/// Copies `source` over `target` keeping its modification time, so the two
+/// can later be told apart by metadata alone.
+async fn copy_keeping_mtime(source: &Path, target: &Path) -> std::io::Result<u64> {
+    let copied = tokio::fs::copy(source, target).await?;
+    let modified = tokio::fs::metadata(source).await?.modified()?;
+    set_modified(target, modified).await?;
+    Ok(copied)
+}
+
+async fn set_modified(path: &Path, modified: std::time::SystemTime) -> std::io::Result<()> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || std::fs::File::open(path)?.set_modified(modified))
+        .await
+        .map_err(std::io::Error::other)?
+}
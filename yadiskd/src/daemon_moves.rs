@@ -0,0 +1,80 @@
+async fn local_file_identity(
+    sync_root: &Path,
+    names: &LocalNames,
+    path: &str,
+) -> Option<LocalFileIdentity> {
+    let local_path = sync_path_for(sync_root, names, path).ok()?;
+    let meta = tokio::fs::symlink_metadata(&local_path).await.ok()?;
+    meta.is_file().then(|| identity_from_metadata(&meta))
+}
+
+/// Drops a held delete once something is written at the same path again, as
+/// editors that save by rename and checkouts do with a fresh inode. Runs
+/// before an unchanged copy is skipped, or the delete would still go out.
+fn cancel_recreated_delete(detector: &mut MoveDetector, event: &LocalEvent) -> bool {
+    match event {
+        LocalEvent::Upload { path } | LocalEvent::Mkdir { path } => detector.cancel_delete(path),
+        LocalEvent::Delete { .. } | LocalEvent::Move { .. } => false,
+    }
+}
+
+/// Rewrites an upload of a path that carries the identity of an indexed file
+/// into a move of that file, when the rename reached us as a delete and a
+/// create instead of a paired rename event.
+async fn pair_local_move(
+    engine: &SyncEngine,
+    sync_root: &Path,
+    names: &LocalNames,
+    detector: &mut MoveDetector,
+    event: LocalEvent,
+) -> LocalEvent {
+    let LocalEvent::Upload { path } = event else {
+        return event;
+    };
+    let Some(identity) = local_file_identity(sync_root, names, &path).await else {
+        return LocalEvent::Upload { path };
+    };
+    if let Some(from) = detector.take_held_delete(&identity) {
+        if from == path {
+            return LocalEvent::Upload { path };
+        }
+        return LocalEvent::Move { from, to: path };
+    }
+    // The new path may show up before the old one is reported as gone.
+    if let Ok(Some(from)) = engine.path_for_local_identity(&identity).await
+        && from != path
+        && local_file_identity(sync_root, names, &from).await != Some(identity)
+    {
+        detector.note_moved_away(from.clone());
+        return LocalEvent::Move { from, to: path };
+    }
+    LocalEvent::Upload { path }
+}
+
+async fn record_local_identities(
+    engine: &SyncEngine,
+    sync_root: &Path,
+    remote_root: &str,
+) -> anyhow::Result<usize> {
+    let known: HashMap<String, LocalFileIdentity> = engine
+        .list_local_identities_by_prefix(remote_root)
+        .await?
+        .into_iter()
+        .collect();
+    let names = engine.local_names().await?;
+    let mut recorded = 0usize;
+    for item in engine.list_items_by_prefix(remote_root).await? {
+        if item.item_type != crate::sync::index::ItemType::File {
+            continue;
+        }
+        let Some(identity) = local_file_identity(sync_root, &names, &item.path).await else {
+            continue;
+        };
+        if known.get(&item.path) == Some(&identity) {
+            continue;
+        }
+        engine.record_local_identity(&item.path, &identity).await?;
+        recorded += 1;
+    }
+    Ok(recorded)
+}
@@ -0,0 +1,97 @@
+async fn reconcile_local_changes(
+    engine: &SyncEngine,
+    sync_root: &Path,
+    cache_root: &Path,
+    remote_root: &str,
+    scope: &Path,
+    fingerprints: &mut FingerprintCache,
+) -> anyhow::Result<usize> {
+    let items = engine.list_items_by_prefix(remote_root).await?;
+    if items.is_empty() {
+        // Without a baseline every local file would look new; wait for the
+        // first cloud listing instead of re-uploading a stale tree.
+        return Ok(0);
+    }
+    let states: HashMap<_, _> = engine
+        .list_states_by_prefix(remote_root)
+        .await?
+        .into_iter()
+        .collect();
+    // Only files found on disk before, and folders above them, can have been
+    // deleted locally; the rest may not be materialized yet.
+    let parents: HashMap<&str, Option<&str>> = items
+        .iter()
+        .map(|item| (item.path.as_str(), item.parent_path.as_deref()))
+        .collect();
+    let mut seen_locally = HashSet::new();
+    for (path, _) in engine.list_local_identities_by_prefix(remote_root).await? {
+        let mut current = parents.get_key_value(path.as_str()).map(|(path, _)| *path);
+        while let Some(path) = current {
+            if !seen_locally.insert(path.to_string()) {
+                break;
+            }
+            current = parents.get(path).copied().flatten();
+        }
+    }
+    let local: HashMap<String, LocalBaseline> = engine
+        .list_local_baselines_by_prefix(remote_root)
+        .await?
+        .into_iter()
+        .collect();
+    // The local tree is compared under its on-disk names.
+    let names = engine.local_names().await?;
+    let baseline: Vec<BaselineEntry> = items
+        .into_iter()
+        .map(|item| BaselineEntry {
+            state: state_for_path(&states, &item.path),
+            path: names.local_path(&item.path),
+            item_type: item.item_type,
+            size: item.size,
+            last_synced_hash: item.last_synced_hash.or(item.hash),
+            last_synced_modified: item.last_synced_modified,
+            seen_locally: seen_locally.contains(&item.path),
+            local: local.get(&item.path).copied(),
+        })
+        .collect();
+
+    let events = reconcile_local_tree(sync_root, scope, &baseline, fingerprints).await?;
+    let count = events.len();
+    for event in events {
+        let event = local_event_to_remote(event, &names);
+        let event = normalize_local_event_for_remote_root(event, remote_root);
+        apply_local_event(engine, sync_root, cache_root, event).await;
+    }
+    Ok(count)
+}
+
+async fn apply_local_event(
+    engine: &SyncEngine,
+    sync_root: &Path,
+    cache_root: &Path,
+    event: LocalEvent,
+) {
+    eprintln!("[yadiskd] local event: {:?}", event);
+    let symlinks = engine.symlink_policy();
+    match engine.local_names().await {
+        Ok(names) => {
+            if is_unsynced_symlink(sync_root, &names, symlinks, &event).await {
+                eprintln!("[yadiskd] symlink left out of sync: {:?}", event);
+                return;
+            }
+            if let Err(err) =
+                mirror_local_event_to_cache(sync_root, cache_root, &names, symlinks, &event)
+                    .await
+            {
+                eprintln!("[yadiskd] local cache mirror error: {err}");
+            }
+        }
+        Err(err) => eprintln!("[yadiskd] local cache mirror error: {err}"),
+    }
+    if let Err(err) = engine.ingest_local_event(event).await {
+        eprintln!("[yadiskd] local ingest error: {err}");
+    }
+}
+
+fn is_reconcile_due(last_reconcile: Option<Instant>, interval: Duration) -> bool {
+    last_reconcile.is_none_or(|at| at.elapsed() >= interval)
+}
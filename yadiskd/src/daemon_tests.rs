@@ -1,7 +1,7 @@
 use super::*;
 use crate::storage::OAuthState;
 use crate::sync::engine::EngineError;
-use crate::sync::index::{FileState, IndexStore, ItemInput, ItemType, StateMeta};
+use crate::sync::index::{DeleteOrigin, FileState, IndexStore, ItemInput, ItemType, StateMeta};
 use crate::sync::local_watcher::LocalEvent;
 use sqlx::SqlitePool;
 use tempfile::tempdir;
@@ -346,7 +346,7 @@ async fn deduplicates_same_upload_fingerprint() {
         .unwrap();
     tokio::fs::write(&sync_file, b"hello").await.unwrap();
 
-    let fp1 = upload_fingerprint(sync_dir.path(), "disk:/Docs/A.txt")
+    let fp1 = upload_fingerprint(sync_dir.path(), &LocalNames::default(), "disk:/Docs/A.txt")
         .await
         .unwrap();
     let mut seen = HashMap::new();
@@ -362,7 +362,7 @@ async fn deduplicates_same_upload_fingerprint() {
     ));
 
     tokio::fs::write(&sync_file, b"hello world").await.unwrap();
-    let fp2 = upload_fingerprint(sync_dir.path(), "disk:/Docs/A.txt")
+    let fp2 = upload_fingerprint(sync_dir.path(), &LocalNames::default(), "disk:/Docs/A.txt")
         .await
         .unwrap();
     assert!(should_process_upload_event(
@@ -385,6 +385,8 @@ async fn mirrors_local_upload_to_cache() {
     mirror_local_event_to_cache(
         sync_dir.path(),
         cache_dir.path(),
+        &LocalNames::default(),
+        SymlinkPolicy::Skip,
         &LocalEvent::Upload {
             path: "/Docs/A.txt".into(),
         },
@@ -397,6 +399,73 @@ async fn mirrors_local_upload_to_cache() {
     assert_eq!(cached, b"local-data");
 }
 
+#[tokio::test]
+async fn symlinks_are_filtered_and_mirrored_by_policy() {
+    let sync_dir = tempdir().unwrap();
+    let cache_dir = tempdir().unwrap();
+    let docs = sync_dir.path().join("Docs");
+    tokio::fs::create_dir_all(docs.join("Sub")).await.unwrap();
+    tokio::fs::write(docs.join("A.txt"), b"local-data")
+        .await
+        .unwrap();
+    std::os::unix::fs::symlink("A.txt", docs.join("ToFile")).unwrap();
+    std::os::unix::fs::symlink("Sub", docs.join("ToDir")).unwrap();
+    let names = LocalNames::default();
+    let upload = |path: &str| LocalEvent::Upload { path: path.into() };
+
+    for (policy, to_file, to_dir) in [
+        (SymlinkPolicy::Skip, true, true),
+        (SymlinkPolicy::Follow, false, true),
+        (SymlinkPolicy::Link, false, false),
+    ] {
+        assert_eq!(
+            is_unsynced_symlink(sync_dir.path(), &names, policy, &upload("/Docs/ToFile")).await,
+            to_file
+        );
+        assert_eq!(
+            is_unsynced_symlink(sync_dir.path(), &names, policy, &upload("/Docs/ToDir")).await,
+            to_dir
+        );
+    }
+    assert!(
+        !is_unsynced_symlink(
+            sync_dir.path(),
+            &names,
+            SymlinkPolicy::Skip,
+            &upload("/Docs/A.txt")
+        )
+        .await
+    );
+
+    mirror_local_event_to_cache(
+        sync_dir.path(),
+        cache_dir.path(),
+        &names,
+        SymlinkPolicy::Link,
+        &upload("/Docs/ToFile"),
+    )
+    .await
+    .unwrap();
+    let cached = crate::sync::paths::cache_path_for(cache_dir.path(), "/Docs/ToFile").unwrap();
+    assert_eq!(
+        tokio::fs::read_link(&cached).await.unwrap(),
+        PathBuf::from("A.txt")
+    );
+
+    mirror_local_event_to_cache(
+        sync_dir.path(),
+        cache_dir.path(),
+        &names,
+        SymlinkPolicy::Follow,
+        &upload("/Docs/ToFile"),
+    )
+    .await
+    .unwrap();
+    let meta = tokio::fs::symlink_metadata(&cached).await.unwrap();
+    assert!(meta.is_file());
+    assert_eq!(tokio::fs::read(&cached).await.unwrap(), b"local-data");
+}
+
 #[tokio::test]
 async fn skips_cached_upload_when_local_matches_cache() {
     let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
@@ -445,6 +514,7 @@ async fn skips_cached_upload_when_local_matches_cache() {
             &engine,
             sync_dir.path(),
             cache_dir.path(),
+            &LocalNames::default(),
             "disk:/Docs/A.txt"
         )
         .await
@@ -456,6 +526,7 @@ async fn skips_cached_upload_when_local_matches_cache() {
             &engine,
             sync_dir.path(),
             cache_dir.path(),
+            &LocalNames::default(),
             "disk:/Docs/A.txt"
         )
         .await
@@ -553,9 +624,16 @@ async fn cache_eviction_skips_pinned_files() {
 
     let client = YadiskClient::with_base_url("http://127.0.0.1:9", "token").unwrap();
     let engine = SyncEngine::new(client, index, cache_dir.path().to_path_buf());
-    run_cache_eviction_once(&engine, cache_dir.path(), "/", 8)
-        .await
-        .unwrap();
+    let sync_dir = tempdir().unwrap();
+    run_cache_eviction_once(
+        &engine,
+        sync_dir.path(),
+        cache_dir.path(),
+        "/",
+        &EvictionPolicy::new(8),
+    )
+    .await
+    .unwrap();
 
     let states = engine
         .list_path_states_with_pin_by_prefix("/")
@@ -573,6 +651,101 @@ async fn cache_eviction_skips_pinned_files() {
     );
 }
 
+#[tokio::test]
+async fn cache_eviction_keeps_unsynced_and_open_files() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let index = IndexStore::from_pool(pool);
+    index.init().await.unwrap();
+    let sync_dir = tempdir().unwrap();
+    let cache_dir = tempdir().unwrap();
+
+    for path in ["/Edited.txt", "/Open.txt", "/Queued.txt", "/Idle.txt"] {
+        let item = index
+            .upsert_item(&ItemInput {
+                path: path.to_string(),
+                parent_path: Some("/".to_string()),
+                name: path.trim_start_matches('/').to_string(),
+                item_type: ItemType::File,
+                size: Some(5),
+                modified: None,
+                hash: None,
+                resource_id: None,
+                last_synced_hash: None,
+                last_synced_modified: None,
+            })
+            .await
+            .unwrap();
+        index
+            .set_state_with_meta(
+                item.id,
+                FileState::Cached,
+                false,
+                None,
+                StateMeta {
+                    last_accessed: Some(100),
+                    ..StateMeta::default()
+                },
+            )
+            .await
+            .unwrap();
+        let cached = crate::sync::paths::cache_path_for(cache_dir.path(), path).unwrap();
+        tokio::fs::write(&cached, b"12345").await.unwrap();
+        tokio::fs::copy(&cached, sync_dir.path().join(path.trim_start_matches('/')))
+            .await
+            .unwrap();
+    }
+    tokio::fs::write(sync_dir.path().join("Edited.txt"), b"edits")
+        .await
+        .unwrap();
+    let _reader = std::fs::File::open(sync_dir.path().join("Open.txt")).unwrap();
+
+    let client = YadiskClient::with_base_url("http://127.0.0.1:9", "token").unwrap();
+    let engine = SyncEngine::new(client, index, cache_dir.path().to_path_buf());
+    engine.enqueue_upload("/Queued.txt").await.unwrap();
+    let policy = EvictionPolicy::default().with_max_idle(Duration::from_secs(24 * 60 * 60));
+    run_cache_eviction_once(&engine, sync_dir.path(), cache_dir.path(), "/", &policy)
+        .await
+        .unwrap();
+
+    for (name, kept) in [
+        ("Edited.txt", true),
+        ("Open.txt", true),
+        ("Queued.txt", true),
+        ("Idle.txt", false),
+    ] {
+        assert_eq!(
+            tokio::fs::try_exists(cache_dir.path().join(name))
+                .await
+                .unwrap(),
+            kept,
+            "{name}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn local_copy_is_compared_by_size_and_mtime_once_aligned() {
+    let dir = tempdir().unwrap();
+    let cached = dir.path().join("cached.txt");
+    let local = dir.path().join("local.txt");
+    tokio::fs::write(&cached, b"12345").await.unwrap();
+    tokio::fs::write(&local, b"12345").await.unwrap();
+    let past = std::time::SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    set_modified(&cached, past).await.unwrap();
+
+    // Equal content with another mtime is hashed once, then aligned.
+    assert!(!local_copy_differs(&local, &cached).await);
+    let modified = |path: &Path| std::fs::metadata(path).unwrap().modified().unwrap();
+    assert_eq!(modified(&local), past);
+
+    tokio::fs::write(&local, b"edits").await.unwrap();
+    assert!(local_copy_differs(&local, &cached).await);
+
+    copy_keeping_mtime(&local, &cached).await.unwrap();
+    assert_eq!(modified(&cached), modified(&local));
+    assert!(!local_copy_differs(&local, &cached).await);
+}
+
 #[tokio::test]
 async fn cache_eviction_prefers_oldest_last_accessed_file() {
     let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
@@ -622,9 +795,16 @@ async fn cache_eviction_prefers_oldest_last_accessed_file() {
 
     let client = YadiskClient::with_base_url("http://127.0.0.1:9", "token").unwrap();
     let engine = SyncEngine::new(client, index, cache_dir.path().to_path_buf());
-    run_cache_eviction_once(&engine, cache_dir.path(), "/", 10)
-        .await
-        .unwrap();
+    let sync_dir = tempdir().unwrap();
+    run_cache_eviction_once(
+        &engine,
+        sync_dir.path(),
+        cache_dir.path(),
+        "/",
+        &EvictionPolicy::new(10),
+    )
+    .await
+    .unwrap();
 
     assert!(
         tokio::fs::metadata(cache_dir.path().join("A.txt"))
@@ -827,18 +1007,20 @@ async fn materialize_updates_existing_files_for_state_transitions() {
 }
 
 #[tokio::test]
-async fn materialize_skips_missing_syncing_files() {
+async fn held_mass_deletions_are_neither_restored_nor_pruned() {
     let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
     let index = IndexStore::from_pool(pool);
     index.init().await.unwrap();
     let sync_dir = tempdir().unwrap();
     let cache_dir = tempdir().unwrap();
+    let trash_dir = tempdir().unwrap();
+    let _trash_guard = install_test_trash_dir(trash_dir.path().to_path_buf());
 
     let item = index
         .upsert_item(&ItemInput {
-            path: "/Docs/Deleting.txt".into(),
+            path: "/Docs/Removed.txt".into(),
             parent_path: Some("/Docs".into()),
-            name: "Deleting.txt".into(),
+            name: "Removed.txt".into(),
             item_type: ItemType::File,
             size: Some(5),
             modified: None,
@@ -850,7 +1032,15 @@ async fn materialize_skips_missing_syncing_files() {
         .await
         .unwrap();
     index
-        .set_state(item.id, FileState::Syncing, true, None)
+        .set_state(item.id, FileState::CloudOnly, false, None)
+        .await
+        .unwrap();
+    index
+        .hold_delete("/Docs", DeleteOrigin::Local, 1, 1_700_000_000)
+        .await
+        .unwrap();
+    index
+        .hold_delete("disk:/Photos", DeleteOrigin::Cloud, 1, 1_700_000_000)
         .await
         .unwrap();
 
@@ -859,58 +1049,751 @@ async fn materialize_skips_missing_syncing_files() {
     materialize_sync_tree(&engine, sync_dir.path(), cache_dir.path(), "/")
         .await
         .unwrap();
-
     assert!(
-        tokio::fs::metadata(sync_dir.path().join("Docs/Deleting.txt"))
+        tokio::fs::metadata(sync_dir.path().join("Docs/Removed.txt"))
             .await
             .is_err()
     );
+
+    let photo = sync_dir.path().join("Photos/A.jpg");
+    tokio::fs::create_dir_all(photo.parent().unwrap())
+        .await
+        .unwrap();
+    tokio::fs::write(&photo, b"jpg").await.unwrap();
+    let previous = std::collections::HashSet::from([sync_dir.path().to_path_buf(), photo.clone()]);
+    let current = std::collections::HashSet::from([sync_dir.path().to_path_buf()]);
+    prune_removed_materialized_paths(
+        &engine,
+        &previous,
+        &current,
+        sync_dir.path(),
+        cache_dir.path(),
+    )
+    .await
+    .unwrap();
+    assert!(tokio::fs::metadata(&photo).await.is_ok());
 }
 
 #[tokio::test]
-async fn prune_removed_materialized_paths_removes_stale_paths_only() {
+async fn materialize_uses_recorded_local_names_and_maps_events_back() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let index = IndexStore::from_pool(pool);
+    index.init().await.unwrap();
     let sync_dir = tempdir().unwrap();
     let cache_dir = tempdir().unwrap();
-    let trash_dir = tempdir().unwrap();
-    let _trash_guard = install_test_trash_dir(trash_dir.path().to_path_buf());
-    let docs = sync_dir.path().join("Docs");
-    tokio::fs::create_dir_all(&docs).await.unwrap();
-    let stale = docs.join("Stale.txt");
-    let keep = sync_dir.path().join("Keep.txt");
-    tokio::fs::write(&stale, b"old").await.unwrap();
-    tokio::fs::write(&keep, b"keep").await.unwrap();
-    let stale_cache = cache_dir.path().join("Docs/Stale.txt");
-    let keep_cache = cache_dir.path().join("Keep.txt");
-    tokio::fs::create_dir_all(stale_cache.parent().unwrap())
-        .await
-        .unwrap();
-    tokio::fs::write(&stale_cache, b"old-cache").await.unwrap();
-    tokio::fs::write(&keep_cache, b"keep-cache").await.unwrap();
 
-    let previous = std::collections::HashSet::from([
-        sync_dir.path().to_path_buf(),
-        docs.clone(),
-        stale.clone(),
-    ]);
-    let current = std::collections::HashSet::from([sync_dir.path().to_path_buf(), keep.clone()]);
+    for name in ["Cafe\u{301}.txt", "Caf\u{e9}.txt"] {
+        let item = index
+            .upsert_item(&ItemInput {
+                path: format!("/Docs/{name}"),
+                parent_path: Some("/Docs".into()),
+                name: name.into(),
+                item_type: ItemType::File,
+                size: Some(5),
+                modified: None,
+                hash: None,
+                resource_id: None,
+                last_synced_hash: None,
+                last_synced_modified: None,
+            })
+            .await
+            .unwrap();
+        index
+            .set_state(item.id, FileState::CloudOnly, false, None)
+            .await
+            .unwrap();
+        if name.starts_with("Cafe") {
+            index
+                .set_local_name(item.id, Some("Caf\u{e9} (2).txt"))
+                .await
+                .unwrap();
+        }
+    }
 
-    prune_removed_materialized_paths(&previous, &current, sync_dir.path(), cache_dir.path())
+    let client = YadiskClient::with_base_url("http://127.0.0.1:9", "token").unwrap();
+    let engine = SyncEngine::new(client, index, cache_dir.path().to_path_buf());
+    materialize_sync_tree(&engine, sync_dir.path(), cache_dir.path(), "/")
         .await
         .unwrap();
+    let mut local: Vec<String> = std::fs::read_dir(sync_dir.path().join("Docs"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    local.sort();
+    assert_eq!(local, vec!["Caf\u{e9} (2).txt", "Caf\u{e9}.txt"]);
 
-    assert!(tokio::fs::metadata(&stale).await.is_err());
-    assert!(tokio::fs::metadata(&docs).await.is_err());
-    assert!(tokio::fs::metadata(&keep).await.is_ok());
-    assert!(
-        tokio::fs::metadata(trash_dir.path().join("Stale.txt"))
+    let names = engine.local_names().await.unwrap();
+    assert_eq!(
+        local_event_to_remote(
+            LocalEvent::Move {
+                from: "/Docs/Caf\u{e9} (2).txt".into(),
+                to: "/Docs/Cafe\u{301} old.txt".into(),
+            },
+            &names
+        ),
+        LocalEvent::Move {
+            from: "/Docs/Cafe\u{301}.txt".into(),
+            to: "/Docs/Caf\u{e9} old.txt".into(),
+        }
+    );
+}
+
+#[tokio::test]
+async fn materialize_escapes_names_that_cannot_exist_on_disk() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let index = IndexStore::from_pool(pool);
+    index.init().await.unwrap();
+    let sync_dir = tempdir().unwrap();
+    let cache_dir = tempdir().unwrap();
+
+    let long = format!("{}.txt", "n".repeat(300));
+    for name in ["..", long.as_str(), "A.txt"] {
+        let item = index
+            .upsert_item(&ItemInput {
+                path: format!("/Docs/{name}"),
+                parent_path: Some("/Docs".into()),
+                name: name.into(),
+                item_type: ItemType::File,
+                size: Some(5),
+                modified: None,
+                hash: None,
+                resource_id: None,
+                last_synced_hash: None,
+                last_synced_modified: None,
+            })
             .await
-            .is_ok()
+            .unwrap();
+        index
+            .set_state(item.id, FileState::CloudOnly, false, None)
+            .await
+            .unwrap();
+    }
+
+    let client = YadiskClient::with_base_url("http://127.0.0.1:9", "token").unwrap();
+    let engine = SyncEngine::new(client, index, cache_dir.path().to_path_buf());
+    assert_eq!(
+        materialize_sync_tree(&engine, sync_dir.path(), cache_dir.path(), "/")
+            .await
+            .unwrap(),
+        3
     );
-    assert!(
-        tokio::fs::metadata(trash_dir.path().join("Docs"))
+    let local: Vec<String> = std::fs::read_dir(sync_dir.path().join("Docs"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    assert_eq!(local.len(), 3);
+    assert!(local.iter().any(|name| name.starts_with("%2E%2E~")));
+    assert!(local.iter().all(|name| name.len() <= 255));
+    assert_eq!(std::fs::read_dir(sync_dir.path()).unwrap().count(), 1);
+}
+
+#[tokio::test]
+async fn materialize_recreates_stored_symlinks_and_never_writes_through_links() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let index = IndexStore::from_pool(pool);
+    index.init().await.unwrap();
+    let sync_dir = tempdir().unwrap();
+    let cache_dir = tempdir().unwrap();
+    let outside = tempdir().unwrap();
+
+    for name in ["Link", "Out.txt"] {
+        let item = index
+            .upsert_item(&ItemInput {
+                path: format!("/Docs/{name}"),
+                parent_path: Some("/Docs".into()),
+                name: name.into(),
+                item_type: ItemType::File,
+                size: Some(5),
+                modified: None,
+                hash: None,
+                resource_id: None,
+                last_synced_hash: None,
+                last_synced_modified: None,
+            })
             .await
-            .is_ok()
+            .unwrap();
+        index
+            .set_state(item.id, FileState::CloudOnly, false, None)
+            .await
+            .unwrap();
+        if name == "Link" {
+            index
+                .set_link_target(item.id, Some("Out.txt"))
+                .await
+                .unwrap();
+        }
+    }
+    let docs = sync_dir.path().join("Docs");
+    tokio::fs::create_dir_all(&docs).await.unwrap();
+    tokio::fs::write(docs.join("Link"), b"").await.unwrap();
+    let external = outside.path().join("keep.txt");
+    tokio::fs::write(&external, b"keep").await.unwrap();
+    std::os::unix::fs::symlink(&external, docs.join("Out.txt")).unwrap();
+
+    let client = YadiskClient::with_base_url("http://127.0.0.1:9", "token").unwrap();
+    let engine = SyncEngine::new(client, index, cache_dir.path().to_path_buf())
+        .with_symlink_policy(SymlinkPolicy::Link);
+    materialize_sync_tree(&engine, sync_dir.path(), cache_dir.path(), "/")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        tokio::fs::read_link(docs.join("Link")).await.unwrap(),
+        PathBuf::from("Out.txt")
     );
-    assert!(tokio::fs::metadata(&stale_cache).await.is_err());
-    assert!(tokio::fs::metadata(&keep_cache).await.is_ok());
+    assert_eq!(tokio::fs::read(&external).await.unwrap(), b"keep");
+}
+
+#[tokio::test]
+async fn materialize_skips_missing_syncing_files() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let index = IndexStore::from_pool(pool);
+    index.init().await.unwrap();
+    let sync_dir = tempdir().unwrap();
+    let cache_dir = tempdir().unwrap();
+
+    let item = index
+        .upsert_item(&ItemInput {
+            path: "/Docs/Deleting.txt".into(),
+            parent_path: Some("/Docs".into()),
+            name: "Deleting.txt".into(),
+            item_type: ItemType::File,
+            size: Some(5),
+            modified: None,
+            hash: None,
+            resource_id: None,
+            last_synced_hash: None,
+            last_synced_modified: None,
+        })
+        .await
+        .unwrap();
+    index
+        .set_state(item.id, FileState::Syncing, true, None)
+        .await
+        .unwrap();
+
+    let client = YadiskClient::with_base_url("http://127.0.0.1:9", "token").unwrap();
+    let engine = SyncEngine::new(client, index, cache_dir.path().to_path_buf());
+    materialize_sync_tree(&engine, sync_dir.path(), cache_dir.path(), "/")
+        .await
+        .unwrap();
+
+    assert!(
+        tokio::fs::metadata(sync_dir.path().join("Docs/Deleting.txt"))
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn prune_removed_materialized_paths_removes_stale_paths_only() {
+    let sync_dir = tempdir().unwrap();
+    let cache_dir = tempdir().unwrap();
+    let trash_dir = tempdir().unwrap();
+    let _trash_guard = install_test_trash_dir(trash_dir.path().to_path_buf());
+    let docs = sync_dir.path().join("Docs");
+    tokio::fs::create_dir_all(&docs).await.unwrap();
+    let stale = docs.join("Stale.txt");
+    let keep = sync_dir.path().join("Keep.txt");
+    tokio::fs::write(&stale, b"old").await.unwrap();
+    tokio::fs::write(&keep, b"keep").await.unwrap();
+    let stale_cache = cache_dir.path().join("Docs/Stale.txt");
+    let keep_cache = cache_dir.path().join("Keep.txt");
+    tokio::fs::create_dir_all(stale_cache.parent().unwrap())
+        .await
+        .unwrap();
+    tokio::fs::write(&stale_cache, b"old-cache").await.unwrap();
+    tokio::fs::write(&keep_cache, b"keep-cache").await.unwrap();
+
+    let previous = std::collections::HashSet::from([
+        sync_dir.path().to_path_buf(),
+        docs.clone(),
+        stale.clone(),
+    ]);
+    let current = std::collections::HashSet::from([sync_dir.path().to_path_buf(), keep.clone()]);
+
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let index = IndexStore::from_pool(pool);
+    index.init().await.unwrap();
+    let client = YadiskClient::with_base_url("http://127.0.0.1:9", "token").unwrap();
+    let engine = SyncEngine::new(client, index, cache_dir.path().to_path_buf());
+    prune_removed_materialized_paths(
+        &engine,
+        &previous,
+        &current,
+        sync_dir.path(),
+        cache_dir.path(),
+    )
+    .await
+    .unwrap();
+
+    assert!(tokio::fs::metadata(&stale).await.is_err());
+    assert!(tokio::fs::metadata(&docs).await.is_err());
+    assert!(tokio::fs::metadata(&keep).await.is_ok());
+    assert!(
+        tokio::fs::metadata(trash_dir.path().join("Stale.txt"))
+            .await
+            .is_ok()
+    );
+    assert!(
+        tokio::fs::metadata(trash_dir.path().join("Docs"))
+            .await
+            .is_ok()
+    );
+    assert!(tokio::fs::metadata(&stale_cache).await.is_err());
+    assert!(tokio::fs::metadata(&keep_cache).await.is_ok());
+}
+
+#[tokio::test]
+async fn reconcile_queues_upload_for_file_edited_while_stopped() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let index = IndexStore::from_pool(pool);
+    index.init().await.unwrap();
+    let sync_dir = tempdir().unwrap();
+    let cache_dir = tempdir().unwrap();
+
+    index
+        .upsert_item(&ItemInput {
+            path: "disk:/Docs".into(),
+            parent_path: Some("disk:/".into()),
+            name: "Docs".into(),
+            item_type: ItemType::Dir,
+            size: None,
+            modified: None,
+            hash: None,
+            resource_id: Some("rid-docs".into()),
+            last_synced_hash: None,
+            last_synced_modified: None,
+        })
+        .await
+        .unwrap();
+    let item = index
+        .upsert_item(&ItemInput {
+            path: "disk:/Docs/A.txt".into(),
+            parent_path: Some("disk:/Docs".into()),
+            name: "A.txt".into(),
+            item_type: ItemType::File,
+            size: Some(5),
+            modified: Some(1),
+            hash: Some(format!("{:x}", md5::compute(b"hello"))),
+            resource_id: Some("rid-a".into()),
+            last_synced_hash: Some(format!("{:x}", md5::compute(b"hello"))),
+            last_synced_modified: Some(1),
+        })
+        .await
+        .unwrap();
+    index
+        .set_state(item.id, FileState::Cached, false, None)
+        .await
+        .unwrap();
+    let sync_file = sync_dir.path().join("Docs/A.txt");
+    tokio::fs::create_dir_all(sync_file.parent().unwrap())
+        .await
+        .unwrap();
+    tokio::fs::write(&sync_file, b"edited on a plane")
+        .await
+        .unwrap();
+
+    let client = YadiskClient::with_base_url("http://127.0.0.1:9", "token").unwrap();
+    let engine = SyncEngine::new(client, index, cache_dir.path().to_path_buf());
+    let mut fingerprints = FingerprintCache::new();
+    let queued = reconcile_local_changes(
+        &engine,
+        sync_dir.path(),
+        cache_dir.path(),
+        "disk:/",
+        sync_dir.path(),
+        &mut fingerprints,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(queued, 1);
+    assert!(engine.has_active_or_queued_work().await.unwrap());
+    assert_eq!(
+        engine.state_for_path("disk:/Docs/A.txt").await.unwrap(),
+        Some(crate::sync::engine::PathDisplayState::Syncing)
+    );
+    let cache_file =
+        crate::sync::paths::cache_path_for(cache_dir.path(), "disk:/Docs/A.txt").unwrap();
+    let cached = tokio::fs::read(&cache_file).await.unwrap();
+    assert_eq!(cached, b"edited on a plane");
+}
+
+#[tokio::test]
+async fn reconcile_keeps_cloud_only_file_never_materialized() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let index = IndexStore::from_pool(pool);
+    index.init().await.unwrap();
+    let sync_dir = tempdir().unwrap();
+    let cache_dir = tempdir().unwrap();
+
+    index
+        .upsert_item(&ItemInput {
+            path: "disk:/Docs".into(),
+            parent_path: Some("disk:/".into()),
+            name: "Docs".into(),
+            item_type: ItemType::Dir,
+            size: None,
+            modified: None,
+            hash: None,
+            resource_id: Some("rid-docs".into()),
+            last_synced_hash: None,
+            last_synced_modified: None,
+        })
+        .await
+        .unwrap();
+    let item = index
+        .upsert_item(&ItemInput {
+            path: "disk:/Docs/FromCloud.txt".into(),
+            parent_path: Some("disk:/Docs".into()),
+            name: "FromCloud.txt".into(),
+            item_type: ItemType::File,
+            size: Some(5),
+            modified: Some(1),
+            hash: Some(format!("{:x}", md5::compute(b"hello"))),
+            resource_id: Some("rid-cloud".into()),
+            last_synced_hash: None,
+            last_synced_modified: None,
+        })
+        .await
+        .unwrap();
+    index
+        .set_state(item.id, FileState::CloudOnly, false, None)
+        .await
+        .unwrap();
+    tokio::fs::create_dir_all(sync_dir.path().join("Docs"))
+        .await
+        .unwrap();
+
+    let client = YadiskClient::with_base_url("http://127.0.0.1:9", "token").unwrap();
+    let engine = SyncEngine::new(client, index, cache_dir.path().to_path_buf());
+    let mut fingerprints = FingerprintCache::new();
+    let queued = reconcile_local_changes(
+        &engine,
+        sync_dir.path(),
+        cache_dir.path(),
+        "disk:/",
+        sync_dir.path(),
+        &mut fingerprints,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(queued, 0);
+    assert!(!engine.has_active_or_queued_work().await.unwrap());
+    assert_eq!(
+        engine
+            .state_for_path("disk:/Docs/FromCloud.txt")
+            .await
+            .unwrap(),
+        Some(crate::sync::engine::PathDisplayState::CloudOnly)
+    );
+}
+
+#[tokio::test]
+async fn reconcile_leaves_untouched_copy_of_remotely_changed_file() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let index = IndexStore::from_pool(pool);
+    index.init().await.unwrap();
+    let sync_dir = tempdir().unwrap();
+    let cache_dir = tempdir().unwrap();
+
+    index
+        .upsert_item(&ItemInput {
+            path: "disk:/Docs".into(),
+            parent_path: Some("disk:/".into()),
+            name: "Docs".into(),
+            item_type: ItemType::Dir,
+            size: None,
+            modified: None,
+            hash: None,
+            resource_id: Some("rid-docs".into()),
+            last_synced_hash: None,
+            last_synced_modified: None,
+        })
+        .await
+        .unwrap();
+    let file = |content: &[u8]| ItemInput {
+        path: "disk:/Docs/A.txt".into(),
+        parent_path: Some("disk:/Docs".into()),
+        name: "A.txt".into(),
+        item_type: ItemType::File,
+        size: Some(content.len() as i64),
+        modified: Some(1),
+        hash: Some(format!("{:x}", md5::compute(content))),
+        resource_id: Some("rid-a".into()),
+        last_synced_hash: Some(format!("{:x}", md5::compute(content))),
+        last_synced_modified: Some(1),
+    };
+    let item = index.upsert_item(&file(b"hello")).await.unwrap();
+    index
+        .set_state(item.id, FileState::Cached, false, None)
+        .await
+        .unwrap();
+    let cache_file =
+        crate::sync::paths::cache_path_for(cache_dir.path(), "disk:/Docs/A.txt").unwrap();
+    tokio::fs::create_dir_all(cache_file.parent().unwrap())
+        .await
+        .unwrap();
+    tokio::fs::write(&cache_file, b"hello").await.unwrap();
+
+    let client = YadiskClient::with_base_url("http://127.0.0.1:9", "token").unwrap();
+    let engine = SyncEngine::new(client, index.clone(), cache_dir.path().to_path_buf());
+    materialize_sync_tree(&engine, sync_dir.path(), cache_dir.path(), "disk:/")
+        .await
+        .unwrap();
+
+    // The file changes in the cloud and the new version is downloaded while
+    // the sync-folder copy still holds the old one.
+    index
+        .upsert_item(&file(b"newer from the cloud"))
+        .await
+        .unwrap();
+    tokio::fs::write(&cache_file, b"newer from the cloud")
+        .await
+        .unwrap();
+
+    let mut fingerprints = FingerprintCache::new();
+    let queued = reconcile_local_changes(
+        &engine,
+        sync_dir.path(),
+        cache_dir.path(),
+        "disk:/",
+        sync_dir.path(),
+        &mut fingerprints,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(queued, 0);
+    assert!(!engine.has_active_or_queued_work().await.unwrap());
+    materialize_sync_tree(&engine, sync_dir.path(), cache_dir.path(), "disk:/")
+        .await
+        .unwrap();
+    assert_eq!(
+        tokio::fs::read(sync_dir.path().join("Docs/A.txt"))
+            .await
+            .unwrap(),
+        b"newer from the cloud"
+    );
+}
+
+#[tokio::test]
+async fn materialize_keeps_local_edit_of_cached_file() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let index = IndexStore::from_pool(pool);
+    index.init().await.unwrap();
+    let sync_dir = tempdir().unwrap();
+    let cache_dir = tempdir().unwrap();
+
+    let item = index
+        .upsert_item(&ItemInput {
+            path: "/A.txt".into(),
+            parent_path: Some("/".into()),
+            name: "A.txt".into(),
+            item_type: ItemType::File,
+            size: Some(5),
+            modified: None,
+            hash: None,
+            resource_id: None,
+            last_synced_hash: None,
+            last_synced_modified: None,
+        })
+        .await
+        .unwrap();
+    index
+        .set_state(item.id, FileState::Cached, false, None)
+        .await
+        .unwrap();
+    let cache_file = crate::sync::paths::cache_path_for(cache_dir.path(), "/A.txt").unwrap();
+    tokio::fs::create_dir_all(cache_file.parent().unwrap())
+        .await
+        .unwrap();
+    tokio::fs::write(&cache_file, b"hello").await.unwrap();
+
+    let client = YadiskClient::with_base_url("http://127.0.0.1:9", "token").unwrap();
+    let engine = SyncEngine::new(client, index, cache_dir.path().to_path_buf());
+    materialize_sync_tree(&engine, sync_dir.path(), cache_dir.path(), "/")
+        .await
+        .unwrap();
+    let local_file = sync_dir.path().join("A.txt");
+    tokio::fs::write(&local_file, b"edited locally")
+        .await
+        .unwrap();
+    materialize_sync_tree(&engine, sync_dir.path(), cache_dir.path(), "/")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        tokio::fs::read(&local_file).await.unwrap(),
+        b"edited locally"
+    );
+}
+
+#[test]
+fn reconcile_is_due_initially_and_after_interval() {
+    assert!(is_reconcile_due(None, Duration::from_secs(300)));
+    assert!(!is_reconcile_due(
+        Some(Instant::now()),
+        Duration::from_secs(300)
+    ));
+    assert!(is_reconcile_due(Some(Instant::now()), Duration::ZERO));
+}
+
+#[tokio::test]
+async fn split_rename_is_paired_into_move_by_inode() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let index = IndexStore::from_pool(pool);
+    index.init().await.unwrap();
+    let sync_dir = tempdir().unwrap();
+    let cache_dir = tempdir().unwrap();
+
+    index
+        .upsert_item(&ItemInput {
+            path: "disk:/Photos/A.jpg".into(),
+            parent_path: Some("disk:/Photos".into()),
+            name: "A.jpg".into(),
+            item_type: ItemType::File,
+            size: Some(4),
+            modified: None,
+            hash: None,
+            resource_id: Some("rid-a".into()),
+            last_synced_hash: None,
+            last_synced_modified: None,
+        })
+        .await
+        .unwrap();
+    let old_file = sync_dir.path().join("Photos/A.jpg");
+    let new_file = sync_dir.path().join("Archive/2024/A.jpg");
+    tokio::fs::create_dir_all(old_file.parent().unwrap())
+        .await
+        .unwrap();
+    tokio::fs::create_dir_all(new_file.parent().unwrap())
+        .await
+        .unwrap();
+    tokio::fs::write(&old_file, b"jpeg").await.unwrap();
+
+    let client = YadiskClient::with_base_url("http://127.0.0.1:9", "token").unwrap();
+    let engine = SyncEngine::new(client, index, cache_dir.path().to_path_buf());
+    assert_eq!(
+        record_local_identities(&engine, sync_dir.path(), "disk:/")
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        record_local_identities(&engine, sync_dir.path(), "disk:/")
+            .await
+            .unwrap(),
+        0
+    );
+    tokio::fs::rename(&old_file, &new_file).await.unwrap();
+
+    // Delete reported first: held, then paired with the create.
+    let mut detector = MoveDetector::new(Duration::from_secs(60));
+    let identity = engine
+        .local_identity_for_path("disk:/Photos/A.jpg")
+        .await
+        .unwrap()
+        .unwrap();
+    detector.hold_delete("disk:/Photos/A.jpg".into(), identity);
+    let event = pair_local_move(
+        &engine,
+        sync_dir.path(),
+        &LocalNames::default(),
+        &mut detector,
+        LocalEvent::Upload {
+            path: "disk:/Archive/2024/A.jpg".into(),
+        },
+    )
+    .await;
+    assert_eq!(
+        event,
+        LocalEvent::Move {
+            from: "disk:/Photos/A.jpg".into(),
+            to: "disk:/Archive/2024/A.jpg".into(),
+        }
+    );
+    assert!(!detector.has_pending());
+
+    // Create reported first: paired from the index, late delete swallowed.
+    let event = pair_local_move(
+        &engine,
+        sync_dir.path(),
+        &LocalNames::default(),
+        &mut detector,
+        LocalEvent::Upload {
+            path: "disk:/Archive/2024/A.jpg".into(),
+        },
+    )
+    .await;
+    assert!(matches!(event, LocalEvent::Move { .. }));
+    assert!(detector.consume_moved_away("disk:/Photos/A.jpg"));
+}
+
+#[tokio::test]
+async fn upload_of_unrelated_file_is_not_paired() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    let index = IndexStore::from_pool(pool);
+    index.init().await.unwrap();
+    let sync_dir = tempdir().unwrap();
+    let cache_dir = tempdir().unwrap();
+    tokio::fs::write(sync_dir.path().join("B.txt"), b"new")
+        .await
+        .unwrap();
+
+    let client = YadiskClient::with_base_url("http://127.0.0.1:9", "token").unwrap();
+    let engine = SyncEngine::new(client, index, cache_dir.path().to_path_buf());
+    let mut detector = MoveDetector::new(Duration::from_secs(60));
+    let event = pair_local_move(
+        &engine,
+        sync_dir.path(),
+        &LocalNames::default(),
+        &mut detector,
+        LocalEvent::Upload {
+            path: "disk:/B.txt".into(),
+        },
+    )
+    .await;
+    assert_eq!(
+        event,
+        LocalEvent::Upload {
+            path: "disk:/B.txt".into()
+        }
+    );
+}
+
+#[test]
+fn writing_a_path_again_cancels_its_held_delete() {
+    let identity = LocalFileIdentity {
+        device: 1,
+        inode: 7,
+        size: 5,
+        modified: 1_700_000_000,
+    };
+    let mut detector = MoveDetector::new(Duration::ZERO);
+    detector.hold_delete("disk:/A.txt".into(), identity);
+    detector.hold_delete("disk:/Dir".into(), identity);
+    detector.hold_delete("disk:/Gone.txt".into(), identity);
+
+    assert!(cancel_recreated_delete(
+        &mut detector,
+        &LocalEvent::Upload {
+            path: "disk:/A.txt".into()
+        }
+    ));
+    assert!(cancel_recreated_delete(
+        &mut detector,
+        &LocalEvent::Mkdir {
+            path: "disk:/Dir".into()
+        }
+    ));
+    assert!(!cancel_recreated_delete(
+        &mut detector,
+        &LocalEvent::Upload {
+            path: "disk:/Other.txt".into()
+        }
+    ));
+    assert_eq!(detector.take_expired(), vec!["disk:/Gone.txt".to_string()]);
 }
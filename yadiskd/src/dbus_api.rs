@@ -4,6 +4,7 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use std::process::Command;
 use std::sync::Arc;
+use std::time::Duration;
 
 use thiserror::Error;
 use tokio::sync::RwLock;
@@ -14,10 +15,15 @@ use yadisk_integrations::ids::{
 };
 use zbus::{interface, object_server::SignalEmitter};
 
+use crate::pause::{PauseState, now_unix};
 use crate::storage::{OAuthState, TokenStorage};
+use crate::sync::conflict::ConflictChoice;
 use crate::sync::engine::EngineError;
 use crate::sync::engine::PathDisplayState;
 use crate::sync::engine::SyncEngine;
+use crate::sync::engine::VersionInfo;
+use crate::sync::pin_rules::PinRule;
+use crate::sync::queue::OpFilter;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PathState {
@@ -48,6 +54,20 @@ pub struct ConflictInfo {
 }
 
 type FullStateSnapshot = (Vec<(String, String)>, Vec<(u64, String, String)>);
+/// `(id, path, renamed_local, reason, local_size, local_modified,
+/// remote_size, remote_modified)`; unknown sizes and times are `-1`.
+type ConflictDetailsRow = (u64, String, String, String, i64, i64, i64, i64);
+/// `(id, kind, path, payload, last_error, attempts, first_failed_at,
+/// last_failed_at)`; a missing payload is an empty string.
+type FailedOpRow = (u64, String, String, String, String, u32, i64, i64);
+/// `(id, kind, path, payload, attempt, retry_at, priority, running)`; a missing
+/// payload is an empty string and a missing `retry_at` is `-1`.
+type OperationRow = (u64, String, String, String, u32, i64, i32, bool);
+/// `(path, origin, files, held_at)`; `origin` is `local` or `cloud`.
+type PendingDeleteRow = (String, String, i64, i64);
+/// `(id, pattern, max_size, max_age_secs, created_at)`; a limit that is not
+/// set is `-1`.
+type PinRuleRow = (u64, String, i64, i64, i64);
 
 #[derive(Debug, Error)]
 pub enum DbusServiceError {
@@ -136,6 +156,16 @@ impl SyncDbusService {
         }
     }
 
+    async fn remove_conflict(&self, id: u64) -> zbus::fdo::Result<()> {
+        let mut conflicts = self.conflicts.write().await;
+        let before = conflicts.len();
+        conflicts.retain(|conflict| conflict.id != id);
+        if conflicts.len() == before {
+            return Err(map_to_fdo(DbusServiceError::NotFound));
+        }
+        Ok(())
+    }
+
     async fn full_state_snapshot(&self) -> zbus::fdo::Result<FullStateSnapshot> {
         if let Some(engine) = &self.backend {
             let items = engine
@@ -186,9 +216,29 @@ impl SyncDbusService {
     }
 }
 
+fn pin_rule_from_args(
+    pattern: &str,
+    max_size: i64,
+    max_age_secs: i64,
+) -> zbus::fdo::Result<PinRule> {
+    let mut rule = PinRule::new(pattern).map_err(zbus::fdo::Error::InvalidArgs)?;
+    if let Ok(max_size) = u64::try_from(max_size) {
+        rule = rule.with_max_size(max_size);
+    }
+    if let Ok(max_age_secs) = u64::try_from(max_age_secs) {
+        rule = rule.with_max_age(Duration::from_secs(max_age_secs));
+    }
+    Ok(rule)
+}
+
 fn map_engine_error(err: EngineError) -> zbus::fdo::Error {
     match err {
-        EngineError::MissingItem(_) => map_to_fdo(DbusServiceError::NotFound),
+        EngineError::MissingItem(_)
+        | EngineError::MissingConflict(_)
+        | EngineError::MissingFailedOp(_)
+        | EngineError::MissingOperation(_)
+        | EngineError::MissingPinRule(_)
+        | EngineError::NotInTrash(_) => map_to_fdo(DbusServiceError::NotFound),
         _ => map_to_fdo(DbusServiceError::Failed),
     }
 }
@@ -196,6 +246,7 @@ fn map_engine_error(err: EngineError) -> zbus::fdo::Error {
 pub struct ControlDbusService {
     backend: Option<Arc<SyncEngine>>,
     daemon_status: Arc<RwLock<(String, String)>>,
+    pause: Arc<PauseState>,
     auth_override: RwLock<Option<(String, String)>>,
     auth_session: RwLock<Option<AuthSession>>,
     integration_override: RwLock<Option<(String, String)>>,
@@ -224,13 +275,23 @@ impl ControlDbusService {
         Self {
             backend: Some(engine),
             daemon_status,
+            pause: Arc::new(PauseState::default()),
             auth_override: RwLock::new(None),
             auth_session: RwLock::new(None),
             integration_override: RwLock::new(None),
         }
     }
 
+    /// Shares the daemon's pause state instead of a private one.
+    pub fn with_pause(mut self, pause: Arc<PauseState>) -> Self {
+        self.pause = pause;
+        self
+    }
+
     async fn daemon_status_tuple(&self) -> (String, String) {
+        if let Some(reason) = self.pause.current_reason() {
+            return ("paused".to_string(), reason.message().to_string());
+        }
         if self.backend.is_some() {
             return self.daemon_status.read().await.clone();
         }
@@ -354,6 +415,7 @@ impl Default for ControlDbusService {
         Self {
             backend: None,
             daemon_status: Self::daemon_status_handle(),
+            pause: Arc::new(PauseState::default()),
             auth_override: RwLock::new(None),
             auth_session: RwLock::new(None),
             integration_override: RwLock::new(None),
@@ -405,7 +467,7 @@ impl SyncDbusService {
         eprintln!("[yadiskd] dbus Download path={path}");
         if let Some(engine) = &self.backend {
             for candidate in [&slash, &disk] {
-                match engine.enqueue_download(candidate).await {
+                match engine.enqueue_download_on_demand(candidate).await {
                     Ok(_) => {
                         eprintln!("[yadiskd] dbus Download queued path={candidate}");
                         return Ok(());
@@ -420,6 +482,24 @@ impl SyncDbusService {
         Ok(())
     }
 
+    /// Downloads the files after `path` in its folder ahead of use; called
+    /// when a file is opened. Returns how many downloads were queued.
+    async fn prefetch_siblings(&self, path: &str) -> zbus::fdo::Result<u32> {
+        let [slash, disk] = Self::path_candidates(path).map_err(map_to_fdo)?;
+        eprintln!("[yadiskd] dbus PrefetchSiblings path={path}");
+        let Some(engine) = &self.backend else {
+            return Ok(0);
+        };
+        for candidate in [&slash, &disk] {
+            match engine.prefetch_siblings(candidate).await {
+                Ok(count) => return Ok(u32::try_from(count).unwrap_or(u32::MAX)),
+                Err(EngineError::MissingItem(_)) => continue,
+                Err(err) => return Err(map_engine_error(err)),
+            }
+        }
+        Err(map_to_fdo(DbusServiceError::NotFound))
+    }
+
     async fn pin(&self, path: &str, pin: bool) -> zbus::fdo::Result<()> {
         let [slash, disk] = Self::path_candidates(path).map_err(map_to_fdo)?;
         eprintln!("[yadiskd] dbus Pin path={path} pin={pin}");
@@ -483,6 +563,57 @@ impl SyncDbusService {
         Ok(())
     }
 
+    /// Copies `from` to `to` on the server, without downloading or
+    /// uploading the content.
+    async fn copy(&self, from: &str, to: &str) -> zbus::fdo::Result<()> {
+        let [from_slash, from_disk] = Self::path_candidates(from).map_err(map_to_fdo)?;
+        let [to_slash, to_disk] = Self::path_candidates(to).map_err(map_to_fdo)?;
+        eprintln!("[yadiskd] dbus Copy from={from} to={to}");
+        if let Some(engine) = &self.backend {
+            for (source, target) in [(&from_slash, &to_slash), (&from_disk, &to_disk)] {
+                match engine.enqueue_copy(source, target).await {
+                    Ok(_) => {
+                        eprintln!("[yadiskd] dbus Copy queued from={source} to={target}");
+                        return Ok(());
+                    }
+                    Err(EngineError::MissingItem(_)) => continue,
+                    Err(err) => return Err(map_engine_error(err)),
+                }
+            }
+            return Err(map_to_fdo(DbusServiceError::NotFound));
+        }
+        if !self.states.read().await.contains_key(&from_slash) {
+            return Err(map_to_fdo(DbusServiceError::NotFound));
+        }
+        self.states
+            .write()
+            .await
+            .insert(to_slash, PathState::Syncing);
+        Ok(())
+    }
+
+    /// Restores a path deleted from this machine out of the Yandex trash
+    /// and downloads it again.
+    async fn restore_deleted(&self, path: &str) -> zbus::fdo::Result<()> {
+        let [slash, disk] = Self::path_candidates(path).map_err(map_to_fdo)?;
+        eprintln!("[yadiskd] dbus RestoreDeleted path={path}");
+        if let Some(engine) = &self.backend {
+            for candidate in [&slash, &disk] {
+                match engine.restore_deleted(candidate).await {
+                    Ok(()) => {
+                        eprintln!("[yadiskd] dbus RestoreDeleted done path={candidate}");
+                        return Ok(());
+                    }
+                    Err(EngineError::MissingItem(_)) => continue,
+                    Err(err) => return Err(map_engine_error(err)),
+                }
+            }
+            return Err(map_to_fdo(DbusServiceError::NotFound));
+        }
+        self.states.write().await.insert(slash, PathState::Syncing);
+        Ok(())
+    }
+
     async fn get_state(&self, path: &str) -> zbus::fdo::Result<String> {
         let [slash, disk] = Self::path_candidates(path).map_err(map_to_fdo)?;
         if let Some(engine) = &self.backend {
@@ -520,6 +651,315 @@ impl SyncDbusService {
             .collect())
     }
 
+    async fn list_conflict_details(&self) -> zbus::fdo::Result<Vec<ConflictDetailsRow>> {
+        if let Some(engine) = &self.backend {
+            let details = engine
+                .list_conflict_details()
+                .await
+                .map_err(map_engine_error)?;
+            return Ok(details
+                .into_iter()
+                .map(|details| {
+                    let version = |info: Option<VersionInfo>| {
+                        info.map_or((-1, -1), |info| {
+                            (i64::try_from(info.size).unwrap_or(i64::MAX), info.modified)
+                        })
+                    };
+                    let (local_size, local_modified) = version(details.local);
+                    let (remote_size, remote_modified) = version(details.remote);
+                    let conflict = details.conflict;
+                    (
+                        u64::try_from(conflict.id).unwrap_or(0),
+                        conflict.path,
+                        conflict.renamed_local,
+                        conflict.reason,
+                        local_size,
+                        local_modified,
+                        remote_size,
+                        remote_modified,
+                    )
+                })
+                .collect());
+        }
+        let conflicts = self.conflicts.read().await;
+        Ok(conflicts
+            .iter()
+            .map(|c| {
+                (
+                    c.id,
+                    c.path.clone(),
+                    c.renamed_local.clone(),
+                    "both-changed".to_string(),
+                    -1,
+                    -1,
+                    -1,
+                    -1,
+                )
+            })
+            .collect())
+    }
+
+    async fn resolve_conflict(&self, id: u64, choice: &str) -> zbus::fdo::Result<()> {
+        let choice: ConflictChoice = choice.parse().map_err(zbus::fdo::Error::InvalidArgs)?;
+        eprintln!("[yadiskd] dbus ResolveConflict id={id} choice={choice:?}");
+        if let Some(engine) = &self.backend {
+            let id = i64::try_from(id).map_err(|_| map_to_fdo(DbusServiceError::NotFound))?;
+            return engine
+                .resolve_conflict(id, choice)
+                .await
+                .map_err(map_engine_error);
+        }
+        self.remove_conflict(id).await
+    }
+
+    async fn dismiss_conflict(&self, id: u64) -> zbus::fdo::Result<()> {
+        eprintln!("[yadiskd] dbus DismissConflict id={id}");
+        if let Some(engine) = &self.backend {
+            let id = i64::try_from(id).map_err(|_| map_to_fdo(DbusServiceError::NotFound))?;
+            return engine.dismiss_conflict(id).await.map_err(map_engine_error);
+        }
+        self.remove_conflict(id).await
+    }
+
+    async fn list_failed_ops(&self) -> zbus::fdo::Result<Vec<FailedOpRow>> {
+        let Some(engine) = &self.backend else {
+            return Ok(Vec::new());
+        };
+        let failed = engine.list_failed_ops().await.map_err(map_engine_error)?;
+        Ok(failed
+            .into_iter()
+            .map(|op| {
+                (
+                    u64::try_from(op.id).unwrap_or(0),
+                    op.kind.as_str().to_string(),
+                    op.path,
+                    op.payload.unwrap_or_default(),
+                    op.last_error,
+                    op.attempts,
+                    op.first_failed_at,
+                    op.last_failed_at,
+                )
+            })
+            .collect())
+    }
+
+    async fn retry_failed_op(&self, id: u64) -> zbus::fdo::Result<()> {
+        eprintln!("[yadiskd] dbus RetryFailedOp id={id}");
+        let Some(engine) = &self.backend else {
+            return Err(map_to_fdo(DbusServiceError::NotFound));
+        };
+        let id = i64::try_from(id).map_err(|_| map_to_fdo(DbusServiceError::NotFound))?;
+        engine.retry_failed_op(id).await.map_err(map_engine_error)
+    }
+
+    async fn retry_all_failed_ops(&self) -> zbus::fdo::Result<u32> {
+        eprintln!("[yadiskd] dbus RetryAllFailedOps");
+        let Some(engine) = &self.backend else {
+            return Ok(0);
+        };
+        let count = engine
+            .retry_all_failed_ops()
+            .await
+            .map_err(map_engine_error)?;
+        Ok(u32::try_from(count).unwrap_or(u32::MAX))
+    }
+
+    async fn discard_failed_op(&self, id: u64) -> zbus::fdo::Result<()> {
+        eprintln!("[yadiskd] dbus DiscardFailedOp id={id}");
+        let Some(engine) = &self.backend else {
+            return Err(map_to_fdo(DbusServiceError::NotFound));
+        };
+        let id = i64::try_from(id).map_err(|_| map_to_fdo(DbusServiceError::NotFound))?;
+        engine.discard_failed_op(id).await.map_err(map_engine_error)
+    }
+
+    async fn list_operations(
+        &self,
+        kind: &str,
+        path_prefix: &str,
+    ) -> zbus::fdo::Result<Vec<OperationRow>> {
+        let filter = OpFilter::new(kind, path_prefix).map_err(zbus::fdo::Error::InvalidArgs)?;
+        let Some(engine) = &self.backend else {
+            return Ok(Vec::new());
+        };
+        let ops = engine
+            .list_operations(&filter)
+            .await
+            .map_err(map_engine_error)?;
+        Ok(ops
+            .into_iter()
+            .map(|op| {
+                (
+                    u64::try_from(op.id).unwrap_or(0),
+                    op.kind.as_str().to_string(),
+                    op.path,
+                    op.payload.unwrap_or_default(),
+                    op.attempt,
+                    op.retry_at.unwrap_or(-1),
+                    op.priority,
+                    op.running,
+                )
+            })
+            .collect())
+    }
+
+    async fn cancel_operation(&self, id: u64) -> zbus::fdo::Result<()> {
+        eprintln!("[yadiskd] dbus CancelOperation id={id}");
+        let Some(engine) = &self.backend else {
+            return Err(map_to_fdo(DbusServiceError::NotFound));
+        };
+        let id = i64::try_from(id).map_err(|_| map_to_fdo(DbusServiceError::NotFound))?;
+        engine
+            .cancel_operation(id)
+            .await
+            .map(|_| ())
+            .map_err(map_engine_error)
+    }
+
+    async fn set_priority(&self, id: u64, priority: i32) -> zbus::fdo::Result<()> {
+        eprintln!("[yadiskd] dbus SetPriority id={id} priority={priority}");
+        let Some(engine) = &self.backend else {
+            return Err(map_to_fdo(DbusServiceError::NotFound));
+        };
+        let id = i64::try_from(id).map_err(|_| map_to_fdo(DbusServiceError::NotFound))?;
+        engine
+            .set_operation_priority(id, priority)
+            .await
+            .map_err(map_engine_error)
+    }
+
+    async fn pause_queue(&self) -> zbus::fdo::Result<()> {
+        eprintln!("[yadiskd] dbus PauseQueue");
+        if let Some(engine) = &self.backend {
+            engine.pause_queue();
+        }
+        Ok(())
+    }
+
+    async fn resume_queue(&self) -> zbus::fdo::Result<()> {
+        eprintln!("[yadiskd] dbus ResumeQueue");
+        if let Some(engine) = &self.backend {
+            engine.resume_queue();
+        }
+        Ok(())
+    }
+
+    async fn list_pending_deletes(&self) -> zbus::fdo::Result<Vec<PendingDeleteRow>> {
+        let Some(engine) = &self.backend else {
+            return Ok(Vec::new());
+        };
+        let pending = engine
+            .list_pending_deletes()
+            .await
+            .map_err(map_engine_error)?;
+        Ok(pending
+            .into_iter()
+            .map(|held| {
+                (
+                    held.path,
+                    held.origin.as_str().to_string(),
+                    held.files,
+                    held.held_at,
+                )
+            })
+            .collect())
+    }
+
+    /// Lets a held mass deletion through; returns how many paths it covered.
+    async fn confirm_pending_deletes(&self) -> zbus::fdo::Result<u32> {
+        eprintln!("[yadiskd] dbus ConfirmPendingDeletes");
+        let Some(engine) = &self.backend else {
+            return Ok(0);
+        };
+        let count = engine
+            .confirm_pending_deletes()
+            .await
+            .map_err(map_engine_error)?;
+        Ok(u32::try_from(count).unwrap_or(u32::MAX))
+    }
+
+    /// Brings back what a held mass deletion removed; returns how many paths
+    /// it covered.
+    async fn reject_pending_deletes(&self) -> zbus::fdo::Result<u32> {
+        eprintln!("[yadiskd] dbus RejectPendingDeletes");
+        let Some(engine) = &self.backend else {
+            return Ok(0);
+        };
+        let count = engine
+            .reject_pending_deletes()
+            .await
+            .map_err(map_engine_error)?;
+        Ok(u32::try_from(count).unwrap_or(u32::MAX))
+    }
+
+    async fn list_pin_rules(&self) -> zbus::fdo::Result<Vec<PinRuleRow>> {
+        let Some(engine) = &self.backend else {
+            return Ok(Vec::new());
+        };
+        let rules = engine.list_pin_rules().await.map_err(map_engine_error)?;
+        Ok(rules
+            .into_iter()
+            .map(|rule| {
+                (
+                    u64::try_from(rule.id).unwrap_or_default(),
+                    rule.pattern,
+                    rule.max_size.unwrap_or(-1),
+                    rule.max_age_secs.unwrap_or(-1),
+                    rule.created_at,
+                )
+            })
+            .collect())
+    }
+
+    /// Adds a rule pinning the files whose path matches `pattern`, such as
+    /// `disk:/Work/**` or `*.pdf`; a negative `max_size` or `max_age_secs`
+    /// leaves that limit unset. Rules apply on the next sync of each folder.
+    async fn add_pin_rule(
+        &self,
+        pattern: &str,
+        max_size: i64,
+        max_age_secs: i64,
+    ) -> zbus::fdo::Result<u64> {
+        let rule = pin_rule_from_args(pattern, max_size, max_age_secs)?;
+        eprintln!(
+            "[yadiskd] dbus AddPinRule pattern={pattern} max_size={max_size} max_age_secs={max_age_secs}"
+        );
+        let Some(engine) = &self.backend else {
+            return Err(map_to_fdo(DbusServiceError::Failed));
+        };
+        let id = engine.add_pin_rule(&rule).await.map_err(map_engine_error)?;
+        Ok(u64::try_from(id).unwrap_or_default())
+    }
+
+    async fn update_pin_rule(
+        &self,
+        id: u64,
+        pattern: &str,
+        max_size: i64,
+        max_age_secs: i64,
+    ) -> zbus::fdo::Result<()> {
+        let rule = pin_rule_from_args(pattern, max_size, max_age_secs)?;
+        eprintln!("[yadiskd] dbus UpdatePinRule id={id} pattern={pattern}");
+        let Some(engine) = &self.backend else {
+            return Err(map_to_fdo(DbusServiceError::NotFound));
+        };
+        let id = i64::try_from(id).map_err(|_| map_to_fdo(DbusServiceError::NotFound))?;
+        engine
+            .update_pin_rule(id, &rule)
+            .await
+            .map_err(map_engine_error)
+    }
+
+    /// Files only this rule pinned are unpinned on the next sync.
+    async fn remove_pin_rule(&self, id: u64) -> zbus::fdo::Result<()> {
+        eprintln!("[yadiskd] dbus RemovePinRule id={id}");
+        let Some(engine) = &self.backend else {
+            return Err(map_to_fdo(DbusServiceError::NotFound));
+        };
+        let id = i64::try_from(id).map_err(|_| map_to_fdo(DbusServiceError::NotFound))?;
+        engine.remove_pin_rule(id).await.map_err(map_engine_error)
+    }
+
     async fn get_full_state(&self) -> zbus::fdo::Result<FullStateSnapshot> {
         self.full_state_snapshot().await
     }
@@ -538,6 +978,20 @@ impl SyncDbusService {
         path: &str,
         renamed_local: &str,
     ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    pub async fn queue_changed(
+        ctxt: &SignalEmitter<'_>,
+        pending: u32,
+        paused: bool,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    pub async fn pending_deletes_changed(
+        ctxt: &SignalEmitter<'_>,
+        paths: u32,
+        files: u64,
+    ) -> zbus::Result<()>;
 }
 
 #[interface(name = "me.spaceinbox.yadisk.Control1")]
@@ -647,6 +1101,38 @@ impl ControlDbusService {
         ))
     }
 
+    /// Pauses syncing for `duration_secs`, or until `Resume` when it is 0.
+    async fn pause(&self, duration_secs: u64) -> zbus::fdo::Result<()> {
+        eprintln!("[yadiskd] dbus Pause duration_secs={duration_secs}");
+        if duration_secs == 0 {
+            self.pause.pause();
+        } else {
+            let duration = i64::try_from(duration_secs)
+                .map_err(|_| zbus::fdo::Error::InvalidArgs("duration is too long".to_string()))?;
+            self.pause.pause_until(now_unix().saturating_add(duration));
+        }
+        Ok(())
+    }
+
+    async fn resume(&self) -> zbus::fdo::Result<()> {
+        eprintln!("[yadiskd] dbus Resume");
+        self.pause.resume();
+        Ok(())
+    }
+
+    /// Returns `(paused, reason, until)`; `reason` is one of `user`, `until`,
+    /// `metered` or `battery` and `until` is 0 unless the pause is timed.
+    async fn get_pause_state(&self) -> zbus::fdo::Result<(bool, String, i64)> {
+        Ok(match self.pause.current_reason() {
+            Some(reason) => (
+                true,
+                reason.as_str().to_string(),
+                reason.until().unwrap_or(0),
+            ),
+            None => (false, String::new(), 0),
+        })
+    }
+
     async fn run_integration_check(&self) -> zbus::fdo::Result<(String, String)> {
         let status = Self::detect_integration_status();
         {
@@ -730,6 +1216,24 @@ mod tests {
         assert_eq!(state, "syncing");
     }
 
+    #[tokio::test]
+    async fn copy_requires_known_source() {
+        let service = SyncDbusService::default();
+        assert!(
+            service
+                .copy("/Templates/App", "/Projects/App")
+                .await
+                .is_err()
+        );
+        service.download("/Templates/App").await.unwrap();
+        service
+            .copy("disk:/Templates/App", "/Projects/App")
+            .await
+            .unwrap();
+        let state = service.get_state("/Projects/App").await.unwrap();
+        assert_eq!(state, "syncing");
+    }
+
     #[tokio::test]
     async fn get_state_supports_partial_value() {
         let service = SyncDbusService::default();
@@ -789,6 +1293,55 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn resolve_and_dismiss_remove_conflicts() {
+        let service = SyncDbusService::default();
+        {
+            let mut conflicts = service.conflicts.write().await;
+            for id in [1, 2] {
+                conflicts.push(ConflictInfo {
+                    id,
+                    path: format!("/Docs/{id}.txt"),
+                    renamed_local: format!("/Docs/{id} (conflict).txt"),
+                });
+            }
+        }
+
+        service.resolve_conflict(1, "keep-local").await.unwrap();
+        service.dismiss_conflict(2).await.unwrap();
+        assert!(service.list_conflicts().await.unwrap().is_empty());
+
+        match service
+            .dismiss_conflict(2)
+            .await
+            .expect_err("already closed")
+        {
+            zbus::fdo::Error::Failed(msg) => assert!(msg.contains(DBUS_ERROR_NOT_FOUND)),
+            other => panic!("unexpected error: {other:?}"),
+        }
+        assert!(matches!(
+            service.resolve_conflict(1, "keep-all").await,
+            Err(zbus::fdo::Error::InvalidArgs(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn list_operations_rejects_unknown_filter() {
+        let service = SyncDbusService::default();
+        assert!(
+            service
+                .list_operations("upload", "/My Docs")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(matches!(
+            service.list_operations("uploads", "").await,
+            Err(zbus::fdo::Error::InvalidArgs(_))
+        ));
+        assert!(service.cancel_operation(1).await.is_err());
+    }
+
     #[tokio::test]
     async fn control_service_defaults_to_running_idle() {
         let service = ControlDbusService::default();
@@ -797,6 +1350,31 @@ mod tests {
         assert_eq!(message, "idle");
     }
 
+    #[tokio::test]
+    async fn control_service_pause_is_reported_in_status() {
+        let service = ControlDbusService::default();
+        service.pause(0).await.unwrap();
+        assert_eq!(
+            service.get_pause_state().await.unwrap(),
+            (true, "user".to_string(), 0)
+        );
+        assert_eq!(
+            service.get_daemon_status().await.unwrap(),
+            ("paused".to_string(), "paused by user".to_string())
+        );
+        service.pause(600).await.unwrap();
+        let (paused, reason, until) = service.get_pause_state().await.unwrap();
+        assert!(paused);
+        assert_eq!(reason, "until");
+        assert!(until > now_unix());
+        service.resume().await.unwrap();
+        assert_eq!(
+            service.get_pause_state().await.unwrap(),
+            (false, String::new(), 0)
+        );
+        assert_eq!(service.get_daemon_status().await.unwrap().0, "running");
+    }
+
     #[tokio::test]
     async fn control_service_supports_integration_check_override() {
         let service = ControlDbusService::default();
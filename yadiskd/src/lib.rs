@@ -1,6 +1,7 @@
 pub mod daemon;
 pub mod dbus_api;
 pub mod oauth_flow;
+pub mod pause;
 pub mod storage;
 pub mod sync;
 pub mod token_provider;
@@ -0,0 +1,255 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+
+const POWER_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Why syncing is paused. When several apply, the user's own choice wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseReason {
+    User,
+    Until(i64),
+    Metered,
+    Battery,
+}
+
+impl PauseReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PauseReason::User => "user",
+            PauseReason::Until(_) => "until",
+            PauseReason::Metered => "metered",
+            PauseReason::Battery => "battery",
+        }
+    }
+
+    pub fn message(self) -> &'static str {
+        match self {
+            PauseReason::User => "paused by user",
+            PauseReason::Until(_) => "paused by user for a while",
+            PauseReason::Metered => "paused on metered network",
+            PauseReason::Battery => "paused on battery power",
+        }
+    }
+
+    pub fn until(self) -> Option<i64> {
+        match self {
+            PauseReason::Until(until) => Some(until),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct PauseFlags {
+    user: bool,
+    until: Option<i64>,
+    metered: bool,
+    battery: bool,
+    /// Set by `resume` while metered or battery pausing is active, so the
+    /// user can sync anyway until the condition changes.
+    override_auto: bool,
+}
+
+/// Daemon-wide pause shared by the worker, cloud polling, Control1 and the
+/// tray. Pausing stops queue processing and cloud polling only; the daemon,
+/// its D-Bus services and already cached files stay available.
+#[derive(Debug, Default)]
+pub struct PauseState {
+    flags: Mutex<PauseFlags>,
+}
+
+impl PauseState {
+    pub fn pause(&self) {
+        let mut flags = self.lock();
+        flags.user = true;
+        flags.until = None;
+    }
+
+    /// Pauses until the unix time `until`, after which syncing resumes on
+    /// its own.
+    pub fn pause_until(&self, until: i64) {
+        let mut flags = self.lock();
+        flags.user = false;
+        flags.until = Some(until);
+    }
+
+    pub fn resume(&self) {
+        let mut flags = self.lock();
+        flags.user = false;
+        flags.until = None;
+        flags.override_auto = flags.metered || flags.battery;
+    }
+
+    pub fn set_metered(&self, metered: bool) {
+        let mut flags = self.lock();
+        if flags.metered != metered {
+            flags.metered = metered;
+            flags.override_auto = false;
+        }
+    }
+
+    pub fn set_on_battery(&self, on_battery: bool) {
+        let mut flags = self.lock();
+        if flags.battery != on_battery {
+            flags.battery = on_battery;
+            flags.override_auto = false;
+        }
+    }
+
+    pub fn reason(&self, now: i64) -> Option<PauseReason> {
+        let mut flags = self.lock();
+        if flags.until.is_some_and(|until| until <= now) {
+            flags.until = None;
+        }
+        if flags.user {
+            Some(PauseReason::User)
+        } else if let Some(until) = flags.until {
+            Some(PauseReason::Until(until))
+        } else if flags.override_auto {
+            None
+        } else if flags.metered {
+            Some(PauseReason::Metered)
+        } else if flags.battery {
+            Some(PauseReason::Battery)
+        } else {
+            None
+        }
+    }
+
+    pub fn current_reason(&self) -> Option<PauseReason> {
+        self.reason(now_unix())
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.current_reason().is_some()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PauseFlags> {
+        match self.flags.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+pub(crate) fn now_unix() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|since| since.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Polls NetworkManager and UPower on the system bus and feeds the metered
+/// and on-battery conditions into `pause`. Either check can be disabled;
+/// a missing service is treated as "not metered" / "on AC power".
+pub async fn watch_power_and_network(
+    pause: std::sync::Arc<PauseState>,
+    pause_on_metered: bool,
+    pause_on_battery: bool,
+    shutdown: CancellationToken,
+) {
+    if !pause_on_metered && !pause_on_battery {
+        return;
+    }
+    let connection = match zbus::Connection::system().await {
+        Ok(connection) => connection,
+        Err(err) => {
+            eprintln!("[yadiskd] warning: system D-Bus unavailable, auto pause disabled: {err}");
+            return;
+        }
+    };
+    loop {
+        if pause_on_metered {
+            pause.set_metered(is_metered(&connection).await);
+        }
+        if pause_on_battery {
+            pause.set_on_battery(is_on_battery(&connection).await);
+        }
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = tokio::time::sleep(POWER_POLL_INTERVAL) => {}
+        }
+    }
+}
+
+async fn is_metered(connection: &zbus::Connection) -> bool {
+    let metered = read_property::<u32>(
+        connection,
+        "org.freedesktop.NetworkManager",
+        "/org/freedesktop/NetworkManager",
+        "org.freedesktop.NetworkManager",
+        "Metered",
+    )
+    .await;
+    // NMMetered: 1 = yes, 3 = guessed yes.
+    matches!(metered, Some(1 | 3))
+}
+
+async fn is_on_battery(connection: &zbus::Connection) -> bool {
+    read_property::<bool>(
+        connection,
+        "org.freedesktop.UPower",
+        "/org/freedesktop/UPower",
+        "org.freedesktop.UPower",
+        "OnBattery",
+    )
+    .await
+    .unwrap_or(false)
+}
+
+async fn read_property<T>(
+    connection: &zbus::Connection,
+    destination: &'static str,
+    path: &'static str,
+    interface: &'static str,
+    name: &'static str,
+) -> Option<T>
+where
+    T: TryFrom<zbus::zvariant::OwnedValue>,
+    T::Error: Into<zbus::Error>,
+{
+    let proxy = zbus::Proxy::new(connection, destination, path, interface)
+        .await
+        .ok()?;
+    proxy.get_property::<T>(name).await.ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_pause_wins_over_automatic_reasons() {
+        let pause = PauseState::default();
+        assert_eq!(pause.reason(0), None);
+        pause.set_on_battery(true);
+        pause.set_metered(true);
+        assert_eq!(pause.reason(0), Some(PauseReason::Metered));
+        pause.pause();
+        assert_eq!(pause.reason(0), Some(PauseReason::User));
+    }
+
+    #[test]
+    fn timed_pause_expires() {
+        let pause = PauseState::default();
+        pause.pause_until(100);
+        assert_eq!(pause.reason(99), Some(PauseReason::Until(100)));
+        assert_eq!(pause.reason(99).and_then(PauseReason::until), Some(100));
+        assert_eq!(pause.reason(100), None);
+    }
+
+    #[test]
+    fn resume_overrides_automatic_pause_until_condition_changes() {
+        let pause = PauseState::default();
+        pause.set_metered(true);
+        pause.resume();
+        assert_eq!(pause.reason(0), None);
+        pause.set_metered(true);
+        assert_eq!(pause.reason(0), None);
+        pause.set_metered(false);
+        pause.set_metered(true);
+        assert_eq!(pause.reason(0), Some(PauseReason::Metered));
+    }
+}
@@ -1,5 +1,6 @@
 #![allow(dead_code)]
 
+use std::str::FromStr;
 use std::sync::OnceLock;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -27,7 +28,136 @@ pub enum ConflictDecision {
     NoOp,
     UploadLocal,
     DownloadRemote,
-    KeepBoth { renamed_local: String },
+    KeepBoth {
+        renamed_local: String,
+    },
+    /// Both sides changed and the policy leaves the choice to the user; the
+    /// local file stays untouched until the conflict is resolved.
+    AskUser,
+}
+
+/// How a file changed on both sides is settled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictPolicy {
+    #[default]
+    KeepBoth,
+    PreferLocal,
+    PreferRemote,
+    NewestWins,
+    Ask,
+}
+
+impl ConflictPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConflictPolicy::KeepBoth => "keep-both",
+            ConflictPolicy::PreferLocal => "prefer-local",
+            ConflictPolicy::PreferRemote => "prefer-remote",
+            ConflictPolicy::NewestWins => "newest",
+            ConflictPolicy::Ask => "ask",
+        }
+    }
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "keep-both" => Ok(ConflictPolicy::KeepBoth),
+            "prefer-local" | "local" => Ok(ConflictPolicy::PreferLocal),
+            "prefer-remote" | "remote" => Ok(ConflictPolicy::PreferRemote),
+            "newest" | "newest-wins" => Ok(ConflictPolicy::NewestWins),
+            "ask" => Ok(ConflictPolicy::Ask),
+            other => Err(format!("unknown conflict policy: {other}")),
+        }
+    }
+}
+
+/// The user's answer to an open conflict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictChoice {
+    KeepLocal,
+    KeepRemote,
+    KeepBoth,
+}
+
+impl FromStr for ConflictChoice {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "keep-local" => Ok(ConflictChoice::KeepLocal),
+            "keep-remote" => Ok(ConflictChoice::KeepRemote),
+            "keep-both" => Ok(ConflictChoice::KeepBoth),
+            other => Err(format!("unknown conflict choice: {other}")),
+        }
+    }
+}
+
+/// Global conflict policy with per-subtree overrides; the deepest matching
+/// subtree wins.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConflictPolicies {
+    default: ConflictPolicy,
+    subtrees: Vec<(String, ConflictPolicy)>,
+}
+
+impl ConflictPolicies {
+    pub fn new(default: ConflictPolicy) -> Self {
+        Self {
+            default,
+            subtrees: Vec::new(),
+        }
+    }
+
+    pub fn with_subtree(mut self, root: &str, policy: ConflictPolicy) -> Self {
+        let root = normalize_policy_path(root);
+        self.subtrees.retain(|(existing, _)| *existing != root);
+        self.subtrees.push((root, policy));
+        self
+    }
+
+    /// Parses overrides written as `/Work=prefer-local;/Shared=ask`.
+    pub fn parse_subtrees(mut self, spec: &str) -> Result<Self, String> {
+        for entry in spec
+            .split(';')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (root, policy) = entry
+                .rsplit_once('=')
+                .ok_or_else(|| format!("invalid conflict policy override: {entry}"))?;
+            self = self.with_subtree(root.trim(), policy.parse()?);
+        }
+        Ok(self)
+    }
+
+    pub fn policy_for(&self, path: &str) -> ConflictPolicy {
+        let path = normalize_policy_path(path);
+        self.subtrees
+            .iter()
+            .filter(|(root, _)| {
+                root == "/"
+                    || path == *root
+                    || path
+                        .strip_prefix(root.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+            .max_by_key(|(root, _)| root.len())
+            .map(|(_, policy)| *policy)
+            .unwrap_or(self.default)
+    }
+}
+
+fn normalize_policy_path(path: &str) -> String {
+    let path = path.strip_prefix("disk:").unwrap_or(path);
+    let trimmed = path.trim_matches('/');
+    if trimmed.is_empty() {
+        "/".to_string()
+    } else {
+        format!("/{trimmed}")
+    }
 }
 
 pub fn resolve_conflict(
@@ -35,6 +165,16 @@ pub fn resolve_conflict(
     base: Option<&FileMetadata>,
     local: &FileMetadata,
     remote: &FileMetadata,
+) -> ConflictDecision {
+    resolve_conflict_with_policy(path, base, local, remote, ConflictPolicy::KeepBoth)
+}
+
+pub fn resolve_conflict_with_policy(
+    path: &str,
+    base: Option<&FileMetadata>,
+    local: &FileMetadata,
+    remote: &FileMetadata,
+    policy: ConflictPolicy,
 ) -> ConflictDecision {
     if let Some(base) = base {
         let local_changed = !local.is_same_as(base);
@@ -43,22 +183,39 @@ pub fn resolve_conflict(
             (false, false) => ConflictDecision::NoOp,
             (true, false) => ConflictDecision::UploadLocal,
             (false, true) => ConflictDecision::DownloadRemote,
-            (true, true) => ConflictDecision::KeepBoth {
-                renamed_local: conflict_path(path, local.modified),
-            },
+            (true, true) => both_changed(path, local, remote, policy),
         };
     }
 
     if local.is_same_as(remote) {
         ConflictDecision::NoOp
     } else {
-        ConflictDecision::KeepBoth {
+        both_changed(path, local, remote, policy)
+    }
+}
+
+fn both_changed(
+    path: &str,
+    local: &FileMetadata,
+    remote: &FileMetadata,
+    policy: ConflictPolicy,
+) -> ConflictDecision {
+    match policy {
+        ConflictPolicy::KeepBoth => ConflictDecision::KeepBoth {
             renamed_local: conflict_path(path, local.modified),
+        },
+        ConflictPolicy::PreferLocal => ConflictDecision::UploadLocal,
+        ConflictPolicy::PreferRemote => ConflictDecision::DownloadRemote,
+        // Ties go to the local copy: it is the one the user has in front of them.
+        ConflictPolicy::NewestWins if local.modified >= remote.modified => {
+            ConflictDecision::UploadLocal
         }
+        ConflictPolicy::NewestWins => ConflictDecision::DownloadRemote,
+        ConflictPolicy::Ask => ConflictDecision::AskUser,
     }
 }
 
-fn conflict_path(path: &str, stamp: i64) -> String {
+pub fn conflict_path(path: &str, stamp: i64) -> String {
     let suffix = next_conflict_suffix();
     let (dir, name) = match path.rsplit_once('/') {
         Some((dir, name)) => (format!("{dir}/"), name),
@@ -165,6 +322,75 @@ mod tests {
         }
     }
 
+    #[test]
+    fn policies_settle_both_changed_files() {
+        let base = meta("a", 1);
+        let local = meta("b", 5);
+        let remote = meta("c", 3);
+        let decide = |policy| {
+            resolve_conflict_with_policy("/Docs/A.txt", Some(&base), &local, &remote, policy)
+        };
+        assert_eq!(
+            decide(ConflictPolicy::PreferLocal),
+            ConflictDecision::UploadLocal
+        );
+        assert_eq!(
+            decide(ConflictPolicy::PreferRemote),
+            ConflictDecision::DownloadRemote
+        );
+        assert_eq!(
+            decide(ConflictPolicy::NewestWins),
+            ConflictDecision::UploadLocal
+        );
+        assert_eq!(decide(ConflictPolicy::Ask), ConflictDecision::AskUser);
+        assert_eq!(
+            resolve_conflict_with_policy(
+                "/Docs/A.txt",
+                Some(&base),
+                &meta("b", 2),
+                &remote,
+                ConflictPolicy::NewestWins
+            ),
+            ConflictDecision::DownloadRemote
+        );
+    }
+
+    #[test]
+    fn policy_does_not_override_one_sided_changes() {
+        let base = meta("a", 1);
+        assert_eq!(
+            resolve_conflict_with_policy(
+                "/Docs/A.txt",
+                Some(&base),
+                &meta("a", 1),
+                &meta("c", 3),
+                ConflictPolicy::PreferLocal
+            ),
+            ConflictDecision::DownloadRemote
+        );
+    }
+
+    #[test]
+    fn deepest_subtree_policy_wins() {
+        let policies = ConflictPolicies::new(ConflictPolicy::KeepBoth)
+            .parse_subtrees("/Work=prefer-local; disk:/Work/Shared/=ask")
+            .unwrap();
+        assert_eq!(
+            policies.policy_for("/Work/Report.odt"),
+            ConflictPolicy::PreferLocal
+        );
+        assert_eq!(
+            policies.policy_for("disk:/Work/Shared/Plan.txt"),
+            ConflictPolicy::Ask
+        );
+        assert_eq!(
+            policies.policy_for("/Workshop/Notes.txt"),
+            ConflictPolicy::KeepBoth
+        );
+        assert!(ConflictPolicies::default().parse_subtrees("/Work").is_err());
+        assert!("bogus".parse::<ConflictPolicy>().is_err());
+    }
+
     #[test]
     fn conflict_paths_are_unique_for_same_timestamp() {
         let mut seen = std::collections::HashSet::new();
@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::index::{DeleteOrigin, IndexError, IndexStore, ItemType};
+use super::queue::{OpFilter, OperationKind};
+
+/// Local deletions within this long of each other count as one change;
+/// removing a folder is reported file by file.
+pub const DELETE_BURST_WINDOW: Duration = Duration::from_secs(30);
+
+/// Deleting fewer files than this never trips the percentage limit, so a
+/// couple of files can still be removed from a small tree.
+const MIN_FILES_FOR_PERCENT: u64 = 10;
+
+/// How much a single change may delete before it is held back for the user
+/// to confirm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeleteLimits {
+    /// More files than this is a mass deletion; `0` turns the limit off.
+    pub max_files: u64,
+    /// More than this share of the tree, in percent; `0` turns the limit off.
+    pub max_percent: u8,
+}
+
+impl Default for DeleteLimits {
+    fn default() -> Self {
+        Self {
+            max_files: 500,
+            max_percent: 50,
+        }
+    }
+}
+
+impl DeleteLimits {
+    /// Whether deleting `files` out of `total` files is a mass deletion.
+    pub fn exceeded_by(&self, files: u64, total: u64) -> bool {
+        if self.max_files > 0 && files > self.max_files {
+            return true;
+        }
+        self.max_percent > 0
+            && files >= MIN_FILES_FOR_PERCENT
+            && files * 100 > total.max(files) * u64::from(self.max_percent)
+    }
+}
+
+/// Files deleted locally within the burst window, keyed by path so a folder
+/// and the files reported inside it are counted once.
+#[derive(Debug)]
+pub struct DeleteBurst {
+    window: Duration,
+    files: HashMap<String, Instant>,
+}
+
+impl DeleteBurst {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            files: HashMap::new(),
+        }
+    }
+
+    /// Adds `paths` and returns how many files were deleted within the window.
+    pub fn record(&mut self, paths: impl IntoIterator<Item = String>, now: Instant) -> u64 {
+        let window = self.window;
+        self.files
+            .retain(|_, at| now.saturating_duration_since(*at) < window);
+        for path in paths {
+            let key = path.strip_prefix("disk:").unwrap_or(&path).to_string();
+            self.files.insert(key, now);
+        }
+        self.files.len() as u64
+    }
+
+    pub fn clear(&mut self) {
+        self.files.clear();
+    }
+}
+
+/// Whether `path` is `root` or lies inside it; `disk:/` and `/` paths
+/// compare equal.
+pub fn is_within(path: &str, root: &str) -> bool {
+    let path = path
+        .strip_prefix("disk:")
+        .unwrap_or(path)
+        .trim_end_matches('/');
+    let root = root
+        .strip_prefix("disk:")
+        .unwrap_or(root)
+        .trim_end_matches('/');
+    path.strip_prefix(root)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Holds a local delete of `path` in `pending_deletes` instead of letting it
+/// be queued when it is part of a mass deletion, or while an earlier one
+/// waits for the user. Deletes of the same burst still in the queue are held
+/// with it. Every writer of local deletes goes through this, so the FUSE
+/// mount is guarded like the sync folder.
+pub async fn hold_local_delete(
+    index: &IndexStore,
+    limits: DeleteLimits,
+    burst: &Mutex<DeleteBurst>,
+    path: &str,
+    now: i64,
+) -> Result<bool, IndexError> {
+    let pending = index.list_pending_deletes().await?;
+    let held_locally = pending
+        .iter()
+        .filter(|held| held.origin == DeleteOrigin::Local)
+        .collect::<Vec<_>>();
+    if held_locally.iter().any(|held| is_within(path, &held.path)) {
+        return Ok(true);
+    }
+    let files = files_at_or_below(index, path).await?;
+    let deleted = burst
+        .lock()
+        .expect("delete burst mutex poisoned")
+        .record(files.iter().cloned(), Instant::now());
+    if held_locally.is_empty() {
+        let total = index.count_files().await?;
+        if !limits.exceeded_by(deleted, total) {
+            return Ok(false);
+        }
+        eprintln!(
+            "[yadiskd] mass deletion: {deleted} of {total} files removed locally, \
+             waiting for confirmation"
+        );
+        let queued = index
+            .list_ops(&OpFilter {
+                kind: Some(OperationKind::Delete),
+                path_prefix: None,
+            })
+            .await?;
+        for op in queued {
+            if index.cancel_op(op.id).await?.is_some() {
+                let files = files_at_or_below(index, &op.path).await?;
+                index
+                    .hold_delete(&op.path, DeleteOrigin::Local, files.len() as i64, now)
+                    .await?;
+            }
+        }
+    }
+    index
+        .hold_delete(path, DeleteOrigin::Local, files.len() as i64, now)
+        .await?;
+    Ok(true)
+}
+
+/// Indexed files at or below `path`; a path the index no longer knows
+/// counts as one file.
+pub async fn files_at_or_below(index: &IndexStore, path: &str) -> Result<Vec<String>, IndexError> {
+    let items = index.list_items_by_prefix(path).await?;
+    if items.is_empty() {
+        return Ok(vec![path.to_string()]);
+    }
+    Ok(items
+        .into_iter()
+        .filter(|item| item.item_type == ItemType::File)
+        .map(|item| item.path)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_limit_trips_above_max_files() {
+        let limits = DeleteLimits {
+            max_files: 100,
+            max_percent: 0,
+        };
+        assert!(!limits.exceeded_by(100, 100_000));
+        assert!(limits.exceeded_by(101, 100_000));
+    }
+
+    #[test]
+    fn percent_limit_ignores_small_deletions() {
+        let limits = DeleteLimits {
+            max_files: 0,
+            max_percent: 50,
+        };
+        assert!(!limits.exceeded_by(3, 4));
+        assert!(!limits.exceeded_by(10, 20));
+        assert!(limits.exceeded_by(11, 20));
+        assert!(limits.exceeded_by(12, 0));
+    }
+
+    #[test]
+    fn zero_limits_never_trip() {
+        let limits = DeleteLimits {
+            max_files: 0,
+            max_percent: 0,
+        };
+        assert!(!limits.exceeded_by(1_000_000, 1_000_000));
+    }
+
+    #[test]
+    fn burst_counts_each_file_once_within_window() {
+        let mut burst = DeleteBurst::new(Duration::from_secs(30));
+        let start = Instant::now();
+        assert_eq!(
+            burst.record(["/Docs/A.txt".into(), "/Docs/B.txt".into()], start),
+            2
+        );
+        assert_eq!(burst.record(["disk:/Docs/A.txt".into()], start), 2);
+        let later = start + Duration::from_secs(31);
+        assert_eq!(burst.record(["/Docs/C.txt".into()], later), 1);
+    }
+
+    #[test]
+    fn within_matches_root_and_descendants_in_both_forms() {
+        assert!(is_within("disk:/Docs/A.txt", "/Docs"));
+        assert!(is_within("/Docs", "disk:/Docs/"));
+        assert!(!is_within("/Docsets/A.txt", "/Docs"));
+        assert!(!is_within("/Docs", "/Docs/A.txt"));
+    }
+}
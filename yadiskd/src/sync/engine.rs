@@ -3,6 +3,7 @@
 use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
@@ -19,13 +20,21 @@ use yadisk_core::{ApiErrorClass, DiskInfo, OperationStatus, ResourceType, Yadisk
 use crate::token_provider::{TokenProvider, TokenProviderError};
 
 use super::backoff::Backoff;
-use super::conflict::{self, ConflictDecision, FileMetadata};
+use super::conflict::{self, ConflictChoice, ConflictDecision, ConflictPolicies, FileMetadata};
+use super::delete_guard::{self, DELETE_BURST_WINDOW, DeleteBurst, DeleteLimits, is_within};
 use super::index::{
-    ConflictRecord, FileState, IndexError, IndexStore, ItemInput, ItemRecord, ItemType, StateMeta,
+    ConflictRecord, DeleteOrigin, FailedOpRecord, FileState, IndexError, IndexStore, ItemInput,
+    ItemRecord, ItemType, LocalBaseline, LocalFileIdentity, PendingDeleteRecord, PinRuleRecord,
+    QueueSummary, QueuedOpRecord, StateMeta, TrashedItemRecord,
 };
 use super::local_watcher::LocalEvent;
-use super::paths::{PathError, cache_path_for};
-use super::queue::{Operation, OperationKind};
+use super::merge::{self, TextMerge};
+use super::paths::{LocalNames, PathError, cache_path_for, local_names_for};
+use super::pin_rules::PinRule;
+use super::prefetch::{PREFETCH_PRIORITY, PrefetchPolicy};
+use super::queue::{ON_DEMAND_PRIORITY, OpFilter, Operation, OperationKind, paths_overlap};
+use super::settle::{UploadSettler, WriteSettle};
+use super::symlinks::{SYMLINK_PROPERTY, SymlinkPolicy};
 use super::transfer::{TransferClient, TransferError};
 
 #[derive(Debug, Error)]
@@ -44,8 +53,22 @@ pub enum EngineError {
     Time(#[from] time::error::Parse),
     #[error("item not found for path: {0}")]
     MissingItem(String),
+    #[error("no open conflict with id {0}")]
+    MissingConflict(i64),
+    #[error("no failed operation with id {0}")]
+    MissingFailedOp(i64),
+    #[error("no queued operation with id {0}")]
+    MissingOperation(i64),
+    #[error("operation {0} is already running and cannot be stopped")]
+    OperationRunning(i64),
+    #[error("no pin rule with id {0}")]
+    MissingPinRule(i64),
+    #[error("not found in trash: {0}")]
+    NotInTrash(String),
     #[error("operation failed")]
     OperationFailed,
+    #[error("operation failed on the server")]
+    RemoteOperationFailed,
     #[error("upload size {size} exceeds server limit {max_size}")]
     UploadTooLarge { size: u64, max_size: u64 },
     #[error("upload size {size} exceeds available cloud space {available}")]
@@ -65,6 +88,25 @@ pub struct SyncEngine {
     backoff: Backoff,
     active_transfers: Arc<Mutex<HashMap<String, CancellationToken>>>,
     disk_info_cache: Arc<Mutex<DiskInfoCache>>,
+    upload_settler: Option<Arc<UploadSettler>>,
+    conflict_policies: ConflictPolicies,
+    text_merge: Option<TextMerge>,
+    /// Where the daemon mirrors the cloud; merged text is written back there.
+    sync_root: Option<PathBuf>,
+    queue_paused: AtomicBool,
+    /// Delete for good instead of moving to the Yandex trash.
+    delete_permanently: bool,
+    /// Mass deletions above these limits wait for the user; `None` lets
+    /// every deletion through.
+    delete_limits: Option<DeleteLimits>,
+    delete_burst: Mutex<DeleteBurst>,
+    symlinks: SymlinkPolicy,
+    /// Read-ahead after a file is opened; `None` turns it off.
+    prefetch: Option<PrefetchPolicy>,
+    /// Paths touched by operations currently being run by a worker.
+    in_flight: Arc<Mutex<Vec<String>>>,
+    /// Serializes claiming so two workers never start overlapping paths.
+    claim_lock: AsyncMutex<()>,
 }
 
 const MAX_RETRY_ATTEMPTS: u32 = 5;
@@ -77,6 +119,20 @@ pub struct CloudSpaceStatus {
     pub low: bool,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionInfo {
+    pub size: u64,
+    pub modified: i64,
+}
+
+/// An open conflict with what is known about both sides of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictDetails {
+    pub conflict: ConflictRecord,
+    pub local: Option<VersionInfo>,
+    pub remote: Option<VersionInfo>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PathDisplayState {
     CloudOnly,
@@ -106,6 +162,15 @@ pub struct SyncDelta {
     pub enqueued_downloads: usize,
 }
 
+/// What `recover_interrupted_ops` found after a restart.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RecoveryReport {
+    /// Interrupted operations put back on the queue.
+    pub requeued: usize,
+    /// Items reset from a stale `syncing` state.
+    pub reset: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct MovePayload {
     from: String,
@@ -114,6 +179,64 @@ struct MovePayload {
     overwrite: bool,
     #[serde(default = "default_move_action")]
     action: String,
+    /// Status URL of the async move/copy once the API accepted it, so an
+    /// interrupted operation waits for it instead of issuing it again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    operation_url: Option<String>,
+}
+
+/// Server-side copy of `from` to `path`; nothing is uploaded again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CopyPayload {
+    from: String,
+    path: String,
+    #[serde(default)]
+    overwrite: bool,
+    /// Status URL of the async copy once the API accepted it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    operation_url: Option<String>,
+}
+
+/// Upload payload for an overwrite the user asked for; the conflict check
+/// against the remote version is skipped.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct UploadPayload {
+    #[serde(default)]
+    overwrite: bool,
+    /// Name chosen for the local copy of a keep-both conflict; set once the
+    /// copy is under way so a resumed upload finishes the same copy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    keep_both: Option<String>,
+}
+
+/// Paths claimed by a running operation, released when it is dropped.
+struct InFlightPaths {
+    in_flight: Arc<Mutex<Vec<String>>>,
+    paths: Vec<String>,
+}
+
+impl InFlightPaths {
+    fn claim(in_flight: &Arc<Mutex<Vec<String>>>, paths: Vec<String>) -> Self {
+        in_flight
+            .lock()
+            .expect("in-flight mutex poisoned")
+            .extend(paths.iter().cloned());
+        Self {
+            in_flight: Arc::clone(in_flight),
+            paths,
+        }
+    }
+}
+
+impl Drop for InFlightPaths {
+    fn drop(&mut self) {
+        let mut in_flight = self.in_flight.lock().expect("in-flight mutex poisoned");
+        for path in &self.paths {
+            if let Some(index) = in_flight.iter().position(|claimed| claimed == path) {
+                in_flight.swap_remove(index);
+            }
+        }
+    }
 }
 
 struct LocalFileVersion {
@@ -142,6 +265,18 @@ impl SyncEngine {
             ),
             active_transfers: Arc::new(Mutex::new(HashMap::new())),
             disk_info_cache: Arc::new(Mutex::new(None)),
+            upload_settler: None,
+            conflict_policies: ConflictPolicies::default(),
+            text_merge: None,
+            sync_root: None,
+            queue_paused: AtomicBool::new(false),
+            delete_permanently: false,
+            delete_limits: None,
+            delete_burst: Mutex::new(DeleteBurst::new(DELETE_BURST_WINDOW)),
+            symlinks: SymlinkPolicy::default(),
+            prefetch: None,
+            in_flight: Arc::new(Mutex::new(Vec::new())),
+            claim_lock: AsyncMutex::new(()),
         }
     }
 
@@ -155,6 +290,50 @@ impl SyncEngine {
         self
     }
 
+    pub fn with_write_settle(mut self, settle: WriteSettle) -> Self {
+        self.upload_settler = Some(Arc::new(UploadSettler::new(settle)));
+        self
+    }
+
+    pub fn with_conflict_policies(mut self, policies: ConflictPolicies) -> Self {
+        self.conflict_policies = policies;
+        self
+    }
+
+    pub fn with_text_merge(mut self, text_merge: TextMerge) -> Self {
+        self.text_merge = Some(text_merge);
+        self
+    }
+
+    pub fn with_sync_root(mut self, sync_root: PathBuf) -> Self {
+        self.sync_root = Some(sync_root);
+        self
+    }
+
+    pub fn with_permanent_delete(mut self, permanently: bool) -> Self {
+        self.delete_permanently = permanently;
+        self
+    }
+
+    pub fn with_delete_guard(mut self, limits: DeleteLimits) -> Self {
+        self.delete_limits = Some(limits);
+        self
+    }
+
+    pub fn with_symlink_policy(mut self, policy: SymlinkPolicy) -> Self {
+        self.symlinks = policy;
+        self
+    }
+
+    pub fn symlink_policy(&self) -> SymlinkPolicy {
+        self.symlinks
+    }
+
+    pub fn with_prefetch(mut self, policy: PrefetchPolicy) -> Self {
+        self.prefetch = Some(policy);
+        self
+    }
+
     pub fn cancel_transfer(&self, path: &str) {
         let mut map = self
             .active_transfers
@@ -183,12 +362,111 @@ impl SyncEngine {
             .lock()
             .expect("transfer mutex poisoned")
             .is_empty()
+            || !self
+                .in_flight
+                .lock()
+                .expect("in-flight mutex poisoned")
+                .is_empty()
         {
             return Ok(true);
         }
+        if self.is_queue_paused() {
+            return Ok(false);
+        }
         Ok(self.index.has_ready_op().await?)
     }
 
+    /// Remembers which local file backs an indexed file; a no-op for paths
+    /// that are not indexed yet.
+    pub async fn record_local_identity(
+        &self,
+        path: &str,
+        identity: &LocalFileIdentity,
+    ) -> Result<(), EngineError> {
+        if let Some(item) = self.index.get_item_by_path(path).await?
+            && item.item_type == ItemType::File
+        {
+            self.index.set_local_identity(item.id, identity).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn local_identity_for_path(
+        &self,
+        path: &str,
+    ) -> Result<Option<LocalFileIdentity>, EngineError> {
+        Ok(self.index.get_local_identity_by_path(path).await?)
+    }
+
+    pub async fn path_for_local_identity(
+        &self,
+        identity: &LocalFileIdentity,
+    ) -> Result<Option<String>, EngineError> {
+        Ok(self.index.find_path_by_local_identity(identity).await?)
+    }
+
+    pub async fn list_local_identities_by_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<(String, LocalFileIdentity)>, EngineError> {
+        Ok(self.index.list_local_identities_by_prefix(prefix).await?)
+    }
+
+    /// Remembers the sync-folder copy of `path` as the daemon wrote or saw
+    /// it; a no-op for paths that are not indexed files.
+    pub async fn record_local_baseline(
+        &self,
+        path: &str,
+        baseline: LocalBaseline,
+    ) -> Result<(), EngineError> {
+        if let Some(item) = self.index.get_item_by_path(path).await?
+            && item.item_type == ItemType::File
+        {
+            self.index.set_local_baseline(item.id, baseline).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn list_local_baselines_by_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<(String, LocalBaseline)>, EngineError> {
+        Ok(self.index.list_local_baselines_by_prefix(prefix).await?)
+    }
+
+    /// How remote paths map to paths under the sync root.
+    pub async fn local_names(&self) -> Result<LocalNames, EngineError> {
+        Ok(LocalNames::new(self.index.list_local_names().await?))
+    }
+
+    /// Works out the names the items below `prefix` get on disk and records
+    /// the ones that differ from their remote names.
+    async fn assign_local_names(&self, prefix: &str) -> Result<(), EngineError> {
+        let items = self.index.list_items_by_prefix(prefix).await?;
+        let recorded: HashMap<String, String> =
+            self.index.list_local_names().await?.into_iter().collect();
+        let mut by_parent: HashMap<&str, Vec<&ItemRecord>> = HashMap::new();
+        for item in &items {
+            if let Some(parent) = item.parent_path.as_deref()
+                && is_within(parent, prefix)
+            {
+                by_parent.entry(parent).or_default().push(item);
+            }
+        }
+        for siblings in by_parent.values() {
+            let names: Vec<&str> = siblings.iter().map(|item| item.name.as_str()).collect();
+            for (item, local_name) in siblings.iter().zip(local_names_for(&names)) {
+                let wanted = (local_name != item.name).then_some(local_name);
+                if recorded.get(&item.path) != wanted.as_ref() {
+                    self.index
+                        .set_local_name(item.id, wanted.as_deref())
+                        .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn register_transfer_token(&self, path: &str) -> CancellationToken {
         let token = CancellationToken::new();
         let mut map = self
@@ -313,6 +591,13 @@ fn is_cloud_space_low(total_space: u64, available_space: u64) -> bool {
 
 include!("engine_impl_core.rs");
 include!("engine_impl_ops.rs");
+include!("engine_impl_conflicts.rs");
+include!("engine_impl_failed_ops.rs");
+include!("engine_impl_queue.rs");
+include!("engine_impl_trash.rs");
+include!("engine_impl_delete_guard.rs");
+include!("engine_impl_symlinks.rs");
+include!("engine_impl_pin_rules.rs");
 
 fn parse_modified(value: Option<&str>) -> Result<Option<i64>, time::error::Parse> {
     let Some(value) = value else {
@@ -333,6 +618,20 @@ fn parent_path(path: &str) -> Option<String> {
     })
 }
 
+/// Pin setting of the nearest folder at or above `path` that has one.
+fn pinned_by_folder(folder_pins: &[(String, bool)], path: &str) -> bool {
+    folder_pins
+        .iter()
+        .filter(|(folder, _)| is_within(path, folder))
+        .max_by_key(|(folder, _)| {
+            folder
+                .trim_start_matches("disk:")
+                .trim_end_matches('/')
+                .len()
+        })
+        .is_some_and(|(_, pinned)| *pinned)
+}
+
 fn state_for_path_variant(states: &HashMap<String, FileState>, path: &str) -> Option<FileState> {
     if let Some(state) = states.get(path) {
         return Some(state.clone());
@@ -391,7 +690,8 @@ fn is_transient_error(err: &EngineError) -> bool {
         | EngineError::Transfer(TransferError::Request(_))
         | EngineError::Transfer(TransferError::Io(_))
         | EngineError::Transfer(TransferError::ConcurrencyClosed)
-        | EngineError::OperationFailed => true,
+        | EngineError::OperationFailed
+        | EngineError::RemoteOperationFailed => true,
         _ => false,
     }
 }
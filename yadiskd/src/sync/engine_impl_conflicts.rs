@@ -0,0 +1,143 @@
+impl SyncEngine {
+    /// Applies the user's choice to an open conflict and closes it.
+    ///
+    /// Conflicts settled as keep-both already have the local version under
+    /// `renamed_local`; conflicts left to the user (`renamed_local == path`)
+    /// still hold the unsent local version at `path`.
+    pub async fn resolve_conflict(
+        &self,
+        id: i64,
+        choice: ConflictChoice,
+    ) -> Result<(), EngineError> {
+        let conflict = self.open_conflict(id).await?;
+        let path = conflict.path.as_str();
+        let renamed = conflict.renamed_local.as_str();
+        if renamed == path {
+            match choice {
+                ConflictChoice::KeepLocal => {
+                    self.enqueue_overwriting_upload(path).await?;
+                }
+                ConflictChoice::KeepRemote => {
+                    self.enqueue_download(path).await?;
+                }
+                ConflictChoice::KeepBoth => {
+                    let copy = conflict::conflict_path(path, now_unix());
+                    let source = cache_path_for(&self.cache_root, path)?;
+                    let target = cache_path_for(&self.cache_root, &copy)?;
+                    if let Some(parent) = target.parent() {
+                        tokio::fs::create_dir_all(parent).await?;
+                    }
+                    tokio::fs::copy(&source, &target).await?;
+                    self.enqueue_upload(&copy).await?;
+                    self.enqueue_download(path).await?;
+                }
+            }
+        } else {
+            match choice {
+                ConflictChoice::KeepLocal => {
+                    // Put the renamed local copy back in place of the remote
+                    // version with a server-side move instead of re-uploading.
+                    self.index.delete_ops_for_path(path).await?;
+                    self.index.delete_ops_for_path(renamed).await?;
+                    self.cancel_transfer(path);
+                    self.cancel_transfer(renamed);
+                    let source = cache_path_for(&self.cache_root, renamed)?;
+                    let target = cache_path_for(&self.cache_root, path)?;
+                    if tokio::fs::symlink_metadata(&source).await.is_ok() {
+                        tokio::fs::rename(&source, &target).await?;
+                    } else if let Err(err) = tokio::fs::remove_file(&target).await
+                        && err.kind() != std::io::ErrorKind::NotFound
+                    {
+                        return Err(err.into());
+                    }
+                    self.enqueue_move(renamed, path, "move").await?;
+                }
+                ConflictChoice::KeepRemote => {
+                    self.enqueue_delete(renamed).await?;
+                }
+                ConflictChoice::KeepBoth => {}
+            }
+        }
+        self.index
+            .close_conflict(id, "resolved", now_unix())
+            .await?;
+        Ok(())
+    }
+
+    /// Lists open conflicts with size and mtime of both versions: the local
+    /// one from the cached copy, the remote one from the index.
+    pub async fn list_conflict_details(&self) -> Result<Vec<ConflictDetails>, EngineError> {
+        let conflicts = self.index.list_conflicts().await?;
+        let mut out = Vec::with_capacity(conflicts.len());
+        for conflict in conflicts {
+            let mut local = None;
+            if let Ok(source) = cache_path_for(&self.cache_root, &conflict.renamed_local)
+                && let Ok(meta) = tokio::fs::metadata(&source).await
+                && meta.is_file()
+            {
+                local = Some(VersionInfo {
+                    size: meta.len(),
+                    modified: meta
+                        .modified()
+                        .ok()
+                        .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+                        .map(|since| since.as_secs() as i64)
+                        .unwrap_or(0),
+                });
+            } else if conflict.renamed_local != conflict.path {
+                local = self
+                    .index
+                    .get_item_by_path(&conflict.renamed_local)
+                    .await?
+                    .and_then(|item| version_from_item(&item));
+            }
+            let remote = self
+                .index
+                .get_item_by_path(&conflict.path)
+                .await?
+                .and_then(|item| version_from_item(&item));
+            out.push(ConflictDetails {
+                conflict,
+                local,
+                remote,
+            });
+        }
+        Ok(out)
+    }
+
+    /// Closes a conflict without touching either copy.
+    pub async fn dismiss_conflict(&self, id: i64) -> Result<(), EngineError> {
+        if !self
+            .index
+            .close_conflict(id, "dismissed", now_unix())
+            .await?
+        {
+            return Err(EngineError::MissingConflict(id));
+        }
+        Ok(())
+    }
+
+    async fn open_conflict(&self, id: i64) -> Result<ConflictRecord, EngineError> {
+        self.index
+            .get_conflict(id)
+            .await?
+            .filter(|conflict| conflict.status == "open")
+            .ok_or(EngineError::MissingConflict(id))
+    }
+
+    async fn enqueue_overwriting_upload(&self, path: &str) -> Result<i64, EngineError> {
+        let payload = serde_json::to_string(&UploadPayload {
+            overwrite: true,
+            keep_both: None,
+        })
+        .map_err(|_| EngineError::OperationFailed)?;
+        self.enqueue_upload_with_payload(path, Some(payload)).await
+    }
+}
+
+fn version_from_item(item: &ItemRecord) -> Option<VersionInfo> {
+    Some(VersionInfo {
+        size: u64::try_from(item.size?).ok()?,
+        modified: item.modified.unwrap_or(0),
+    })
+}
@@ -3,6 +3,7 @@ impl SyncEngine {
         let list = self
             .call_with_fresh_client(|client| async move { client.list_directory_all(path, 100, None).await })
             .await?;
+        let folder_pins = self.index.list_folder_pins().await?;
         for item in &list {
             let input = ItemInput {
                 path: item.path.clone(),
@@ -20,15 +21,18 @@ impl SyncEngine {
                 last_synced_modified: None,
             };
             let record = self.index.upsert_item(&input).await?;
+            self.record_link_target(record.id, item).await?;
 
             // For files, default to cloud-only unless already cached/pinned.
             if input.item_type == ItemType::File && self.index.get_state(record.id).await?.is_none()
             {
+                let pinned = pinned_by_folder(&folder_pins, &item.path);
                 self.index
-                    .set_state(record.id, FileState::CloudOnly, false, None)
+                    .set_state(record.id, FileState::CloudOnly, pinned, None)
                     .await?;
             }
         }
+        self.assign_local_names(path).await?;
 
         Ok(list.len())
     }
@@ -51,6 +55,7 @@ impl SyncEngine {
             .collect();
 
         let mut delta = SyncDelta::default();
+        let folder_pins = self.index.list_folder_pins().await?;
 
         for item in &remote_items {
             let previous_by_path = path_variants(&item.path)
@@ -72,6 +77,7 @@ impl SyncEngine {
                 last_synced_modified: parse_modified(item.modified.as_deref())?,
             };
             let record = self.index.upsert_item(&input).await?;
+            self.record_link_target(record.id, item).await?;
             delta.indexed += 1;
 
             if let Some(resource_id) = &item.resource_id
@@ -95,17 +101,23 @@ impl SyncEngine {
                         )
                         .await?;
                 } else if input.item_type == ItemType::File {
+                    let pinned = pinned_by_folder(&folder_pins, &item.path);
                     self.index
-                        .set_state(record.id, FileState::CloudOnly, false, None)
+                        .set_state(record.id, FileState::CloudOnly, pinned, None)
                         .await?;
                 }
+                if let Some(pinned) = self.index.get_folder_pin(previous.id).await? {
+                    self.index.set_folder_pin(record.id, Some(pinned)).await?;
+                }
+                self.move_text_bases(&previous.path, &item.path).await;
                 self.index.delete_item_by_path(&previous.path).await?;
                 delta.deleted += 1;
             } else if input.item_type == ItemType::File
                 && self.index.get_state(record.id).await?.is_none()
             {
+                let pinned = pinned_by_folder(&folder_pins, &item.path);
                 self.index
-                    .set_state(record.id, FileState::CloudOnly, false, None)
+                    .set_state(record.id, FileState::CloudOnly, pinned, None)
                     .await?;
             }
 
@@ -125,6 +137,7 @@ impl SyncEngine {
             }
         }
 
+        let mut vanished = Vec::new();
         for old in &local_items {
             if path_variants(&old.path)
                 .iter()
@@ -143,9 +156,15 @@ impl SyncEngine {
             {
                 continue;
             }
+            vanished.push(old);
+        }
+        for old in self.hold_cloud_deletes(&local_items, vanished).await? {
+            self.drop_text_bases(&old.path).await;
             self.index.delete_item_by_path(&old.path).await?;
             delta.deleted += 1;
         }
+        self.assign_local_names(path).await?;
+        self.apply_pin_rules(path).await?;
 
         let pinned_cloud = self
             .index
@@ -159,7 +178,72 @@ impl SyncEngine {
         Ok(delta)
     }
 
+    /// Queues low-priority downloads of the cloud-only files that follow
+    /// `path` in its folder, so a viewer stepping through the folder finds
+    /// them cached. They stay unpinned and run as ordinary downloads, which
+    /// `cancel_transfer` stops. Returns how many were queued.
+    pub async fn prefetch_siblings(&self, path: &str) -> Result<usize, EngineError> {
+        let item = self
+            .index
+            .get_item_by_path(path)
+            .await?
+            .ok_or_else(|| EngineError::MissingItem(path.to_string()))?;
+        let Some(policy) = self.prefetch else {
+            return Ok(0);
+        };
+        let Some(parent) = item.parent_path.as_deref() else {
+            return Ok(0);
+        };
+        if item.item_type != ItemType::File {
+            return Ok(0);
+        }
+        let children = self.index.list_children(parent).await?;
+        let mut queued = 0;
+        for sibling in policy.select(&item, &children) {
+            let state = self.index.get_state(sibling.id).await?;
+            if state
+                .as_ref()
+                .is_some_and(|state| state.state != FileState::CloudOnly)
+            {
+                continue;
+            }
+            let pinned = state.is_some_and(|state| state.pinned);
+            self.index
+                .set_state(sibling.id, FileState::Syncing, pinned, None)
+                .await?;
+            self.index
+                .enqueue_op(&Operation {
+                    kind: OperationKind::Download,
+                    path: sibling.path.clone(),
+                    payload: None,
+                    attempt: 0,
+                    retry_at: None,
+                    priority: PREFETCH_PRIORITY,
+                })
+                .await?;
+            queued += 1;
+        }
+        if queued > 0 {
+            eprintln!("[yadiskd] prefetch queued {queued} files after {path}");
+        }
+        Ok(queued)
+    }
+
     pub async fn enqueue_download(&self, path: &str) -> Result<i64, EngineError> {
+        self.enqueue_download_with_priority(path, 50).await
+    }
+
+    /// Queues a download someone is waiting for, such as a file opened in a
+    /// file manager; it runs ahead of everything else, even while paused.
+    pub async fn enqueue_download_on_demand(&self, path: &str) -> Result<i64, EngineError> {
+        self.enqueue_download_with_priority(path, ON_DEMAND_PRIORITY).await
+    }
+
+    async fn enqueue_download_with_priority(
+        &self,
+        path: &str,
+        priority: i32,
+    ) -> Result<i64, EngineError> {
         let item = self
             .index
             .get_item_by_path(path)
@@ -176,12 +260,20 @@ impl SyncEngine {
                 payload: None,
                 attempt: 0,
                 retry_at: None,
-                priority: 50,
+                priority,
             })
             .await?)
     }
 
     pub async fn enqueue_upload(&self, path: &str) -> Result<i64, EngineError> {
+        self.enqueue_upload_with_payload(path, None).await
+    }
+
+    async fn enqueue_upload_with_payload(
+        &self,
+        path: &str,
+        payload: Option<String>,
+    ) -> Result<i64, EngineError> {
         let item = if let Some(item) = self.index.get_item_by_path(path).await? {
             item
         } else {
@@ -215,7 +307,7 @@ impl SyncEngine {
             .enqueue_op(&Operation {
                 kind: OperationKind::Upload,
                 path: path.to_string(),
-                payload: None,
+                payload,
                 attempt: 0,
                 retry_at: None,
                 priority: 50,
@@ -290,6 +382,7 @@ impl SyncEngine {
             path: to.to_string(),
             overwrite: true,
             action: action.to_string(),
+            operation_url: None,
         })
         .map_err(|_| EngineError::OperationFailed)?;
         Ok(self
@@ -305,15 +398,51 @@ impl SyncEngine {
             .await?)
     }
 
+    /// Queues a server-side copy of `from` (a file or a whole folder) to
+    /// `to`. The copied items are added to the index once the copy is done.
+    pub async fn enqueue_copy(&self, from: &str, to: &str) -> Result<i64, EngineError> {
+        if self.index.get_item_by_path(from).await?.is_none() {
+            return Err(EngineError::MissingItem(from.to_string()));
+        }
+        let payload = serde_json::to_string(&CopyPayload {
+            from: from.to_string(),
+            path: to.to_string(),
+            overwrite: false,
+            operation_url: None,
+        })
+        .map_err(|_| EngineError::OperationFailed)?;
+        Ok(self
+            .index
+            .enqueue_op(&Operation {
+                kind: OperationKind::Copy,
+                path: to.to_string(),
+                payload: Some(payload),
+                attempt: 0,
+                retry_at: None,
+                priority: 60,
+            })
+            .await?)
+    }
+
+    /// Queues the operation for a local change and returns its id. A delete
+    /// held back as part of a mass deletion queues nothing and returns `0`.
     pub async fn ingest_local_event(&self, event: LocalEvent) -> Result<i64, EngineError> {
         match event {
             LocalEvent::Upload { path } => self.enqueue_upload(&path).await,
             LocalEvent::Mkdir { path } => self.enqueue_mkdir(&path).await,
-            LocalEvent::Delete { path } => self.enqueue_delete(&path).await,
+            LocalEvent::Delete { path } => {
+                if self.hold_local_delete(&path).await? {
+                    return Ok(0);
+                }
+                self.enqueue_delete(&path).await
+            }
             LocalEvent::Move { from, to } => self.enqueue_move(&from, &to, "move").await,
         }
     }
 
+    /// Pins or unpins the item and everything below it. A folder keeps the
+    /// setting for files added to it later, replacing what its subfolders
+    /// had.
     pub async fn pin_path(&self, path: &str, pinned: bool) -> Result<(), EngineError> {
         let item = self
             .index
@@ -334,12 +463,19 @@ impl SyncEngine {
             .await?
             .into_iter()
             .collect();
+        self.drop_text_bases(path).await;
+        let root_id = targets[0].id;
         for target in targets {
             let current_state =
                 state_for_path_variant(&states, &target.path).unwrap_or(FileState::CloudOnly);
             self.index
                 .set_state(target.id, current_state, pinned, None)
                 .await?;
+            self.index.set_auto_pinned(target.id, false).await?;
+            if target.item_type == ItemType::Dir {
+                let folder_pin = (target.id == root_id).then_some(pinned);
+                self.index.set_folder_pin(target.id, folder_pin).await?;
+            }
         }
         Ok(())
     }
@@ -360,10 +496,16 @@ impl SyncEngine {
                 }
             }
         }
+        self.drop_text_bases(path).await;
+        let root_id = targets[0].id;
         for target in targets {
             self.index
                 .set_state(target.id, FileState::CloudOnly, false, None)
                 .await?;
+            if target.item_type == ItemType::Dir {
+                let folder_pin = (target.id == root_id).then_some(false);
+                self.index.set_folder_pin(target.id, folder_pin).await?;
+            }
         }
         let cache_path = cache_path_for(&self.cache_root, path)?;
         if let Ok(meta) = tokio::fs::metadata(&cache_path).await {
@@ -483,50 +625,122 @@ impl SyncEngine {
         local: &FileMetadata,
         remote: &FileMetadata,
     ) -> Result<ConflictDecision, EngineError> {
-        let decision = conflict::resolve_conflict(path, base, local, remote);
-        if let ConflictDecision::KeepBoth { renamed_local } = &decision {
-            self.index
-                .record_conflict(path, renamed_local, now_unix(), "both-changed")
-                .await?;
-        }
+        let decision = self.decide_conflict(path, base, local, remote);
+        self.record_conflict_decision(path, &decision).await?;
         Ok(decision)
     }
 
+    fn decide_conflict(
+        &self,
+        path: &str,
+        base: Option<&FileMetadata>,
+        local: &FileMetadata,
+        remote: &FileMetadata,
+    ) -> ConflictDecision {
+        let policy = self.conflict_policies.policy_for(path);
+        conflict::resolve_conflict_with_policy(path, base, local, remote, policy)
+    }
+
+    async fn record_conflict_decision(
+        &self,
+        path: &str,
+        decision: &ConflictDecision,
+    ) -> Result<(), EngineError> {
+        match decision {
+            ConflictDecision::KeepBoth { renamed_local } => {
+                self.index
+                    .record_conflict(path, renamed_local, now_unix(), "both-changed")
+                    .await?;
+            }
+            ConflictDecision::AskUser
+                if self.index.open_conflict_for_path(path).await?.is_none() =>
+            {
+                self.index
+                    .record_conflict(path, path, now_unix(), "needs-decision")
+                    .await?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
     pub async fn run_once(&self) -> Result<bool, EngineError> {
-        let Some(op) = self.index.dequeue_op().await? else {
+        if self.is_queue_paused() {
+            return self.run_once_on_demand().await;
+        }
+        self.run_once_where(|_| true).await
+    }
+
+    /// Runs a queued download the user is waiting for, if there is one;
+    /// these go ahead while the queue or sync as a whole is paused.
+    pub async fn run_once_on_demand(&self) -> Result<bool, EngineError> {
+        self.run_once_where(|op| {
+            op.kind == OperationKind::Download && op.priority >= ON_DEMAND_PRIORITY
+        })
+        .await
+    }
+
+    async fn run_once_where(
+        &self,
+        accept: impl Fn(&Operation) -> bool,
+    ) -> Result<bool, EngineError> {
+        let claim = self.claim_lock.lock().await;
+        let Some(op) = self
+            .index
+            .dequeue_op_where(|op| accept(op) && !self.overlaps_in_flight(op))
+            .await?
+        else {
             return Ok(false);
         };
+        let _in_flight = InFlightPaths::claim(&self.in_flight, op.touched_paths());
+        drop(claim);
+        let (kind, path) = (op.kind.clone(), op.path.clone());
+        let result = self.run_claimed(op).await;
+        // An index failure may have lost the requeue; keep the journal entry
+        // so the operation is recovered on the next start.
+        if !matches!(result, Err(EngineError::Index(_)))
+            && let Err(err) = self.index.finish_journal(&kind, &path).await
+        {
+            eprintln!("[yadiskd] warning: failed to finish journal for {path}: {err}");
+        }
+        result
+    }
+
+    async fn run_claimed(&self, op: Operation) -> Result<bool, EngineError> {
+        if op.kind == OperationKind::Upload
+            && let Some(settler) = &self.upload_settler
+            && let Some(delay) = settler.pending_delay(&op.path).await
+        {
+            // Still being written: put the upload back without counting an
+            // attempt, so half-written files are not sent repeatedly.
+            let retry_at = now_unix().saturating_add(delay.as_secs().max(1) as i64);
+            self.index
+                .enqueue_op(&Operation {
+                    retry_at: Some(retry_at),
+                    ..op
+                })
+                .await?;
+            return Ok(false);
+        }
         eprintln!("[yadiskd] op start: kind={:?} path={}", op.kind, op.path);
 
         let result = match op.kind.clone() {
             OperationKind::Download => self.execute_download(&op.path).await,
-            OperationKind::Upload => self.execute_upload(&op.path).await,
-            OperationKind::Mkdir => self.execute_mkdir(&op.path).await,
-            OperationKind::Delete => {
-                let delete_path = op.path.clone();
-                match self
-                    .call_with_fresh_client(|client| {
-                        let delete_path = delete_path.clone();
-                        async move { client.delete_resource(&delete_path, true).await }
-                    })
-                    .await
-                {
-                    Ok(link) => {
-                        if let Some(link) = link {
-                            self.wait_for_operation(link.href.as_str()).await?;
-                        }
-                    }
-                    Err(EngineError::Api(yadisk_core::YadiskError::Api { status, .. }))
-                        if status == reqwest::StatusCode::NOT_FOUND =>
-                    {
-                        // Resource already deleted on remote — treat as success
-                    }
-                    Err(err) => return Err(err),
+            OperationKind::Upload => {
+                let payload = op
+                    .payload
+                    .as_deref()
+                    .and_then(|payload| serde_json::from_str::<UploadPayload>(payload).ok())
+                    .unwrap_or_default();
+                match payload.keep_both {
+                    Some(renamed_local) => self.resume_keep_both(&op.path, &renamed_local).await,
+                    None => self.execute_upload(&op.path, payload.overwrite).await,
                 }
-                self.index.delete_item_by_path(&op.path).await?;
-                Ok(())
             }
+            OperationKind::Mkdir => self.execute_mkdir(&op.path).await,
+            OperationKind::Delete => self.execute_delete(&op).await,
             OperationKind::Move => self.execute_move_like_op(&op).await,
+            OperationKind::Copy => self.execute_copy(&op).await,
         };
 
         if let Err(err) = result {
@@ -536,6 +750,7 @@ impl SyncEngine {
                         "[yadiskd] op failed permanently after retries: kind={:?} path={} err={}",
                         op.kind, op.path, err
                     );
+                    self.dead_letter(&op, &err).await;
                     return Err(err);
                 }
                 let retry_after = match &err {
@@ -547,8 +762,22 @@ impl SyncEngine {
                 .unwrap_or_else(|| {
                     now_unix().saturating_add(self.backoff.delay(op.attempt + 1).as_secs() as i64)
                 });
+                // Retry from the last recorded step rather than from scratch.
+                let progress = self
+                    .index
+                    .get_journaled_op(&op.kind, &op.path)
+                    .await?
+                    .map(|journaled| journaled.payload)
+                    .unwrap_or_else(|| op.payload.clone());
                 self.index
-                    .requeue_op(&op, retry_after, Some(&err.to_string()))
+                    .requeue_op(
+                        &Operation {
+                            payload: progress,
+                            ..op.clone()
+                        },
+                        retry_after,
+                        Some(&err.to_string()),
+                    )
                     .await?;
                 eprintln!(
                     "[yadiskd] op requeued: kind={:?} path={} attempt={} retry_at={}",
@@ -601,9 +830,11 @@ impl SyncEngine {
                 "[yadiskd] op failed: kind={:?} path={} err={}",
                 op.kind, op.path, err
             );
+            self.dead_letter(&op, &err).await;
             return Err(err);
         }
 
+        self.index.delete_failed_ops_for(&op.kind, &op.path).await?;
         eprintln!("[yadiskd] op done: kind={:?} path={}", op.kind, op.path);
         Ok(true)
     }
@@ -0,0 +1,197 @@
+impl SyncEngine {
+    pub async fn list_pending_deletes(&self) -> Result<Vec<PendingDeleteRecord>, EngineError> {
+        Ok(self.index.list_pending_deletes().await?)
+    }
+
+    /// Lets the held deletions through: local ones are deleted in the cloud,
+    /// cloud ones are dropped from the index and so from this computer.
+    pub async fn confirm_pending_deletes(&self) -> Result<usize, EngineError> {
+        let pending = self.index.list_pending_deletes().await?;
+        for held in &pending {
+            if !is_covered_by_other(held, &pending) {
+                match held.origin {
+                    DeleteOrigin::Local => {
+                        self.enqueue_delete(&held.path).await?;
+                    }
+                    DeleteOrigin::Cloud => {
+                        let items = self.index.list_items_by_prefix(&held.path).await?;
+                        self.drop_text_bases(&held.path).await;
+                        for item in items.iter().rev() {
+                            self.index.delete_item_by_path(&item.path).await?;
+                        }
+                    }
+                }
+            }
+            self.index.remove_pending_delete(held.id).await?;
+        }
+        self.delete_burst
+            .lock()
+            .expect("delete burst mutex poisoned")
+            .clear();
+        eprintln!("[yadiskd] mass deletion confirmed: {} paths", pending.len());
+        Ok(pending.len())
+    }
+
+    /// Undoes the held deletions: files removed locally are downloaded again,
+    /// files gone from the cloud are uploaded again from the local cache.
+    pub async fn reject_pending_deletes(&self) -> Result<usize, EngineError> {
+        let pending = self.index.list_pending_deletes().await?;
+        for held in &pending {
+            if !is_covered_by_other(held, &pending) {
+                match held.origin {
+                    DeleteOrigin::Local => self.restore_held_local_delete(&held.path).await?,
+                    DeleteOrigin::Cloud => self.restore_held_cloud_delete(&held.path).await?,
+                }
+            }
+            self.index.remove_pending_delete(held.id).await?;
+        }
+        self.delete_burst
+            .lock()
+            .expect("delete burst mutex poisoned")
+            .clear();
+        eprintln!("[yadiskd] mass deletion rejected: {} paths", pending.len());
+        Ok(pending.len())
+    }
+
+    /// Holds a local delete back instead of queueing it when it is part of a
+    /// mass deletion, or while an earlier one waits for the user.
+    async fn hold_local_delete(&self, path: &str) -> Result<bool, EngineError> {
+        let Some(limits) = self.delete_limits else {
+            return Ok(false);
+        };
+        Ok(delete_guard::hold_local_delete(
+            &self.index,
+            limits,
+            &self.delete_burst,
+            path,
+            now_unix(),
+        )
+        .await?)
+    }
+
+    /// Splits items that vanished from the cloud into those that may leave
+    /// the index now and those held back as a mass deletion. Only the
+    /// topmost vanished paths are recorded; their subtrees go with them.
+    async fn hold_cloud_deletes<'a>(
+        &self,
+        tree: &[ItemRecord],
+        vanished: Vec<&'a ItemRecord>,
+    ) -> Result<Vec<&'a ItemRecord>, EngineError> {
+        let Some(limits) = self.delete_limits else {
+            return Ok(vanished);
+        };
+        let pending = self.index.list_pending_deletes().await?;
+        let fresh: Vec<&ItemRecord> = vanished
+            .into_iter()
+            .filter(|item| {
+                !pending.iter().any(|held| {
+                    held.origin == DeleteOrigin::Cloud && is_within(&item.path, &held.path)
+                })
+            })
+            .collect();
+        if fresh.is_empty() {
+            return Ok(fresh);
+        }
+        if !pending.iter().any(|held| held.origin == DeleteOrigin::Cloud) {
+            let files = fresh
+                .iter()
+                .filter(|item| item.item_type == ItemType::File)
+                .count() as u64;
+            let total = tree
+                .iter()
+                .filter(|item| item.item_type == ItemType::File)
+                .count() as u64;
+            if !limits.exceeded_by(files, total) {
+                return Ok(fresh);
+            }
+            eprintln!(
+                "[yadiskd] mass deletion: {files} of {total} files removed in the cloud, \
+                 waiting for confirmation"
+            );
+        }
+
+        let parents: HashMap<&str, Option<&str>> = fresh
+            .iter()
+            .map(|item| (item.path.as_str(), item.parent_path.as_deref()))
+            .collect();
+        let mut files_by_top: HashMap<&str, i64> = HashMap::new();
+        for item in &fresh {
+            let mut top = item.path.as_str();
+            while let Some(dir) = parents.get(top).copied().flatten() {
+                if !parents.contains_key(dir) {
+                    break;
+                }
+                top = dir;
+            }
+            let files = files_by_top.entry(top).or_default();
+            if item.item_type == ItemType::File {
+                *files += 1;
+            }
+        }
+        for (path, files) in files_by_top {
+            self.index
+                .hold_delete(path, DeleteOrigin::Cloud, files, now_unix())
+                .await?;
+        }
+        Ok(Vec::new())
+    }
+
+    /// The cached copies of these files were removed with the local delete,
+    /// so the ones that were available offline are downloaded again.
+    async fn restore_held_local_delete(&self, path: &str) -> Result<(), EngineError> {
+        for item in self.index.list_items_by_prefix(path).await? {
+            if item.item_type != ItemType::File {
+                continue;
+            }
+            let Some(state) = self.index.get_state(item.id).await? else {
+                continue;
+            };
+            if state.pinned || matches!(state.state, FileState::Cached | FileState::Syncing) {
+                self.enqueue_download(&item.path).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Re-creates the folders and uploads the files that still have a cached
+    /// copy; files that were only in the cloud are gone and leave the index.
+    async fn restore_held_cloud_delete(&self, path: &str) -> Result<(), EngineError> {
+        for item in self.index.list_items_by_prefix(path).await? {
+            let cached = item.item_type == ItemType::File
+                && tokio::fs::try_exists(cache_path_for(&self.cache_root, &item.path)?).await?;
+            if item.item_type == ItemType::File && !cached {
+                self.index.delete_item_by_path(&item.path).await?;
+                continue;
+            }
+            // The cloud resource is gone; forget it so the next listing
+            // treats the item as local-only until it is uploaded.
+            self.index
+                .upsert_item(&ItemInput {
+                    path: item.path.clone(),
+                    parent_path: item.parent_path.clone(),
+                    name: item.name.clone(),
+                    item_type: item.item_type.clone(),
+                    size: item.size,
+                    modified: item.modified,
+                    hash: item.hash.clone(),
+                    resource_id: None,
+                    last_synced_hash: None,
+                    last_synced_modified: None,
+                })
+                .await?;
+            if item.item_type == ItemType::Dir {
+                self.enqueue_mkdir(&item.path).await?;
+            } else {
+                self.enqueue_upload(&item.path).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether another held path of the same origin already contains `held`.
+fn is_covered_by_other(held: &PendingDeleteRecord, pending: &[PendingDeleteRecord]) -> bool {
+    pending.iter().any(|other| {
+        other.id != held.id && other.origin == held.origin && is_within(&held.path, &other.path)
+    })
+}
@@ -0,0 +1,131 @@
+impl SyncEngine {
+    /// Operations that failed for good, most recent first.
+    pub async fn list_failed_ops(&self) -> Result<Vec<FailedOpRecord>, EngineError> {
+        Ok(self.index.list_failed_ops().await?)
+    }
+
+    /// Puts a failed operation back on the queue with a fresh retry budget.
+    pub async fn retry_failed_op(&self, id: i64) -> Result<(), EngineError> {
+        let failed = self
+            .index
+            .get_failed_op(id)
+            .await?
+            .ok_or(EngineError::MissingFailedOp(id))?;
+        self.requeue_failed_op(&failed).await
+    }
+
+    /// Requeues every failed operation and returns how many were requeued.
+    /// Ancestors go first so dependents wait for them again.
+    pub async fn retry_all_failed_ops(&self) -> Result<usize, EngineError> {
+        let mut failed = self.index.list_failed_ops().await?;
+        failed.sort_by_key(|op| op.path.trim_end_matches('/').matches('/').count());
+        for op in &failed {
+            self.requeue_failed_op(op).await?;
+        }
+        Ok(failed.len())
+    }
+
+    pub async fn discard_failed_op(&self, id: i64) -> Result<(), EngineError> {
+        if !self.index.delete_failed_op(id).await? {
+            return Err(EngineError::MissingFailedOp(id));
+        }
+        Ok(())
+    }
+
+    async fn requeue_failed_op(&self, failed: &FailedOpRecord) -> Result<(), EngineError> {
+        self.index
+            .enqueue_op(&Operation {
+                kind: failed.kind.clone(),
+                path: failed.path.clone(),
+                payload: failed.payload.clone(),
+                attempt: 0,
+                retry_at: None,
+                priority: failed.priority,
+            })
+            .await?;
+        // Queued again, so the item is pending rather than failed.
+        if let Some(item) = self.index.get_item_by_path(&failed.path).await?
+            && let Some(state) = self.index.get_state(item.id).await?
+            && state.state == FileState::Error
+        {
+            self.index
+                .set_state(item.id, FileState::Syncing, state.pinned, None)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Records `op` as failed for good, together with every queued operation
+    /// that depends on it directly or transitively.
+    async fn dead_letter(&self, op: &Operation, err: &EngineError) {
+        self.record_dead_letter(op, op.attempt.saturating_add(1), &err.to_string())
+            .await;
+        let mut failed = vec![(op.kind.clone(), op.path.clone())];
+        while let Some((kind, path)) = failed.pop() {
+            let dependents = match self.index.take_dependent_ops(&kind, &path).await {
+                Ok(dependents) => dependents,
+                Err(index_err) => {
+                    eprintln!(
+                        "[yadiskd] failed to fail dependents of {kind:?} {path}: {index_err}"
+                    );
+                    continue;
+                }
+            };
+            for dependent in dependents {
+                let dependent = dependent.into_operation();
+                let reason = format!("depends on failed {} {}: {err}", kind.as_str(), path);
+                eprintln!(
+                    "[yadiskd] op failed: kind={:?} path={} err={}",
+                    dependent.kind, dependent.path, reason
+                );
+                self.mark_path_failed(&dependent.path, &reason).await;
+                self.record_dead_letter(&dependent, dependent.attempt, &reason)
+                    .await;
+                failed.push((dependent.kind, dependent.path));
+            }
+        }
+    }
+
+    async fn record_dead_letter(&self, op: &Operation, attempts: u32, last_error: &str) {
+        if let Err(index_err) = self
+            .index
+            .record_failed_op(op, attempts, last_error, now_unix())
+            .await
+        {
+            eprintln!(
+                "[yadiskd] failed to record failed op: kind={:?} path={} err={}",
+                op.kind, op.path, index_err
+            );
+        }
+    }
+
+    async fn mark_path_failed(&self, path: &str, message: &str) {
+        let Ok(Some(item)) = self.index.get_item_by_path(path).await else {
+            return;
+        };
+        let pinned = self
+            .index
+            .get_state(item.id)
+            .await
+            .ok()
+            .flatten()
+            .map(|state| state.pinned)
+            .unwrap_or(true);
+        let _ = self
+            .index
+            .set_state_with_meta(
+                item.id,
+                FileState::Error,
+                pinned,
+                Some(message),
+                StateMeta {
+                    retry_at: None,
+                    last_success_at: None,
+                    last_error_at: Some(now_unix()),
+                    last_accessed: None,
+                    dirty: false,
+                },
+            )
+            .await;
+    }
+}
@@ -53,7 +53,19 @@ impl SyncEngine {
         if let Some(parent) = parent_path(path) {
             self.ensure_cache_dir_for_remote(&parent).await?;
         }
+        if self.symlinks == SymlinkPolicy::Link
+            && let Some(target) = self.index.get_link_target(item.id).await?
+        {
+            return self.restore_symlink(&item, path, &target).await;
+        }
 
+        // Keep the pin the download was queued with: on-demand downloads pin
+        // the file, prefetches leave it for eviction to drop again.
+        let pinned = self
+            .index
+            .get_state(item.id)
+            .await?
+            .is_none_or(|state| state.pinned);
         let link = self
             .call_with_fresh_client(|client| async move { client.get_download_link(path).await })
             .await?;
@@ -73,7 +85,7 @@ impl SyncEngine {
             Ok(()) => {}
             Err(TransferError::Cancelled) => {
                 self.index
-                    .set_state(item.id, FileState::CloudOnly, true, None)
+                    .set_state(item.id, FileState::CloudOnly, pinned, None)
                     .await?;
                 return Ok(());
             }
@@ -84,7 +96,7 @@ impl SyncEngine {
             .set_state_with_meta(
                 item.id,
                 FileState::Cached,
-                true,
+                pinned,
                 None,
                 StateMeta {
                     retry_at: None,
@@ -95,6 +107,7 @@ impl SyncEngine {
                 },
             )
             .await?;
+        self.save_text_base(path, &target).await;
         Ok(())
     }
 
@@ -109,13 +122,21 @@ impl SyncEngine {
         Ok(())
     }
 
-    async fn execute_upload(&self, path: &str) -> Result<(), EngineError> {
-        let source = cache_path_for(&self.cache_root, path)?;
+    async fn execute_upload(&self, path: &str, overwrite: bool) -> Result<(), EngineError> {
+        let mut source = cache_path_for(&self.cache_root, path)?;
         let item = self
             .index
             .get_item_by_path(path)
             .await?
             .ok_or_else(|| EngineError::MissingItem(path.to_string()))?;
+        if tokio::fs::symlink_metadata(&source).await?.is_symlink() {
+            match self.upload_source_for_symlink(path, &source, &item).await? {
+                Some(followed) => source = followed,
+                None => return Ok(()),
+            }
+        } else {
+            self.forget_symlink(path, &item).await?;
+        }
         let local_version = self.local_file_version(&source).await?;
         if let Some(max_size) = self.max_upload_size().await
             && local_version.size > max_size
@@ -156,13 +177,24 @@ impl SyncEngine {
             None
         };
 
-        let decision = if let Some(remote) = &remote {
+        let decision = if overwrite {
+            ConflictDecision::UploadLocal
+        } else if let Some(remote) = &remote {
             let remote_meta = FileMetadata {
                 modified: parse_modified(remote.modified.as_deref())?.unwrap_or(0),
                 hash: remote.md5.clone(),
             };
-            self.resolve_conflict_and_record(path, base.as_ref(), &local_version.meta, &remote_meta)
-                .await?
+            let decision =
+                self.decide_conflict(path, base.as_ref(), &local_version.meta, &remote_meta);
+            // Text files edited in different places are merged instead of
+            // being kept as two copies; only real overlaps become conflicts.
+            if matches!(decision, ConflictDecision::KeepBoth { .. })
+                && self.merge_text_conflict(path, &source, &item, remote).await?
+            {
+                return Ok(());
+            }
+            self.record_conflict_decision(path, &decision).await?;
+            decision
         } else {
             ConflictDecision::UploadLocal
         };
@@ -181,47 +213,123 @@ impl SyncEngine {
                 }
                 self.execute_download(path).await?;
             }
-            ConflictDecision::KeepBoth { renamed_local } => {
-                let renamed_source = cache_path_for(&self.cache_root, &renamed_local)?;
-                if let Some(parent) = renamed_source.parent() {
-                    tokio::fs::create_dir_all(parent).await?;
-                }
-                tokio::fs::copy(&source, &renamed_source).await?;
-                let conflict_item = self
+            ConflictDecision::AskUser => {
+                let pinned = self
                     .index
-                    .upsert_item(&ItemInput {
-                        path: renamed_local.clone(),
-                        parent_path: parent_path(&renamed_local),
-                        name: renamed_local
-                            .split('/')
-                            .next_back()
-                            .unwrap_or(renamed_local.as_str())
-                            .to_string(),
-                        item_type: ItemType::File,
-                        size: Some(local_version.size as i64),
-                        modified: Some(local_version.modified),
-                        hash: Some(local_version.hash.clone()),
-                        resource_id: None,
-                        last_synced_hash: None,
-                        last_synced_modified: None,
-                    })
+                    .get_state(item.id)
+                    .await?
+                    .map(|state| state.pinned)
+                    .unwrap_or(true);
+                self.index
+                    .set_state_with_meta(
+                        item.id,
+                        FileState::Error,
+                        pinned,
+                        Some("conflict awaiting user decision"),
+                        StateMeta {
+                            retry_at: None,
+                            last_success_at: None,
+                            last_error_at: Some(now_unix()),
+                            last_accessed: None,
+                            dirty: false,
+                        },
+                    )
                     .await?;
-                self.upload_path_from_source(
+            }
+            ConflictDecision::KeepBoth { renamed_local } => {
+                let progress = UploadPayload {
+                    overwrite,
+                    keep_both: Some(renamed_local.clone()),
+                };
+                self.record_progress(&OperationKind::Upload, path, &progress)
+                    .await?;
+                self.keep_both_copies(
+                    path,
+                    &source,
                     &renamed_local,
-                    &renamed_source,
-                    &conflict_item,
                     &local_version,
+                    remote.as_ref(),
                 )
                 .await?;
-                if let Some(remote) = &remote {
-                    let _ = self.apply_remote_snapshot(remote).await?;
-                }
-                self.execute_download(path).await?;
             }
         }
         Ok(())
     }
 
+    /// Uploads the local side of a keep-both conflict under `renamed_local`
+    /// and replaces `path` with the remote version.
+    async fn keep_both_copies(
+        &self,
+        path: &str,
+        source: &std::path::Path,
+        renamed_local: &str,
+        local_version: &LocalFileVersion,
+        remote: Option<&yadisk_core::Resource>,
+    ) -> Result<(), EngineError> {
+        let renamed_source = cache_path_for(&self.cache_root, renamed_local)?;
+        if renamed_source != source {
+            if let Some(parent) = renamed_source.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            // Copy through a partial file so an interrupted copy never
+            // passes for the finished one on resume.
+            let partial = renamed_source.with_extension("yadisk-partial");
+            tokio::fs::copy(source, &partial).await?;
+            tokio::fs::rename(&partial, &renamed_source).await?;
+        }
+        let conflict_item = self
+            .index
+            .upsert_item(&ItemInput {
+                path: renamed_local.to_string(),
+                parent_path: parent_path(renamed_local),
+                name: renamed_local
+                    .split('/')
+                    .next_back()
+                    .unwrap_or(renamed_local)
+                    .to_string(),
+                item_type: ItemType::File,
+                size: Some(local_version.size as i64),
+                modified: Some(local_version.modified),
+                hash: Some(local_version.hash.clone()),
+                resource_id: None,
+                last_synced_hash: None,
+                last_synced_modified: None,
+            })
+            .await?;
+        self.upload_path_from_source(renamed_local, &renamed_source, &conflict_item, local_version)
+            .await?;
+        if let Some(remote) = remote {
+            let _ = self.apply_remote_snapshot(remote).await?;
+        }
+        self.execute_download(path).await
+    }
+
+    /// Finishes a keep-both upload interrupted by a restart. The local copy
+    /// is taken from `path` again unless it was already completed.
+    async fn resume_keep_both(&self, path: &str, renamed_local: &str) -> Result<(), EngineError> {
+        let renamed_source = cache_path_for(&self.cache_root, renamed_local)?;
+        let source = if tokio::fs::try_exists(&renamed_source).await? {
+            renamed_source
+        } else {
+            cache_path_for(&self.cache_root, path)?
+        };
+        let local_version = self.local_file_version(&source).await?;
+        let remote = match self
+            .call_with_fresh_client(|client| async move { client.get_resource(path).await })
+            .await
+        {
+            Ok(resource) => Some(resource),
+            Err(EngineError::Api(yadisk_core::YadiskError::Api { status, .. }))
+                if status == reqwest::StatusCode::NOT_FOUND =>
+            {
+                None
+            }
+            Err(err) => return Err(err),
+        };
+        self.keep_both_copies(path, &source, renamed_local, &local_version, remote.as_ref())
+            .await
+    }
+
     async fn local_file_version(&self, source: &std::path::Path) -> Result<LocalFileVersion, EngineError> {
         let meta = tokio::fs::symlink_metadata(source).await?;
         if !meta.is_file() {
@@ -296,9 +404,161 @@ impl SyncEngine {
                 },
             )
             .await?;
+        if let Ok(source) = cache_path_for(&self.cache_root, path) {
+            self.save_text_base(path, &source).await;
+        }
         Ok(())
     }
 
+    /// Three-way merges a text file changed on both sides using the stored
+    /// base, then uploads the result. Returns `false` when the file is not
+    /// eligible or the edits overlap, leaving the conflict to the caller.
+    async fn merge_text_conflict(
+        &self,
+        path: &str,
+        source: &std::path::Path,
+        item: &ItemRecord,
+        remote: &yadisk_core::Resource,
+    ) -> Result<bool, EngineError> {
+        let Some(text_merge) = &self.text_merge else {
+            return Ok(false);
+        };
+        let Some(base_hash) = item.last_synced_hash.as_deref() else {
+            return Ok(false);
+        };
+        if !text_merge.is_text(path, remote.mime_type.as_deref())
+            || remote.size.is_none_or(|size| size > text_merge.max_bytes)
+            || tokio::fs::metadata(source).await?.len() > text_merge.max_bytes
+        {
+            return Ok(false);
+        }
+        let Some(base) = text_merge.load_base(path, base_hash).await? else {
+            return Ok(false);
+        };
+        let Ok(local) = String::from_utf8(tokio::fs::read(source).await?) else {
+            return Ok(false);
+        };
+
+        let link = self
+            .call_with_fresh_client(|client| async move { client.get_download_link(path).await })
+            .await?;
+        let remote_copy = text_merge.scratch_path(path);
+        tokio::fs::create_dir_all(&text_merge.base_dir).await?;
+        let downloaded = self
+            .transfer
+            .download_to_path_checked(link.href.as_str(), &remote_copy, remote.md5.as_deref())
+            .await;
+        let remote_content = match downloaded {
+            Ok(()) => tokio::fs::read(&remote_copy).await.map_err(EngineError::from),
+            Err(err) => Err(err.into()),
+        };
+        let _ = tokio::fs::remove_file(&remote_copy).await;
+        let Ok(remote_text) = String::from_utf8(remote_content?) else {
+            return Ok(false);
+        };
+        let Some(merged) = merge::merge_text(&base, &local, &remote_text) else {
+            return Ok(false);
+        };
+
+        tokio::fs::write(source, merged).await?;
+        self.write_merge_to_sync_root(path, &local, source).await?;
+        let merged_version = self.local_file_version(source).await?;
+        self.upload_path_from_source(path, source, item, &merged_version)
+            .await?;
+        eprintln!("[yadiskd] merged concurrent text edits: {path}");
+        Ok(true)
+    }
+
+    /// Replaces the sync-folder copy the merge started from with the merged
+    /// text and remembers it as written by the daemon, so the unmerged copy
+    /// is not uploaded back over the merge. A copy edited again meanwhile is
+    /// left alone; that edit goes up on its own.
+    async fn write_merge_to_sync_root(
+        &self,
+        path: &str,
+        merged_from: &str,
+        merged: &std::path::Path,
+    ) -> Result<(), EngineError> {
+        let Some(sync_root) = &self.sync_root else {
+            return Ok(());
+        };
+        let names = self.local_names().await?;
+        let target = cache_path_for(sync_root, &names.local_path(path))?;
+        match tokio::fs::read(&target).await {
+            Ok(current) if current == merged_from.as_bytes() => {}
+            _ => return Ok(()),
+        }
+        tokio::fs::copy(merged, &target).await?;
+        let modified = tokio::fs::metadata(merged).await?.modified()?;
+        tokio::fs::File::options()
+            .write(true)
+            .open(&target)
+            .await?
+            .into_std()
+            .await
+            .set_modified(modified)?;
+        let meta = tokio::fs::metadata(&target).await?;
+        let modified = modified
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        self.record_local_baseline(
+            path,
+            LocalBaseline {
+                size: meta.len(),
+                modified,
+            },
+        )
+        .await
+    }
+
+    async fn save_text_base(&self, path: &str, source: &std::path::Path) {
+        let Some(text_merge) = &self.text_merge else {
+            return;
+        };
+        if let Err(err) = text_merge.save_base(path, source).await {
+            eprintln!("[yadiskd] failed to store merge base for {path}: {err}");
+        }
+    }
+
+    /// Drops the merge bases of `path` and of everything indexed below it.
+    async fn drop_text_bases(&self, path: &str) {
+        let Some(text_merge) = &self.text_merge else {
+            return;
+        };
+        let mut paths = vec![path.to_string()];
+        match self.index.list_items_by_prefix(path).await {
+            Ok(items) => paths.extend(items.into_iter().map(|item| item.path)),
+            Err(err) => eprintln!("[yadiskd] failed to list merge bases under {path}: {err}"),
+        }
+        for path in paths {
+            if let Err(err) = text_merge.remove_base(&path).await {
+                eprintln!("[yadiskd] failed to drop merge base for {path}: {err}");
+            }
+        }
+    }
+
+    /// Carries the merge bases of `from` and everything indexed below it over
+    /// to `to`.
+    async fn move_text_bases(&self, from: &str, to: &str) {
+        let Some(text_merge) = &self.text_merge else {
+            return;
+        };
+        let mut moves = vec![(from.to_string(), to.to_string())];
+        match self.index.list_items_by_prefix(from).await {
+            Ok(items) => moves.extend(items.into_iter().filter_map(|item| {
+                let rest = item.path.strip_prefix(from)?.strip_prefix('/')?;
+                Some((item.path.clone(), format!("{}/{rest}", to.trim_end_matches('/'))))
+            })),
+            Err(err) => eprintln!("[yadiskd] failed to list merge bases under {from}: {err}"),
+        }
+        for (from, to) in moves {
+            if let Err(err) = text_merge.move_base(&from, &to).await {
+                eprintln!("[yadiskd] failed to move merge base from {from} to {to}: {err}");
+            }
+        }
+    }
+
     async fn upload_path_from_source(
         &self,
         path: &str,
@@ -306,6 +566,9 @@ impl SyncEngine {
         item: &ItemRecord,
         local_version: &LocalFileVersion,
     ) -> Result<(), EngineError> {
+        if self.copy_from_identical_remote(path, local_version).await? {
+            return self.mark_item_synced(item, path, local_version).await;
+        }
         let link = self
             .call_with_fresh_client(|client| async move { client.get_upload_link(path, true).await })
             .await?;
@@ -355,11 +618,67 @@ impl SyncEngine {
         self.mark_item_synced(item, path, local_version).await
     }
 
+    /// Creates `path` with a server-side copy when a file with the same
+    /// content is already in the cloud. Returns `false` when the bytes still
+    /// have to be uploaded.
+    async fn copy_from_identical_remote(
+        &self,
+        path: &str,
+        local_version: &LocalFileVersion,
+    ) -> Result<bool, EngineError> {
+        if local_version.size == 0 {
+            return Ok(false);
+        }
+        let Some(from) = self
+            .index
+            .find_synced_file_by_content(&local_version.hash, local_version.size, path)
+            .await?
+        else {
+            return Ok(false);
+        };
+        let copy_from = from.clone();
+        let link = match self
+            .call_with_fresh_client(|client| {
+                let from = copy_from.clone();
+                async move { client.copy_resource(&from, path, true).await }
+            })
+            .await
+        {
+            Ok(link) => link,
+            Err(EngineError::Api(yadisk_core::YadiskError::Api {
+                status: reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::CONFLICT,
+                ..
+            })) => {
+                // The index is stale or the target parent is missing; fall
+                // back to a regular upload.
+                return Ok(false);
+            }
+            Err(err) => return Err(err),
+        };
+        if let Some(link) = link {
+            self.wait_for_operation(link.href.as_str()).await?;
+        }
+        let copied = self
+            .call_with_fresh_client(|client| async move {
+                client.get_resource_with_fields(path, Some(&["md5"])).await
+            })
+            .await?;
+        if !copied
+            .md5
+            .as_deref()
+            .is_some_and(|md5| md5.eq_ignore_ascii_case(&local_version.hash))
+        {
+            return Ok(false);
+        }
+        eprintln!("[yadiskd] upload deduplicated: {path} copied from {from}");
+        Ok(true)
+    }
+
     async fn apply_remote_snapshot(
         &self,
         remote: &yadisk_core::Resource,
     ) -> Result<ItemRecord, EngineError> {
-        Ok(self
+        let item = self
             .index
             .upsert_item(&ItemInput {
                 path: remote.path.clone(),
@@ -376,7 +695,9 @@ impl SyncEngine {
                 last_synced_hash: remote.md5.clone(),
                 last_synced_modified: parse_modified(remote.modified.as_deref())?,
             })
-            .await?)
+            .await?;
+        self.record_link_target(item.id, remote).await?;
+        Ok(item)
     }
 
     async fn execute_mkdir(&self, path: &str) -> Result<(), EngineError> {
@@ -416,6 +737,51 @@ impl SyncEngine {
         Ok(())
     }
 
+    async fn execute_delete(&self, op: &Operation) -> Result<(), EngineError> {
+        let path = op.path.as_str();
+        let permanently = self.delete_permanently;
+        // The trash origin is written before the server is asked, so a failed
+        // write leaves the remote copy alone and the retry starts over; once
+        // the server has trashed the item nothing is left that can fail.
+        let trashed = if permanently {
+            None
+        } else {
+            Some(self.record_trashed(path).await?)
+        };
+        match self
+            .call_with_fresh_client(|client| async move {
+                client.delete_resource(path, permanently).await
+            })
+            .await
+        {
+            Ok(link) => {
+                if let Some(link) = link
+                    && let Err(err) = self.wait_for_operation(link.href.as_str()).await
+                {
+                    self.forget_trashed(trashed).await;
+                    return Err(err);
+                }
+            }
+            Err(EngineError::Api(yadisk_core::YadiskError::Api { status, .. }))
+                if status == reqwest::StatusCode::NOT_FOUND =>
+            {
+                // Resource already deleted on remote — treat as success. An
+                // earlier attempt may have trashed it before failing, so its
+                // origin is only kept then.
+                if op.attempt == 0 {
+                    self.forget_trashed(trashed).await;
+                }
+            }
+            Err(err) => {
+                self.forget_trashed(trashed).await;
+                return Err(err);
+            }
+        }
+        self.drop_text_bases(path).await;
+        self.index.delete_item_by_path(path).await?;
+        Ok(())
+    }
+
     async fn execute_move_like_op(&self, op: &Operation) -> Result<(), EngineError> {
         let Some(payload) = &op.payload else {
             return Ok(());
@@ -452,19 +818,33 @@ impl SyncEngine {
                     })
                     .await?;
             }
-            return self.execute_upload(&payload.path).await;
+            return self.execute_upload(&payload.path, false).await;
         }
-        let link = if payload.action == "copy" {
-            self.client
-                .copy_resource(&payload.from, &payload.path, payload.overwrite)
-                .await?
-        } else {
-            self.client
-                .move_resource(&payload.from, &payload.path, payload.overwrite)
-                .await?
+        let restart = MovePayload {
+            operation_url: None,
+            ..payload.clone()
         };
-        if let Some(link) = link {
-            self.wait_for_operation(link.href.as_str()).await?;
+        if let Some(operation_url) = &payload.operation_url {
+            // Issued before a restart; only the async operation is left.
+            self.wait_for_journaled_operation(op, operation_url, &restart).await?;
+        } else {
+            let link = if payload.action == "copy" {
+                self.client
+                    .copy_resource(&payload.from, &payload.path, payload.overwrite)
+                    .await?
+            } else {
+                self.client
+                    .move_resource(&payload.from, &payload.path, payload.overwrite)
+                    .await?
+            };
+            if let Some(link) = link {
+                let progress = MovePayload {
+                    operation_url: Some(link.href.to_string()),
+                    ..payload.clone()
+                };
+                self.record_progress(&op.kind, &op.path, &progress).await?;
+                self.wait_for_journaled_operation(op, link.href.as_str(), &restart).await?;
+            }
         }
 
         if let Some(source) = source_item {
@@ -507,12 +887,127 @@ impl SyncEngine {
                     .await?;
             }
             if payload.action != "copy" {
+                self.move_text_bases(&payload.from, &payload.path).await;
                 self.index.delete_item_by_path(&payload.from).await?;
             }
         }
         Ok(())
     }
 
+    async fn execute_copy(&self, op: &Operation) -> Result<(), EngineError> {
+        let Some(payload) = &op.payload else {
+            return Ok(());
+        };
+        let payload: CopyPayload =
+            serde_json::from_str(payload).map_err(|_| EngineError::OperationFailed)?;
+        let restart = CopyPayload {
+            operation_url: None,
+            ..payload.clone()
+        };
+        if let Some(operation_url) = &payload.operation_url {
+            self.wait_for_journaled_operation(op, operation_url, &restart).await?;
+        } else {
+            let link = self
+                .call_with_fresh_client(|client| {
+                    let payload = payload.clone();
+                    async move {
+                        client
+                            .copy_resource(&payload.from, &payload.path, payload.overwrite)
+                            .await
+                    }
+                })
+                .await?;
+            if let Some(link) = link {
+                let progress = CopyPayload {
+                    operation_url: Some(link.href.to_string()),
+                    ..payload.clone()
+                };
+                self.record_progress(&op.kind, &op.path, &progress).await?;
+                self.wait_for_journaled_operation(op, link.href.as_str(), &restart).await?;
+            }
+        }
+        self.index_copied_tree(&payload.from, &payload.path).await
+    }
+
+    /// Adds index entries for a finished copy of `from` at `to`. Content is
+    /// the same as the source, so the copies count as synced; they are
+    /// cached only where a local copy already exists.
+    async fn index_copied_tree(&self, from: &str, to: &str) -> Result<(), EngineError> {
+        let from = from.trim_end_matches('/');
+        let to = to.trim_end_matches('/');
+        let mut items = self.index.list_items_by_prefix(from).await?;
+        items.retain(|item| {
+            item.path
+                .strip_prefix(from)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        });
+        items.sort_by_key(|item| item.path.len());
+        for item in items {
+            let path = format!("{to}{}", &item.path[from.len()..]);
+            let target = self
+                .index
+                .upsert_item(&ItemInput {
+                    path: path.clone(),
+                    parent_path: parent_path(&path),
+                    name: path.split('/').next_back().unwrap_or(path.as_str()).to_string(),
+                    item_type: item.item_type.clone(),
+                    size: item.size,
+                    modified: item.modified,
+                    hash: item.hash.clone(),
+                    resource_id: None,
+                    last_synced_hash: item.hash.clone(),
+                    last_synced_modified: item.modified,
+                })
+                .await?;
+            let cached = match cache_path_for(&self.cache_root, &path) {
+                Ok(local) => tokio::fs::try_exists(&local).await.unwrap_or(false),
+                Err(_) => false,
+            };
+            let pinned = self
+                .index
+                .get_state(target.id)
+                .await?
+                .is_some_and(|state| state.pinned);
+            self.index
+                .set_state_with_meta(
+                    target.id,
+                    if cached {
+                        FileState::Cached
+                    } else {
+                        FileState::CloudOnly
+                    },
+                    pinned,
+                    None,
+                    StateMeta {
+                        retry_at: None,
+                        last_success_at: Some(now_unix()),
+                        last_error_at: None,
+                        last_accessed: None,
+                        dirty: false,
+                    },
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Waits for the async operation behind a journaled move or copy. When
+    /// the server reports that it failed, the journal goes back to `restart`
+    /// so a retry issues the request again; after a timeout the link is kept
+    /// and the retry keeps waiting on it.
+    async fn wait_for_journaled_operation<T: Serialize>(
+        &self,
+        op: &Operation,
+        operation_url: &str,
+        restart: &T,
+    ) -> Result<(), EngineError> {
+        let result = self.wait_for_operation(operation_url).await;
+        if matches!(result, Err(EngineError::RemoteOperationFailed)) {
+            self.record_progress(&op.kind, &op.path, restart).await?;
+        }
+        result
+    }
+
     async fn wait_for_operation(&self, operation_url: &str) -> Result<(), EngineError> {
         for attempt in 0..10u32 {
             match self
@@ -522,7 +1017,7 @@ impl SyncEngine {
                 .await?
             {
                 OperationStatus::Success => return Ok(()),
-                OperationStatus::Failure => return Err(EngineError::OperationFailed),
+                OperationStatus::Failure => return Err(EngineError::RemoteOperationFailed),
                 OperationStatus::InProgress => {
                     tokio::time::sleep(self.backoff.delay(attempt)).await;
                 }
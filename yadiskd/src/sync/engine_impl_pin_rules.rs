@@ -0,0 +1,97 @@
+impl SyncEngine {
+    pub async fn add_pin_rule(&self, rule: &PinRule) -> Result<i64, EngineError> {
+        let (max_size, max_age_secs) = pin_rule_limits(rule);
+        let id = self
+            .index
+            .add_pin_rule(&rule.pattern, max_size, max_age_secs, now_unix())
+            .await?;
+        eprintln!("[yadiskd] pin rule added id={id} pattern={}", rule.pattern);
+        Ok(id)
+    }
+
+    pub async fn update_pin_rule(&self, id: i64, rule: &PinRule) -> Result<(), EngineError> {
+        let (max_size, max_age_secs) = pin_rule_limits(rule);
+        if !self
+            .index
+            .update_pin_rule(id, &rule.pattern, max_size, max_age_secs)
+            .await?
+        {
+            return Err(EngineError::MissingPinRule(id));
+        }
+        Ok(())
+    }
+
+    /// Files the rule pinned stay pinned until the next sync of their folder
+    /// finds that no rule matches them any more.
+    pub async fn remove_pin_rule(&self, id: i64) -> Result<(), EngineError> {
+        if !self.index.remove_pin_rule(id).await? {
+            return Err(EngineError::MissingPinRule(id));
+        }
+        Ok(())
+    }
+
+    pub async fn list_pin_rules(&self) -> Result<Vec<PinRuleRecord>, EngineError> {
+        Ok(self.index.list_pin_rules().await?)
+    }
+
+    /// Pins the files below `path` that a rule matches and unpins the ones a
+    /// rule pinned earlier but none matches now. Pins set by the user are
+    /// never taken away; the pinned cloud-only files are downloaded by the
+    /// caller.
+    async fn apply_pin_rules(&self, path: &str) -> Result<(), EngineError> {
+        let rules = self
+            .index
+            .list_pin_rules()
+            .await?
+            .iter()
+            .filter_map(pin_rule_from_record)
+            .collect::<Vec<_>>();
+        let now = now_unix();
+        for file in self.index.list_pin_candidates(path).await? {
+            let matched = rules
+                .iter()
+                .any(|rule| rule.matches(&file.path, file.size, file.modified, now));
+            if matched && !file.pinned {
+                if file.state.is_some() {
+                    self.index.set_pinned(file.item_id, true).await?;
+                } else {
+                    self.index
+                        .set_state(file.item_id, FileState::CloudOnly, true, None)
+                        .await?;
+                }
+                self.index.set_auto_pinned(file.item_id, true).await?;
+            } else if !matched && file.auto_pinned {
+                if file.pinned {
+                    self.index.set_pinned(file.item_id, false).await?;
+                }
+                self.index.set_auto_pinned(file.item_id, false).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn pin_rule_limits(rule: &PinRule) -> (Option<i64>, Option<i64>) {
+    (
+        rule.max_size.map(|size| i64::try_from(size).unwrap_or(i64::MAX)),
+        rule.max_age
+            .map(|age| i64::try_from(age.as_secs()).unwrap_or(i64::MAX)),
+    )
+}
+
+fn pin_rule_from_record(record: &PinRuleRecord) -> Option<PinRule> {
+    let mut rule = match PinRule::new(&record.pattern) {
+        Ok(rule) => rule,
+        Err(err) => {
+            eprintln!("[yadiskd] pin rule {} skipped: {err}", record.id);
+            return None;
+        }
+    };
+    if let Some(max_size) = record.max_size {
+        rule = rule.with_max_size(u64::try_from(max_size).unwrap_or(0));
+    }
+    if let Some(max_age_secs) = record.max_age_secs {
+        rule = rule.with_max_age(Duration::from_secs(u64::try_from(max_age_secs).unwrap_or(0)));
+    }
+    Some(rule)
+}
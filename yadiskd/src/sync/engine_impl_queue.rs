@@ -0,0 +1,139 @@
+impl SyncEngine {
+    /// Running operations first, then the queued ones in the order they will
+    /// run.
+    pub async fn list_operations(
+        &self,
+        filter: &OpFilter,
+    ) -> Result<Vec<QueuedOpRecord>, EngineError> {
+        let mut ops = self.index.list_running_ops(filter).await?;
+        ops.extend(self.index.list_ops(filter).await?);
+        Ok(ops)
+    }
+
+    /// Drops a queued operation, or stops the transfer of a running one, and
+    /// settles the state of the path it left in `syncing`. Running moves,
+    /// copies and deletes are single requests that cannot be stopped.
+    pub async fn cancel_operation(&self, id: i64) -> Result<QueuedOpRecord, EngineError> {
+        let op = match self.index.cancel_op(id).await? {
+            Some(op) => {
+                self.cancel_transfer(&op.path);
+                if self.index.list_stale_syncing_paths().await?.contains(&op.path) {
+                    self.reset_syncing_state(&op.path).await?;
+                }
+                op
+            }
+            None => {
+                let op = self
+                    .index
+                    .get_running_op(id)
+                    .await?
+                    .ok_or(EngineError::MissingOperation(id))?;
+                if !matches!(op.kind, OperationKind::Upload | OperationKind::Download) {
+                    return Err(EngineError::OperationRunning(id));
+                }
+                // The transfer sets the state itself when it stops.
+                self.cancel_transfer(&op.path);
+                op
+            }
+        };
+        eprintln!(
+            "[yadiskd] op cancelled: kind={:?} path={}",
+            op.kind, op.path
+        );
+        Ok(op)
+    }
+
+    pub async fn set_operation_priority(&self, id: i64, priority: i32) -> Result<(), EngineError> {
+        if !self.index.set_op_priority(id, priority).await? {
+            return Err(EngineError::MissingOperation(id));
+        }
+        Ok(())
+    }
+
+    /// Stops the worker from taking new operations other than on-demand
+    /// downloads; transfers already running finish normally.
+    pub fn pause_queue(&self) {
+        self.queue_paused.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume_queue(&self) {
+        self.queue_paused.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_queue_paused(&self) -> bool {
+        self.queue_paused.load(Ordering::SeqCst)
+    }
+
+    pub async fn queue_summary(&self) -> Result<QueueSummary, EngineError> {
+        Ok(self.index.queue_summary().await?)
+    }
+
+    /// Whether `op` touches a path that another worker is working on.
+    fn overlaps_in_flight(&self, op: &Operation) -> bool {
+        let in_flight = self.in_flight.lock().expect("in-flight mutex poisoned");
+        op.touched_paths().iter().any(|path| {
+            in_flight
+                .iter()
+                .any(|claimed| paths_overlap(claimed, path))
+        })
+    }
+
+    /// Stores step progress of a running operation, so a restart resumes it
+    /// from there instead of repeating finished steps.
+    async fn record_progress<T: Serialize>(
+        &self,
+        kind: &OperationKind,
+        path: &str,
+        progress: &T,
+    ) -> Result<(), EngineError> {
+        let payload = serde_json::to_string(progress).map_err(|_| EngineError::OperationFailed)?;
+        self.index.update_journal_payload(kind, path, &payload).await?;
+        Ok(())
+    }
+
+    /// Puts operations interrupted by an unclean shutdown back on the queue
+    /// with their recorded progress, and resets items left in `syncing` that
+    /// nothing is going to finish anymore.
+    pub async fn recover_interrupted_ops(&self) -> Result<RecoveryReport, EngineError> {
+        let mut report = RecoveryReport::default();
+        for op in self.index.list_journaled_ops().await? {
+            eprintln!(
+                "[yadiskd] resuming interrupted op: kind={:?} path={}",
+                op.kind, op.path
+            );
+            self.index.enqueue_op(&op).await?;
+            self.index.finish_journal(&op.kind, &op.path).await?;
+            report.requeued += 1;
+        }
+        for path in self.index.list_stale_syncing_paths().await? {
+            if self.reset_syncing_state(&path).await? {
+                report.reset += 1;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Puts a path that no operation is going to finish back to `cached` or
+    /// `cloud_only`, depending on whether its cached copy exists.
+    async fn reset_syncing_state(&self, path: &str) -> Result<bool, EngineError> {
+        let Some(item) = self.index.get_item_by_path(path).await? else {
+            return Ok(false);
+        };
+        let pinned = self
+            .index
+            .get_state(item.id)
+            .await?
+            .is_some_and(|state| state.pinned);
+        let cached = match cache_path_for(&self.cache_root, path) {
+            Ok(local) => tokio::fs::try_exists(&local).await.unwrap_or(false),
+            Err(_) => false,
+        };
+        let state = if cached {
+            FileState::Cached
+        } else {
+            FileState::CloudOnly
+        };
+        self.index.set_state(item.id, state, pinned, None).await?;
+        Ok(true)
+    }
+}
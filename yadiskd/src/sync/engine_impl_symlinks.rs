@@ -0,0 +1,167 @@
+impl SyncEngine {
+    /// Remote paths of files stored as symlinks, with their targets.
+    pub async fn link_targets(&self) -> Result<Vec<(String, String)>, EngineError> {
+        Ok(self.index.list_link_targets().await?)
+    }
+
+    /// Keeps the recorded link target in step with the custom property of
+    /// the remote resource.
+    async fn record_link_target(
+        &self,
+        item_id: i64,
+        resource: &yadisk_core::Resource,
+    ) -> Result<(), EngineError> {
+        let target = match resource.resource_type {
+            ResourceType::File => resource.custom_property(SYMLINK_PROPERTY),
+            ResourceType::Dir => None,
+        };
+        self.index.set_link_target(item_id, target).await?;
+        Ok(())
+    }
+
+    /// Handles a symlink queued for upload as the policy says. Returns the
+    /// file to upload in its place, or `None` when nothing is left to do.
+    async fn upload_source_for_symlink(
+        &self,
+        path: &str,
+        source: &std::path::Path,
+        item: &ItemRecord,
+    ) -> Result<Option<PathBuf>, EngineError> {
+        match self.symlinks {
+            SymlinkPolicy::Skip => {
+                eprintln!("[yadiskd] symlink skipped: {path}");
+                if item.resource_id.is_none() {
+                    self.index.delete_item_by_path(path).await?;
+                }
+                Ok(None)
+            }
+            SymlinkPolicy::Follow => {
+                let unsupported = || EngineError::UnsupportedLocalEntry {
+                    path: source.display().to_string(),
+                };
+                let followed = tokio::fs::canonicalize(source)
+                    .await
+                    .map_err(|_| unsupported())?;
+                if !tokio::fs::metadata(&followed).await?.is_file() {
+                    return Err(unsupported());
+                }
+                Ok(Some(followed))
+            }
+            SymlinkPolicy::Link => {
+                self.upload_symlink(path, source, item).await?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Uploads the link target as the file content and marks the resource
+    /// with `SYMLINK_PROPERTY`, so downloads recreate the symlink.
+    async fn upload_symlink(
+        &self,
+        path: &str,
+        source: &std::path::Path,
+        item: &ItemRecord,
+    ) -> Result<(), EngineError> {
+        let target = tokio::fs::read_link(source).await?;
+        let target = target
+            .to_str()
+            .ok_or_else(|| EngineError::UnsupportedLocalEntry {
+                path: source.display().to_string(),
+            })?;
+        let content = target.as_bytes().to_vec();
+        let hash = format!("{:x}", md5::compute(&content));
+        let modified = tokio::fs::symlink_metadata(source)
+            .await?
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_else(now_unix);
+        let local_version = LocalFileVersion {
+            hash: hash.clone(),
+            modified,
+            size: content.len() as u64,
+            meta: FileMetadata {
+                modified,
+                hash: Some(hash),
+            },
+        };
+
+        let link = self
+            .call_with_fresh_client(|client| async move { client.get_upload_link(path, true).await })
+            .await?;
+        self.transfer
+            .upload_bytes(link.href.as_str(), content)
+            .await?;
+        let resource = self
+            .call_with_fresh_client(|client| async move {
+                client
+                    .set_custom_property(path, SYMLINK_PROPERTY, Some(target))
+                    .await
+            })
+            .await?;
+        self.index.set_link_target(item.id, Some(target)).await?;
+        let item = ItemRecord {
+            resource_id: resource.resource_id.or_else(|| item.resource_id.clone()),
+            ..item.clone()
+        };
+        self.mark_item_synced(&item, path, &local_version).await?;
+        eprintln!("[yadiskd] symlink stored: {path} -> {target}");
+        Ok(())
+    }
+
+    /// A stored symlink replaced by a regular file loses its mark, so other
+    /// machines download it as a file again.
+    async fn forget_symlink(&self, path: &str, item: &ItemRecord) -> Result<(), EngineError> {
+        if self.index.get_link_target(item.id).await?.is_none() {
+            return Ok(());
+        }
+        match self
+            .call_with_fresh_client(|client| async move {
+                client.set_custom_property(path, SYMLINK_PROPERTY, None).await
+            })
+            .await
+        {
+            Ok(_) => {}
+            Err(EngineError::Api(yadisk_core::YadiskError::Api { status, .. }))
+                if status == reqwest::StatusCode::NOT_FOUND => {}
+            Err(err) => return Err(err),
+        }
+        self.index.set_link_target(item.id, None).await?;
+        Ok(())
+    }
+
+    /// Recreates a stored symlink in the cache instead of downloading the
+    /// file that holds its target.
+    async fn restore_symlink(
+        &self,
+        item: &ItemRecord,
+        path: &str,
+        target: &str,
+    ) -> Result<(), EngineError> {
+        let local = cache_path_for(&self.cache_root, path)?;
+        match tokio::fs::symlink_metadata(&local).await {
+            Ok(meta) if meta.is_dir() => tokio::fs::remove_dir_all(&local).await?,
+            Ok(_) => tokio::fs::remove_file(&local).await?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+        tokio::fs::symlink(target, &local).await?;
+        self.index
+            .set_state_with_meta(
+                item.id,
+                FileState::Cached,
+                true,
+                None,
+                StateMeta {
+                    retry_at: None,
+                    last_success_at: Some(now_unix()),
+                    last_error_at: None,
+                    last_accessed: None,
+                    dirty: false,
+                },
+            )
+            .await?;
+        Ok(())
+    }
+}
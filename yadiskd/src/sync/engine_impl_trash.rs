@@ -0,0 +1,97 @@
+impl SyncEngine {
+    /// Remembers that `path` went to the trash, so `restore_deleted` can
+    /// find it there later.
+    async fn record_trashed(&self, path: &str) -> Result<i64, EngineError> {
+        let item = self.index.get_item_by_path(path).await?;
+        Ok(self
+            .index
+            .record_trashed_item(
+                path,
+                item.as_ref().map(|item| &item.item_type),
+                item.as_ref().and_then(|item| item.resource_id.as_deref()),
+                now_unix(),
+            )
+            .await?)
+    }
+
+    /// Drops the trash origin of a delete that did not go through.
+    async fn forget_trashed(&self, id: Option<i64>) {
+        if let Some(id) = id
+            && let Err(err) = self.index.remove_trashed_item(id).await
+        {
+            eprintln!("[yadiskd] warning: failed to drop trash record {id}: {err}");
+        }
+    }
+
+    pub async fn list_trashed(&self) -> Result<Vec<TrashedItemRecord>, EngineError> {
+        Ok(self.index.list_trashed_items().await?)
+    }
+
+    /// Restores a path deleted from this machine out of the Yandex trash,
+    /// indexes it again and downloads its files.
+    pub async fn restore_deleted(&self, path: &str) -> Result<(), EngineError> {
+        let record = self
+            .index
+            .get_trashed_item(path)
+            .await?
+            .ok_or_else(|| EngineError::MissingItem(path.to_string()))?;
+        let trash = self
+            .call_with_fresh_client(|client| async move { client.list_trash_all(100).await })
+            .await?;
+        let origin = path_variants(&record.path);
+        let from_origin = |entry: &&yadisk_core::Resource| {
+            entry
+                .origin_path
+                .as_deref()
+                .is_some_and(|origin_path| origin.iter().any(|path| path == origin_path))
+        };
+        // Prefer the exact resource; otherwise the newest entry deleted from
+        // the same place.
+        let entry = trash
+            .iter()
+            .filter(from_origin)
+            .find(|entry| {
+                record.resource_id.is_some() && entry.resource_id == record.resource_id
+            })
+            .or_else(|| {
+                trash.iter().filter(from_origin).max_by_key(|entry| {
+                    parse_modified(entry.deleted.as_deref()).ok().flatten()
+                })
+            })
+            .ok_or_else(|| EngineError::NotInTrash(record.path.clone()))?;
+
+        let trash_path = entry.path.as_str();
+        let link = self
+            .call_with_fresh_client(|client| async move {
+                client.restore_from_trash(trash_path, false).await
+            })
+            .await?;
+        if let Some(link) = link {
+            self.wait_for_operation(link.href.as_str()).await?;
+        }
+        eprintln!("[yadiskd] restored from trash: {}", record.path);
+
+        let restored_path = record.path.as_str();
+        let root = self
+            .call_with_fresh_client(|client| async move {
+                client.get_resource(restored_path).await
+            })
+            .await?;
+        let mut restored = Vec::new();
+        if root.resource_type == ResourceType::Dir {
+            restored = self.collect_remote_tree(restored_path).await?;
+        }
+        restored.insert(0, root);
+        for resource in &restored {
+            let item = self.apply_remote_snapshot(resource).await?;
+            if item.item_type == ItemType::File {
+                self.index
+                    .set_state(item.id, FileState::CloudOnly, false, None)
+                    .await?;
+                self.enqueue_download(&item.path).await?;
+            }
+        }
+        self.index.remove_trashed_item(record.id).await?;
+        Ok(())
+    }
+}
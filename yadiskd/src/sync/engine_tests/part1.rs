@@ -3,9 +3,10 @@
     use std::sync::Arc;
     use std::path::Path;
     use tempfile::tempdir;
-    use wiremock::matchers::{body_bytes, header, method, path, query_param};
+    use wiremock::matchers::{body_bytes, header, method, path, query_param, query_param_is_missing};
     use wiremock::{Mock, MockServer, ResponseTemplate};
     use crate::storage::OAuthState;
+    use crate::sync::conflict::{ConflictChoice, ConflictPolicy};
     use crate::token_provider::TokenProvider;
     use tokio::sync::Mutex as AsyncMutex;
     use yadisk_core::OAuthClient;
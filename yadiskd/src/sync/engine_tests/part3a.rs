@@ -181,7 +181,7 @@
         Mock::given(method("DELETE"))
             .and(path("/v1/disk/resources"))
             .and(query_param("path", "disk:/Docs/Delete.txt"))
-            .and(query_param("permanently", "true"))
+            .and(query_param_is_missing("permanently"))
             .respond_with(ResponseTemplate::new(204))
             .mount(&server)
             .await;
@@ -217,6 +217,14 @@
             .await
             .unwrap()
             .is_none());
+        let trashed = engine
+            .index
+            .get_trashed_item("/Docs/Delete.txt")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(trashed.path, "disk:/Docs/Delete.txt");
+        assert_eq!(trashed.item_type, Some(ItemType::File));
     }
 
     #[tokio::test]
@@ -225,7 +233,7 @@
         Mock::given(method("DELETE"))
             .and(path("/v1/disk/resources"))
             .and(query_param("path", "disk:/Docs/Race.txt"))
-            .and(query_param("permanently", "true"))
+            .and(query_param_is_missing("permanently"))
             .respond_with(ResponseTemplate::new(204))
             .mount(&server)
             .await;
@@ -275,7 +283,7 @@
         Mock::given(method("DELETE"))
             .and(path("/v1/disk/resources"))
             .and(query_param("path", "disk:/Docs/InFlight.txt"))
-            .and(query_param("permanently", "true"))
+            .and(query_param_is_missing("permanently"))
             .respond_with(ResponseTemplate::new(204))
             .mount(&server)
             .await;
@@ -725,6 +733,173 @@
         assert_eq!(conflicts[0].path, "/Docs/A.txt");
     }
 
+    #[tokio::test]
+    async fn conflict_resolution_follows_subtree_policy() {
+        let server = MockServer::start().await;
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path()).await.with_conflict_policies(
+            ConflictPolicies::new(ConflictPolicy::NewestWins)
+                .with_subtree("/Shared", ConflictPolicy::Ask),
+        );
+        let base = FileMetadata {
+            modified: 1,
+            hash: Some("base".into()),
+        };
+        let local = FileMetadata {
+            modified: 2,
+            hash: Some("local".into()),
+        };
+        let remote = FileMetadata {
+            modified: 3,
+            hash: Some("remote".into()),
+        };
+        let decision = engine
+            .resolve_conflict_and_record("/Docs/A.txt", Some(&base), &local, &remote)
+            .await
+            .unwrap();
+        assert_eq!(decision, ConflictDecision::DownloadRemote);
+        assert!(engine.index.list_conflicts().await.unwrap().is_empty());
+
+        let decision = engine
+            .resolve_conflict_and_record("/Shared/A.txt", Some(&base), &local, &remote)
+            .await
+            .unwrap();
+        assert_eq!(decision, ConflictDecision::AskUser);
+        let conflicts = engine.index.list_conflicts().await.unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].path, "/Shared/A.txt");
+        assert_eq!(conflicts[0].reason, "needs-decision");
+    }
+
+    async fn seed_cached_file(engine: &SyncEngine, cache_root: &Path, path: &str, body: &[u8]) {
+        let local = cache_path_for(cache_root, path).unwrap();
+        std::fs::create_dir_all(local.parent().unwrap()).unwrap();
+        std::fs::write(&local, body).unwrap();
+        let item = engine
+            .index
+            .upsert_item(&ItemInput {
+                path: path.to_string(),
+                parent_path: parent_path(path),
+                name: path.rsplit('/').next().unwrap().to_string(),
+                item_type: ItemType::File,
+                size: Some(body.len() as i64),
+                modified: Some(1),
+                hash: None,
+                resource_id: None,
+                last_synced_hash: None,
+                last_synced_modified: None,
+            })
+            .await
+            .unwrap();
+        engine
+            .index
+            .set_state(item.id, FileState::Cached, true, None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn resolving_kept_copies_queues_cleanup_and_closes_conflict() {
+        let server = MockServer::start().await;
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path()).await;
+        seed_cached_file(&engine, dir.path(), "/Docs/A.txt", b"remote").await;
+        seed_cached_file(&engine, dir.path(), "/Docs/A (conflict 2-x).txt", b"local").await;
+        let id = engine
+            .index
+            .record_conflict("/Docs/A.txt", "/Docs/A (conflict 2-x).txt", 2, "both-changed")
+            .await
+            .unwrap();
+
+        engine
+            .resolve_conflict(id, ConflictChoice::KeepLocal)
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read(dir.path().join("Docs/A.txt")).unwrap(),
+            b"local"
+        );
+        assert!(!dir.path().join("Docs/A (conflict 2-x).txt").exists());
+        let op = engine.index.dequeue_op().await.unwrap().unwrap();
+        assert_eq!(op.kind, OperationKind::Move);
+        assert_eq!(op.path, "/Docs/A.txt");
+        assert!(engine.list_conflicts().await.unwrap().is_empty());
+        assert!(matches!(
+            engine.resolve_conflict(id, ConflictChoice::KeepRemote).await,
+            Err(EngineError::MissingConflict(_))
+        ));
+
+        let id = engine
+            .index
+            .record_conflict("/Docs/A.txt", "/Docs/A (conflict 3-y).txt", 3, "both-changed")
+            .await
+            .unwrap();
+        engine
+            .resolve_conflict(id, ConflictChoice::KeepRemote)
+            .await
+            .unwrap();
+        let op = engine.index.dequeue_op().await.unwrap().unwrap();
+        assert_eq!(op.kind, OperationKind::Delete);
+        assert_eq!(op.path, "/Docs/A (conflict 3-y).txt");
+    }
+
+    #[tokio::test]
+    async fn conflict_details_describe_both_versions() {
+        let server = MockServer::start().await;
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path()).await;
+        seed_cached_file(&engine, dir.path(), "/Docs/A.txt", b"remote!").await;
+        seed_cached_file(&engine, dir.path(), "/Docs/A (conflict 2-x).txt", b"local").await;
+        engine
+            .index
+            .record_conflict("/Docs/A.txt", "/Docs/A (conflict 2-x).txt", 2, "both-changed")
+            .await
+            .unwrap();
+
+        let details = engine.list_conflict_details().await.unwrap();
+        assert_eq!(details.len(), 1);
+        assert_eq!(details[0].local.map(|local| local.size), Some(5));
+        assert!(details[0].local.unwrap().modified > 1);
+        assert_eq!(
+            details[0].remote,
+            Some(VersionInfo {
+                size: 7,
+                modified: 1
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn resolving_undecided_conflict_keeps_local_with_overwrite() {
+        let server = MockServer::start().await;
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path()).await;
+        seed_cached_file(&engine, dir.path(), "/Docs/A.txt", b"local").await;
+        let id = engine
+            .index
+            .record_conflict("/Docs/A.txt", "/Docs/A.txt", 2, "needs-decision")
+            .await
+            .unwrap();
+
+        engine
+            .resolve_conflict(id, ConflictChoice::KeepLocal)
+            .await
+            .unwrap();
+        let op = engine.index.dequeue_op().await.unwrap().unwrap();
+        assert_eq!(op.kind, OperationKind::Upload);
+        let payload: UploadPayload = serde_json::from_str(op.payload.as_deref().unwrap()).unwrap();
+        assert!(payload.overwrite);
+
+        let id = engine
+            .index
+            .record_conflict("/Docs/A.txt", "/Docs/A.txt", 3, "needs-decision")
+            .await
+            .unwrap();
+        engine.dismiss_conflict(id).await.unwrap();
+        assert!(engine.index.dequeue_op().await.unwrap().is_none());
+        assert!(engine.list_conflicts().await.unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn run_once_move_uses_payload_and_updates_index() {
         let server = MockServer::start().await;
@@ -225,7 +225,9 @@
     async fn run_once_upload_rejects_unsupported_local_entry() {
         let server = MockServer::start().await;
         let dir = tempdir().unwrap();
-        let engine = make_engine(&server, dir.path()).await;
+        let engine = make_engine(&server, dir.path())
+            .await
+            .with_symlink_policy(SymlinkPolicy::Follow);
         engine
             .index
             .upsert_item(&ItemInput {
@@ -246,8 +248,8 @@
         tokio::fs::create_dir_all(source.parent().unwrap())
             .await
             .unwrap();
-        let target = dir.path().join("target.txt");
-        tokio::fs::write(&target, b"payload").await.unwrap();
+        let target = dir.path().join("target");
+        tokio::fs::create_dir_all(&target).await.unwrap();
         std::os::unix::fs::symlink(&target, &source).unwrap();
         engine.enqueue_upload("/Docs/Link.txt").await.unwrap();
 
@@ -331,6 +333,157 @@
         assert!(engine.index.dequeue_op().await.unwrap().is_none());
     }
 
+    #[tokio::test]
+    async fn paused_queue_holds_operations_until_resumed() {
+        let server = MockServer::start().await;
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path()).await;
+        let id = engine
+            .index
+            .enqueue_op(&Operation {
+                kind: OperationKind::Download,
+                path: "/Docs/Missing.txt".into(),
+                payload: None,
+                attempt: 0,
+                retry_at: None,
+                priority: 10,
+            })
+            .await
+            .unwrap();
+
+        engine.pause_queue();
+        assert!(!engine.run_once().await.unwrap());
+        assert!(!engine.has_active_or_queued_work().await.unwrap());
+        assert_eq!(engine.queue_summary().await.unwrap().pending, 1);
+
+        engine.set_operation_priority(id, 90).await.unwrap();
+        let queued = engine.list_operations(&OpFilter::default()).await.unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].priority, 90);
+        engine.cancel_operation(id).await.unwrap();
+        assert!(matches!(
+            engine.cancel_operation(id).await,
+            Err(EngineError::MissingOperation(_))
+        ));
+
+        engine.resume_queue();
+        assert!(!engine.is_queue_paused());
+        assert!(!engine.run_once().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn operations_wait_while_an_overlapping_path_is_in_flight() {
+        let server = MockServer::start().await;
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path()).await;
+        engine
+            .index
+            .enqueue_op(&Operation {
+                kind: OperationKind::Download,
+                path: "/Docs/Missing.txt".into(),
+                payload: None,
+                attempt: 0,
+                retry_at: None,
+                priority: 10,
+            })
+            .await
+            .unwrap();
+
+        let claimed = InFlightPaths::claim(&engine.in_flight, vec!["/Docs".to_string()]);
+        assert!(!engine.run_once().await.unwrap());
+        assert!(engine.has_active_or_queued_work().await.unwrap());
+        assert_eq!(engine.queue_summary().await.unwrap().pending, 1);
+
+        drop(claimed);
+        assert!(engine.in_flight.lock().unwrap().is_empty());
+        let _ = engine.run_once().await;
+        assert_eq!(engine.queue_summary().await.unwrap().pending, 0);
+    }
+
+    #[tokio::test]
+    async fn failed_operations_are_kept_for_retry() {
+        let server = MockServer::start().await;
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path()).await;
+        let op = Operation {
+            kind: OperationKind::Upload,
+            path: "/Docs/Missing.txt".into(),
+            payload: Some(r#"{"overwrite":true}"#.into()),
+            attempt: 0,
+            retry_at: None,
+            priority: 7,
+        };
+        engine.index.enqueue_op(&op).await.unwrap();
+        engine.run_once().await.expect_err("expected failure");
+        assert_eq!(engine.list_failed_ops().await.unwrap().len(), 1);
+        // Queueing the same operation again supersedes the recorded failure.
+        engine.index.enqueue_op(&op).await.unwrap();
+        assert!(engine.list_failed_ops().await.unwrap().is_empty());
+        engine.run_once().await.expect_err("expected failure");
+
+        let failed = engine.list_failed_ops().await.unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].kind, OperationKind::Upload);
+        assert_eq!(failed[0].payload, op.payload);
+        assert_eq!(failed[0].attempts, 1);
+        assert!(!failed[0].last_error.is_empty());
+
+        engine.retry_failed_op(failed[0].id).await.unwrap();
+        assert!(engine.list_failed_ops().await.unwrap().is_empty());
+        let requeued = engine.index.dequeue_op().await.unwrap().unwrap();
+        assert_eq!(requeued, op);
+        assert!(matches!(
+            engine.retry_failed_op(failed[0].id).await,
+            Err(EngineError::MissingFailedOp(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn failed_mkdir_fails_its_dependents() {
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/v1/disk/resources"))
+            .respond_with(ResponseTemplate::new(400).set_body_string("bad request"))
+            .mount(&server)
+            .await;
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path()).await;
+        for (kind, op_path) in [
+            (OperationKind::Mkdir, "/New"),
+            (OperationKind::Mkdir, "/New/Sub"),
+            (OperationKind::Upload, "/New/Sub/a.txt"),
+            (OperationKind::Download, "/Other.txt"),
+        ] {
+            engine
+                .index
+                .enqueue_op(&Operation {
+                    kind,
+                    path: op_path.into(),
+                    payload: None,
+                    attempt: 0,
+                    retry_at: None,
+                    priority: 0,
+                })
+                .await
+                .unwrap();
+        }
+
+        engine.run_once().await.expect_err("mkdir should fail");
+
+        let mut failed: Vec<String> = engine
+            .list_failed_ops()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|op| op.path)
+            .collect();
+        failed.sort();
+        assert_eq!(failed, vec!["/New", "/New/Sub", "/New/Sub/a.txt"]);
+        let queued = engine.list_operations(&OpFilter::default()).await.unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].path, "/Other.txt");
+    }
+
     #[tokio::test]
     async fn run_once_stops_requeue_at_max_attempts() {
         let server = MockServer::start().await;
@@ -379,3 +532,429 @@
         assert!(matches!(err, EngineError::Api(_)));
         assert!(engine.index.dequeue_op().await.unwrap().is_none());
     }
+
+    #[tokio::test]
+    async fn upload_conflict_merges_text_edits_to_different_lines() {
+        let base = b"# Notes\nalpha\nbeta\ngamma\n";
+        let local = b"# Notes\nalpha (local)\nbeta\ngamma\n";
+        let remote = b"# Notes\nalpha\nbeta\ngamma (remote)\n";
+        let merged = b"# Notes\nalpha (local)\nbeta\ngamma (remote)\n";
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/disk/resources"))
+            .and(query_param("path", "/Docs/notes.md"))
+            .and(query_param("fields", "md5"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "path": "/Docs/notes.md",
+                "name": "notes.md",
+                "type": "file",
+                "md5": format!("{:x}", md5::compute(merged))
+            })))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/disk/resources"))
+            .and(query_param("path", "/Docs/notes.md"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "path": "/Docs/notes.md",
+                "name": "notes.md",
+                "type": "file",
+                "size": remote.len(),
+                "modified": "2024-01-01T00:00:00Z",
+                "md5": format!("{:x}", md5::compute(remote)),
+                "mime_type": "text/markdown"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/disk/resources/download"))
+            .and(query_param("path", "/Docs/notes.md"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "href": format!("{}/file", server.uri()),
+                "method": "GET",
+                "templated": false
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/file"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(remote.to_vec()))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/disk/resources/upload"))
+            .and(query_param("path", "/Docs/notes.md"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "href": format!("{}/upload", server.uri()),
+                "method": "PUT",
+                "templated": false
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/upload"))
+            .and(body_bytes(merged.to_vec()))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = tempdir().unwrap();
+        let bases = tempdir().unwrap();
+        let sync_root = tempdir().unwrap();
+        let text_merge = TextMerge::new(bases.path());
+        let engine = make_engine(&server, dir.path())
+            .await
+            .with_text_merge(text_merge.clone())
+            .with_sync_root(sync_root.path().to_path_buf());
+        let base_file = bases.path().join("base.md");
+        tokio::fs::write(&base_file, base).await.unwrap();
+        text_merge.save_base("/Docs/notes.md", &base_file).await.unwrap();
+        let base_hash = format!("{:x}", md5::compute(base));
+        engine
+            .index
+            .upsert_item(&ItemInput {
+                path: "/Docs/notes.md".into(),
+                parent_path: Some("/Docs".into()),
+                name: "notes.md".into(),
+                item_type: ItemType::File,
+                size: Some(base.len() as i64),
+                modified: Some(1),
+                hash: Some(base_hash.clone()),
+                resource_id: None,
+                last_synced_hash: Some(base_hash),
+                last_synced_modified: Some(1),
+            })
+            .await
+            .unwrap();
+        let source = cache_path_for(dir.path(), "/Docs/notes.md").unwrap();
+        tokio::fs::create_dir_all(source.parent().unwrap())
+            .await
+            .unwrap();
+        tokio::fs::write(&source, local).await.unwrap();
+        let synced = sync_root.path().join("Docs/notes.md");
+        tokio::fs::create_dir_all(synced.parent().unwrap())
+            .await
+            .unwrap();
+        tokio::fs::write(&synced, local).await.unwrap();
+
+        engine.enqueue_upload("/Docs/notes.md").await.unwrap();
+        assert!(engine.run_once().await.unwrap());
+
+        assert_eq!(tokio::fs::read(&source).await.unwrap(), merged);
+        // The sync folder gets the merge too, recorded as the daemon's own
+        // write so it is not taken for a local edit.
+        assert_eq!(tokio::fs::read(&synced).await.unwrap(), merged);
+        let baselines = engine.list_local_baselines_by_prefix("/").await.unwrap();
+        assert_eq!(baselines.len(), 1);
+        assert_eq!(baselines[0].0, "/Docs/notes.md");
+        assert_eq!(baselines[0].1.size, merged.len() as u64);
+        assert!(engine.list_conflicts().await.unwrap().is_empty());
+        let merged_hash = format!("{:x}", md5::compute(merged));
+        let item = engine
+            .index
+            .get_item_by_path("/Docs/notes.md")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(item.last_synced_hash.as_deref(), Some(merged_hash.as_str()));
+        assert_eq!(
+            text_merge
+                .load_base("/Docs/notes.md", &merged_hash)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some(std::str::from_utf8(merged).unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn recovery_resumes_an_interrupted_move_and_resets_stale_syncing() {
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/v1/disk/resources/move"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/disk/operations/77"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "success"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path()).await;
+        for (path, name) in [("/Docs/A.txt", "A.txt"), ("/Docs/C.txt", "C.txt")] {
+            engine
+                .index
+                .upsert_item(&ItemInput {
+                    path: path.into(),
+                    parent_path: Some("/Docs".into()),
+                    name: name.into(),
+                    item_type: ItemType::File,
+                    size: Some(1),
+                    modified: None,
+                    hash: None,
+                    resource_id: None,
+                    last_synced_hash: None,
+                    last_synced_modified: None,
+                })
+                .await
+                .unwrap();
+        }
+        let stale = engine
+            .index
+            .get_item_by_path("/Docs/C.txt")
+            .await
+            .unwrap()
+            .unwrap();
+        engine
+            .index
+            .set_state(stale.id, FileState::Syncing, true, None)
+            .await
+            .unwrap();
+        let cached = cache_path_for(dir.path(), "/Docs/C.txt").unwrap();
+        tokio::fs::create_dir_all(cached.parent().unwrap())
+            .await
+            .unwrap();
+        tokio::fs::write(&cached, b"c").await.unwrap();
+
+        // Simulate a crash after the move was accepted by the API.
+        engine
+            .enqueue_move("/Docs/A.txt", "/Docs/B.txt", "move")
+            .await
+            .unwrap();
+        let claimed = engine.index.dequeue_op().await.unwrap().unwrap();
+        let progress = serde_json::json!({
+            "from": "/Docs/A.txt",
+            "path": "/Docs/B.txt",
+            "overwrite": true,
+            "operation_url": format!("{}/v1/disk/operations/77", server.uri()),
+        });
+        engine
+            .index
+            .update_journal_payload(&claimed.kind, &claimed.path, &progress.to_string())
+            .await
+            .unwrap();
+
+        let report = engine.recover_interrupted_ops().await.unwrap();
+        assert_eq!(
+            report,
+            RecoveryReport {
+                requeued: 1,
+                reset: 1
+            }
+        );
+        let state = engine.index.get_state(stale.id).await.unwrap().unwrap();
+        assert_eq!(state.state, FileState::Cached);
+        assert!(state.pinned);
+
+        assert!(engine.run_once().await.unwrap());
+        assert!(
+            engine
+                .index
+                .get_item_by_path("/Docs/B.txt")
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert!(engine.index.list_journaled_ops().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn copy_duplicates_the_folder_on_the_server_and_in_the_index() {
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/v1/disk/resources/copy"))
+            .and(query_param("from", "/Templates/App"))
+            .and(query_param("path", "/Projects/App"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "href": format!("{}/v1/disk/resources?path=/Projects/App", server.uri()),
+                "method": "GET",
+                "templated": false
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/v1/disk/resources/upload"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path()).await;
+        for (path, item_type) in [
+            ("/Templates/App", ItemType::Dir),
+            ("/Templates/App/main.rs", ItemType::File),
+            ("/Templates/Apple.txt", ItemType::File),
+        ] {
+            let item = engine
+                .index
+                .upsert_item(&ItemInput {
+                    path: path.into(),
+                    parent_path: parent_path(path),
+                    name: path.rsplit('/').next().unwrap().into(),
+                    item_type,
+                    size: Some(4),
+                    modified: Some(10),
+                    hash: Some("h".into()),
+                    resource_id: Some(format!("rid-{path}")),
+                    last_synced_hash: Some("h".into()),
+                    last_synced_modified: Some(10),
+                })
+                .await
+                .unwrap();
+            engine
+                .index
+                .set_state(item.id, FileState::Cached, true, None)
+                .await
+                .unwrap();
+        }
+
+        assert!(matches!(
+            engine.enqueue_copy("/Templates/Missing", "/Projects/Missing").await,
+            Err(EngineError::MissingItem(_))
+        ));
+        engine
+            .enqueue_copy("/Templates/App", "/Projects/App")
+            .await
+            .unwrap();
+        assert!(engine.run_once().await.unwrap());
+
+        let copied = engine
+            .index
+            .get_item_by_path("/Projects/App/main.rs")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(copied.hash.as_deref(), Some("h"));
+        assert_eq!(copied.last_synced_hash.as_deref(), Some("h"));
+        assert_eq!(copied.resource_id, None);
+        let state = engine.index.get_state(copied.id).await.unwrap().unwrap();
+        assert_eq!(state.state, FileState::CloudOnly);
+        assert!(!state.pinned);
+        assert!(
+            engine
+                .index
+                .get_item_by_path("/Projects/Apple.txt")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            engine
+                .index
+                .get_item_by_path("/Templates/App/main.rs")
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn restore_deleted_brings_a_trashed_file_back() {
+        let server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path("/v1/disk/resources"))
+            .and(query_param("path", "/Docs/A.txt"))
+            .and(query_param_is_missing("permanently"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/disk/trash/resources"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "_embedded": {
+                    "limit": 100,
+                    "offset": 0,
+                    "total": 2,
+                    "items": [
+                        {
+                            "path": "trash:/A.txt_old",
+                            "name": "A.txt",
+                            "type": "file",
+                            "origin_path": "disk:/Docs/A.txt",
+                            "deleted": "2026-01-01T00:00:00+00:00"
+                        },
+                        {
+                            "path": "trash:/A.txt_new",
+                            "name": "A.txt",
+                            "type": "file",
+                            "origin_path": "disk:/Docs/A.txt",
+                            "deleted": "2026-10-18T00:00:00+00:00"
+                        }
+                    ]
+                }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/v1/disk/trash/resources/restore"))
+            .and(query_param("path", "trash:/A.txt_new"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/disk/resources"))
+            .and(query_param("path", "/Docs/A.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "path": "/Docs/A.txt",
+                "name": "A.txt",
+                "type": "file",
+                "size": 5,
+                "md5": "5d41402abc4b2a76b9719d911017c592"
+            })))
+            .mount(&server)
+            .await;
+
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path()).await;
+        engine
+            .index
+            .upsert_item(&ItemInput {
+                path: "/Docs/A.txt".into(),
+                parent_path: Some("/Docs".into()),
+                name: "A.txt".into(),
+                item_type: ItemType::File,
+                size: Some(5),
+                modified: None,
+                hash: None,
+                resource_id: None,
+                last_synced_hash: None,
+                last_synced_modified: None,
+            })
+            .await
+            .unwrap();
+        engine.enqueue_delete("/Docs/A.txt").await.unwrap();
+        assert!(engine.run_once().await.unwrap());
+        assert!(engine.index.get_item_by_path("/Docs/A.txt").await.unwrap().is_none());
+        assert_eq!(engine.list_trashed().await.unwrap().len(), 1);
+
+        assert!(matches!(
+            engine.restore_deleted("/Docs/B.txt").await,
+            Err(EngineError::MissingItem(_))
+        ));
+        engine.restore_deleted("/Docs/A.txt").await.unwrap();
+
+        assert!(engine.list_trashed().await.unwrap().is_empty());
+        let restored = engine
+            .index
+            .get_item_by_path("/Docs/A.txt")
+            .await
+            .unwrap()
+            .unwrap();
+        let state = engine.index.get_state(restored.id).await.unwrap().unwrap();
+        assert_eq!(state.state, FileState::Syncing);
+        let queued = engine.list_operations(&OpFilter::default()).await.unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].kind, OperationKind::Download);
+    }
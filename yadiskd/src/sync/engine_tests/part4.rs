@@ -272,3 +272,1054 @@
             "item should be deleted from index after 404 delete"
         );
     }
+
+    #[tokio::test]
+    async fn run_once_defers_upload_until_local_writes_settle() {
+        let server = MockServer::start().await;
+        let cache = tempdir().unwrap();
+        let sync_root = tempdir().unwrap();
+        let client = YadiskClient::with_base_url(&server.uri(), "test-token").unwrap();
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        let store = IndexStore::from_pool(pool.clone());
+        store.init().await.unwrap();
+        let engine = SyncEngine::new(client, store, cache.path().to_path_buf())
+            .with_write_settle(WriteSettle {
+                sync_root: sync_root.path().to_path_buf(),
+                quiet: std::time::Duration::from_secs(60),
+                max_wait: std::time::Duration::from_secs(600),
+            });
+
+        let local = sync_root.path().join("Docs/A.txt");
+        std::fs::create_dir_all(local.parent().unwrap()).unwrap();
+        std::fs::write(&local, b"partial").unwrap();
+        let target = cache_path_for(cache.path(), "/Docs/A.txt").unwrap();
+        std::fs::create_dir_all(target.parent().unwrap()).unwrap();
+        std::fs::write(&target, b"partial").unwrap();
+
+        engine.enqueue_upload("/Docs/A.txt").await.unwrap();
+        assert!(!engine.run_once().await.unwrap());
+
+        assert!(!engine.index.has_ready_op().await.unwrap());
+        let (attempt, retry_at): (i64, Option<i64>) =
+            sqlx::query_as("SELECT attempt, retry_at FROM ops_queue WHERE path = ?1")
+                .bind("/Docs/A.txt")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(attempt, 0);
+        assert!(retry_at.is_some_and(|at| at > now_unix()));
+        let item = engine
+            .index
+            .get_item_by_path("/Docs/A.txt")
+            .await
+            .unwrap()
+            .unwrap();
+        let state = engine.index.get_state(item.id).await.unwrap().unwrap();
+        assert_eq!(state.state, FileState::Syncing);
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn run_once_upload_copies_identical_content_on_server() {
+        let server = MockServer::start().await;
+        let md5 = format!("{:x}", md5::compute(b"payload"));
+        Mock::given(method("PUT"))
+            .and(path("/v1/disk/resources/copy"))
+            .and(query_param("from", "/Docs/A.txt"))
+            .and(query_param("path", "/Copy/A.txt"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/disk/resources"))
+            .and(query_param("path", "/Copy/A.txt"))
+            .and(query_param("fields", "md5"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "path": "/Copy/A.txt",
+                "name": "A.txt",
+                "type": "file",
+                "md5": md5
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/disk/resources/upload"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path()).await;
+        let original = engine
+            .index
+            .upsert_item(&ItemInput {
+                path: "/Docs/A.txt".into(),
+                parent_path: Some("/Docs".into()),
+                name: "A.txt".into(),
+                item_type: ItemType::File,
+                size: Some(7),
+                modified: Some(1),
+                hash: Some(md5.clone()),
+                resource_id: Some("rid-a".into()),
+                last_synced_hash: Some(md5.clone()),
+                last_synced_modified: Some(1),
+            })
+            .await
+            .unwrap();
+        engine
+            .index
+            .set_state(original.id, FileState::CloudOnly, false, None)
+            .await
+            .unwrap();
+        let target = cache_path_for(dir.path(), "/Copy/A.txt").unwrap();
+        std::fs::create_dir_all(target.parent().unwrap()).unwrap();
+        std::fs::write(&target, b"payload").unwrap();
+
+        engine.enqueue_upload("/Copy/A.txt").await.unwrap();
+        assert!(engine.run_once().await.unwrap());
+
+        let item = engine
+            .index
+            .get_item_by_path("/Copy/A.txt")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(item.last_synced_hash.as_deref(), Some(md5.as_str()));
+        let state = engine.index.get_state(item.id).await.unwrap().unwrap();
+        assert_eq!(state.state, FileState::Cached);
+    }
+
+    async fn index_cloud_only_files(engine: &SyncEngine, dir: &str, count: usize) {
+        for i in 0..count {
+            let item = engine
+                .index
+                .upsert_item(&ItemInput {
+                    path: format!("{dir}/F{i}.txt"),
+                    parent_path: Some(dir.to_string()),
+                    name: format!("F{i}.txt"),
+                    item_type: ItemType::File,
+                    size: Some(1),
+                    modified: Some(1),
+                    hash: Some(format!("h{i}")),
+                    resource_id: Some(format!("rid-{i}")),
+                    last_synced_hash: Some(format!("h{i}")),
+                    last_synced_modified: Some(1),
+                })
+                .await
+                .unwrap();
+            engine
+                .index
+                .set_state(item.id, FileState::CloudOnly, false, None)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn local_mass_delete_is_held_until_confirmed() {
+        let server = MockServer::start().await;
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path())
+            .await
+            .with_delete_guard(DeleteLimits {
+                max_files: 3,
+                max_percent: 0,
+            });
+        index_cloud_only_files(&engine, "/Docs", 5).await;
+
+        for i in 0..3 {
+            let id = engine
+                .ingest_local_event(LocalEvent::Delete {
+                    path: format!("/Docs/F{i}.txt"),
+                })
+                .await
+                .unwrap();
+            assert!(id > 0);
+        }
+        // The fourth file in a row trips the limit; the queued deletes of the
+        // same burst are held back with it.
+        for i in 3..5 {
+            let id = engine
+                .ingest_local_event(LocalEvent::Delete {
+                    path: format!("/Docs/F{i}.txt"),
+                })
+                .await
+                .unwrap();
+            assert_eq!(id, 0);
+        }
+        assert!(engine.index.list_ops(&OpFilter::default()).await.unwrap().is_empty());
+        let pending = engine.list_pending_deletes().await.unwrap();
+        assert_eq!(pending.len(), 5);
+        assert!(pending.iter().all(|held| held.origin == DeleteOrigin::Local));
+
+        assert_eq!(engine.confirm_pending_deletes().await.unwrap(), 5);
+        let ops = engine.index.list_ops(&OpFilter::default()).await.unwrap();
+        assert_eq!(ops.len(), 5);
+        assert!(ops.iter().all(|op| op.kind == OperationKind::Delete));
+        assert!(engine.list_pending_deletes().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn cloud_mass_delete_keeps_items_until_rejected() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/disk/resources"))
+            .and(query_param("path", "disk:/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "_embedded": {
+                    "limit": 100,
+                    "offset": 0,
+                    "total": 0,
+                    "items": []
+                }
+            })))
+            .mount(&server)
+            .await;
+
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path())
+            .await
+            .with_delete_guard(DeleteLimits {
+                max_files: 0,
+                max_percent: 50,
+            });
+        engine
+            .index
+            .upsert_item(&ItemInput {
+                path: "disk:/Docs".into(),
+                parent_path: Some("disk:/".into()),
+                name: "Docs".into(),
+                item_type: ItemType::Dir,
+                size: None,
+                modified: None,
+                hash: None,
+                resource_id: Some("rid-docs".into()),
+                last_synced_hash: None,
+                last_synced_modified: None,
+            })
+            .await
+            .unwrap();
+        index_cloud_only_files(&engine, "disk:/Docs", 12).await;
+        let cached = cache_path_for(dir.path(), "disk:/Docs/F0.txt").unwrap();
+        std::fs::create_dir_all(cached.parent().unwrap()).unwrap();
+        std::fs::write(&cached, b"x").unwrap();
+
+        for _ in 0..2 {
+            let delta = engine.sync_directory_incremental("disk:/").await.unwrap();
+            assert_eq!(delta.deleted, 0);
+        }
+        let pending = engine.list_pending_deletes().await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].path, "disk:/Docs");
+        assert_eq!(pending[0].origin, DeleteOrigin::Cloud);
+        assert_eq!(pending[0].files, 12);
+        assert_eq!(
+            engine.index.list_items_by_prefix("disk:/Docs").await.unwrap().len(),
+            13
+        );
+
+        assert_eq!(engine.reject_pending_deletes().await.unwrap(), 1);
+        let kept = engine.index.list_items_by_prefix("disk:/Docs").await.unwrap();
+        assert_eq!(
+            kept.iter().map(|item| item.path.as_str()).collect::<Vec<_>>(),
+            vec!["disk:/Docs", "disk:/Docs/F0.txt"]
+        );
+        assert!(kept.iter().all(|item| item.resource_id.is_none()));
+        let ops = engine.index.list_ops(&OpFilter::default()).await.unwrap();
+        let mut kinds: Vec<_> = ops.iter().map(|op| op.kind.as_str()).collect();
+        kinds.sort();
+        assert_eq!(kinds, vec!["mkdir", "upload"]);
+    }
+
+    #[tokio::test]
+    async fn names_clashing_by_case_or_normalization_get_distinct_local_names() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/disk/resources"))
+            .and(query_param("path", "/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "_embedded": {
+                    "limit": 100,
+                    "offset": 0,
+                    "total": 4,
+                    "items": [
+                        {"path": "disk:/Cafe\u{301}.txt", "name": "Cafe\u{301}.txt",
+                         "type": "file", "size": 1, "md5": "nfd"},
+                        {"path": "disk:/Caf\u{e9}.txt", "name": "Caf\u{e9}.txt",
+                         "type": "file", "size": 1, "md5": "nfc"},
+                        {"path": "disk:/Readme.md", "name": "Readme.md",
+                         "type": "file", "size": 1, "md5": "r1"},
+                        {"path": "disk:/Ru\u{308}be.txt", "name": "Ru\u{308}be.txt",
+                         "type": "file", "size": 1, "md5": "r2"}
+                    ]
+                }
+            })))
+            .mount(&server)
+            .await;
+
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path()).await;
+        for _ in 0..2 {
+            engine.sync_directory_incremental("/").await.unwrap();
+            assert_eq!(
+                engine.index.list_local_names().await.unwrap(),
+                vec![
+                    ("disk:/Cafe\u{301}.txt".to_string(), "Caf\u{e9} (2).txt".to_string()),
+                    ("disk:/Ru\u{308}be.txt".to_string(), "R\u{fc}be.txt".to_string()),
+                ]
+            );
+        }
+
+        let names = engine.local_names().await.unwrap();
+        assert_eq!(names.local_path("disk:/Caf\u{e9}.txt"), "/Caf\u{e9}.txt");
+        assert_eq!(names.remote_path("/Caf\u{e9} (2).txt"), "/Cafe\u{301}.txt");
+        assert_eq!(names.remote_path("/R\u{fc}be.txt"), "/Ru\u{308}be.txt");
+        assert_eq!(names.remote_path("/Readme.md"), "/Readme.md");
+    }
+
+    #[tokio::test]
+    async fn skipped_symlink_is_not_uploaded_and_leaves_the_index() {
+        let server = MockServer::start().await;
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path()).await;
+        engine
+            .index
+            .upsert_item(&ItemInput {
+                path: "/Docs/Link".into(),
+                parent_path: Some("/Docs".into()),
+                name: "Link".into(),
+                item_type: ItemType::File,
+                size: None,
+                modified: None,
+                hash: None,
+                resource_id: None,
+                last_synced_hash: None,
+                last_synced_modified: None,
+            })
+            .await
+            .unwrap();
+        let source = cache_path_for(dir.path(), "/Docs/Link").unwrap();
+        std::fs::create_dir_all(source.parent().unwrap()).unwrap();
+        std::os::unix::fs::symlink("A.txt", &source).unwrap();
+        engine.enqueue_upload("/Docs/Link").await.unwrap();
+
+        assert!(engine.run_once().await.unwrap());
+
+        assert!(
+            engine
+                .index
+                .get_item_by_path("/Docs/Link")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn linked_symlink_is_stored_as_link_file_and_recreated_on_download() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/disk/resources/upload"))
+            .and(query_param("path", "/Docs/Link"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "href": format!("{}/upload", server.uri()),
+                "method": "PUT",
+                "templated": false
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/upload"))
+            .and(body_bytes(b"../A.txt"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/v1/disk/resources"))
+            .and(query_param("path", "/Docs/Link"))
+            .and(wiremock::matchers::body_json(serde_json::json!({
+                "custom_properties": { "yadisk_symlink": "../A.txt" }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "path": "disk:/Docs/Link",
+                "name": "Link",
+                "type": "file",
+                "resource_id": "1:link",
+                "custom_properties": { "yadisk_symlink": "../A.txt" }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path())
+            .await
+            .with_symlink_policy(SymlinkPolicy::Link);
+        let item = engine
+            .index
+            .upsert_item(&ItemInput {
+                path: "/Docs/Link".into(),
+                parent_path: Some("/Docs".into()),
+                name: "Link".into(),
+                item_type: ItemType::File,
+                size: None,
+                modified: None,
+                hash: None,
+                resource_id: None,
+                last_synced_hash: None,
+                last_synced_modified: None,
+            })
+            .await
+            .unwrap();
+        let source = cache_path_for(dir.path(), "/Docs/Link").unwrap();
+        std::fs::create_dir_all(source.parent().unwrap()).unwrap();
+        std::os::unix::fs::symlink("../A.txt", &source).unwrap();
+        engine.enqueue_upload("/Docs/Link").await.unwrap();
+        assert!(engine.run_once().await.unwrap());
+
+        assert_eq!(
+            engine.link_targets().await.unwrap(),
+            vec![("/Docs/Link".to_string(), "../A.txt".to_string())]
+        );
+        let synced = engine
+            .index
+            .get_item_by_path("/Docs/Link")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(synced.resource_id.as_deref(), Some("1:link"));
+        assert_eq!(synced.size, Some(8));
+
+        // The cached copy is gone after eviction; downloading recreates the
+        // symlink without fetching the link file.
+        std::fs::remove_file(&source).unwrap();
+        engine.enqueue_download("/Docs/Link").await.unwrap();
+        assert!(engine.run_once().await.unwrap());
+        assert_eq!(
+            std::fs::read_link(&source).unwrap(),
+            std::path::PathBuf::from("../A.txt")
+        );
+        let state = engine.index.get_state(item.id).await.unwrap().unwrap();
+        assert_eq!(state.state, FileState::Cached);
+    }
+
+    #[tokio::test]
+    async fn pin_rules_pin_matching_files_and_release_them_when_removed() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/disk/resources"))
+            .and(query_param("path", "/Work"))
+            .and(query_param("limit", "100"))
+            .and(query_param("offset", "0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "_embedded": {
+                    "limit": 100,
+                    "offset": 0,
+                    "total": 3,
+                    "items": [
+                        {"path": "/Work/Report.pdf", "name": "Report.pdf", "type": "file", "size": 10, "resource_id": "rid-1", "md5": "aa"},
+                        {"path": "/Work/Scan.pdf", "name": "Scan.pdf", "type": "file", "size": 100, "resource_id": "rid-2", "md5": "bb"},
+                        {"path": "/Work/Notes.txt", "name": "Notes.txt", "type": "file", "size": 1, "resource_id": "rid-3", "md5": "cc"}
+                    ]
+                }
+            })))
+            .mount(&server)
+            .await;
+
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path()).await;
+        let rule = PinRule::new("*.pdf").unwrap().with_max_size(50);
+        let id = engine.add_pin_rule(&rule).await.unwrap();
+        assert_eq!(engine.list_pin_rules().await.unwrap()[0].max_size, Some(50));
+
+        let delta = engine.sync_directory_incremental("/Work").await.unwrap();
+        assert_eq!(delta.enqueued_downloads, 1);
+        let pinned = |path: &'static str| {
+            let engine = &engine;
+            async move {
+                let item = engine.index.get_item_by_path(path).await.unwrap().unwrap();
+                engine
+                    .index
+                    .get_state(item.id)
+                    .await
+                    .unwrap()
+                    .is_some_and(|state| state.pinned)
+            }
+        };
+        assert!(pinned("/Work/Report.pdf").await);
+        assert!(!pinned("/Work/Scan.pdf").await);
+
+        engine.pin_path("/Work/Notes.txt", true).await.unwrap();
+        engine.remove_pin_rule(id).await.unwrap();
+        assert!(matches!(
+            engine.remove_pin_rule(id).await,
+            Err(EngineError::MissingPinRule(_))
+        ));
+        engine.sync_directory_incremental("/Work").await.unwrap();
+        assert!(!pinned("/Work/Report.pdf").await);
+        assert!(pinned("/Work/Notes.txt").await);
+    }
+
+    #[tokio::test]
+    async fn pinned_folder_pins_files_added_later_until_unpinned() {
+        async fn mount_listing(server: &MockServer, dir: &str, items: serde_json::Value) {
+            let total = items.as_array().map_or(0, Vec::len);
+            Mock::given(method("GET"))
+                .and(path("/v1/disk/resources"))
+                .and(query_param("path", dir))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "_embedded": {"limit": 100, "offset": 0, "total": total, "items": items}
+                })))
+                .mount(server)
+                .await;
+        }
+        let docs = serde_json::json!(
+            {"path": "/Docs", "name": "Docs", "type": "dir", "resource_id": "rid-docs"}
+        );
+        let sub = serde_json::json!(
+            {"path": "/Docs/Sub", "name": "Sub", "type": "dir", "resource_id": "rid-sub"}
+        );
+
+        let server = MockServer::start().await;
+        mount_listing(&server, "/", serde_json::json!([docs])).await;
+        mount_listing(&server, "/Docs", serde_json::json!([sub])).await;
+        mount_listing(&server, "/Docs/Sub", serde_json::json!([])).await;
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path()).await;
+        engine.sync_directory_incremental("/").await.unwrap();
+        engine.pin_path("/Docs", true).await.unwrap();
+        engine.pin_path("/Docs/Sub", false).await.unwrap();
+
+        server.reset().await;
+        mount_listing(&server, "/", serde_json::json!([docs])).await;
+        mount_listing(
+            &server,
+            "/Docs",
+            serde_json::json!([sub, {"path": "/Docs/New.txt", "name": "New.txt", "type": "file", "size": 3, "resource_id": "rid-1", "md5": "aa"}]),
+        )
+        .await;
+        mount_listing(
+            &server,
+            "/Docs/Sub",
+            serde_json::json!([{"path": "/Docs/Sub/B.txt", "name": "B.txt", "type": "file", "size": 3, "resource_id": "rid-2", "md5": "bb"}]),
+        )
+        .await;
+        let delta = engine.sync_directory_incremental("/").await.unwrap();
+        assert_eq!(delta.enqueued_downloads, 1);
+        let pinned = |path: &'static str| {
+            let engine = &engine;
+            async move {
+                let item = engine.index.get_item_by_path(path).await.unwrap().unwrap();
+                engine
+                    .index
+                    .get_state(item.id)
+                    .await
+                    .unwrap()
+                    .is_some_and(|state| state.pinned)
+            }
+        };
+        assert!(pinned("/Docs/New.txt").await);
+        assert!(!pinned("/Docs/Sub/B.txt").await);
+
+        engine.pin_path("/Docs", false).await.unwrap();
+        assert!(!pinned("/Docs/New.txt").await);
+        assert_eq!(
+            engine.index.list_folder_pins().await.unwrap(),
+            vec![("/Docs".to_string(), false)]
+        );
+    }
+
+    #[tokio::test]
+    async fn prefetch_queues_following_cloud_only_files_unpinned() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/disk/resources/download"))
+            .and(query_param("path", "/Album/B.jpg"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "href": format!("{}/file", server.uri()),
+                "method": "GET",
+                "templated": false
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/file"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"jpeg"))
+            .mount(&server)
+            .await;
+
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path()).await.with_prefetch(PrefetchPolicy {
+            siblings: 3,
+            max_size: 100,
+        });
+        let mut ids = HashMap::new();
+        for (name, size, state) in [
+            ("A.jpg", 4, FileState::Cached),
+            ("B.jpg", 4, FileState::CloudOnly),
+            ("C.jpg", 4, FileState::Cached),
+            ("D.jpg", 1_000, FileState::CloudOnly),
+            ("E.jpg", 4, FileState::CloudOnly),
+            ("F.jpg", 4, FileState::CloudOnly),
+        ] {
+            let item = engine
+                .index
+                .upsert_item(&ItemInput {
+                    path: format!("/Album/{name}"),
+                    parent_path: Some("/Album".into()),
+                    name: name.into(),
+                    item_type: ItemType::File,
+                    size: Some(size),
+                    modified: None,
+                    hash: None,
+                    resource_id: None,
+                    last_synced_hash: None,
+                    last_synced_modified: None,
+                })
+                .await
+                .unwrap();
+            engine.index.set_state(item.id, state, false, None).await.unwrap();
+            ids.insert(name, item.id);
+        }
+
+        assert_eq!(engine.prefetch_siblings("/Album/A.jpg").await.unwrap(), 2);
+        let ops = engine.index.list_ops(&OpFilter::default()).await.unwrap();
+        let queued: Vec<_> = ops.iter().map(|op| (op.path.as_str(), op.priority)).collect();
+        assert_eq!(
+            queued,
+            vec![
+                ("/Album/B.jpg", PREFETCH_PRIORITY),
+                ("/Album/E.jpg", PREFETCH_PRIORITY)
+            ]
+        );
+        assert_eq!(engine.prefetch_siblings("/Album/A.jpg").await.unwrap(), 0);
+
+        engine
+            .index
+            .delete_ops_for_path("/Album/E.jpg")
+            .await
+            .unwrap();
+        assert!(engine.run_once().await.unwrap());
+        let state = engine.index.get_state(ids["B.jpg"]).await.unwrap().unwrap();
+        assert_eq!(state.state, FileState::Cached);
+        assert!(!state.pinned);
+
+        let engine = engine.with_prefetch(PrefetchPolicy {
+            siblings: 0,
+            max_size: 0,
+        });
+        assert_eq!(engine.prefetch_siblings("/Album/E.jpg").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn cancel_operation_stops_running_download_and_settles_queued_ones() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/disk/resources/download"))
+            .and(query_param("path", "/Docs/A.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "href": format!("{}/file", server.uri()),
+                "method": "GET",
+                "templated": false
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/file"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_bytes(b"hello")
+                    .set_delay(std::time::Duration::from_secs(5)),
+            )
+            .mount(&server)
+            .await;
+
+        let dir = tempdir().unwrap();
+        let engine = std::sync::Arc::new(make_engine(&server, dir.path()).await);
+        let mut ids = Vec::new();
+        for name in ["A.txt", "B.txt"] {
+            let item = engine
+                .index
+                .upsert_item(&ItemInput {
+                    path: format!("/Docs/{name}"),
+                    parent_path: Some("/Docs".into()),
+                    name: name.into(),
+                    item_type: ItemType::File,
+                    size: Some(5),
+                    modified: None,
+                    hash: None,
+                    resource_id: None,
+                    last_synced_hash: None,
+                    last_synced_modified: None,
+                })
+                .await
+                .unwrap();
+            engine
+                .index
+                .set_state(item.id, FileState::CloudOnly, false, None)
+                .await
+                .unwrap();
+            ids.push(item.id);
+        }
+        let running = engine.enqueue_download("/Docs/A.txt").await.unwrap();
+        let queued = engine.enqueue_download("/Docs/B.txt").await.unwrap();
+        engine.set_operation_priority(queued, -1).await.unwrap();
+
+        let engine_clone = std::sync::Arc::clone(&engine);
+        let handle = tokio::spawn(async move { engine_clone.run_once().await });
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+
+        let ops = engine.list_operations(&OpFilter::default()).await.unwrap();
+        assert_eq!(
+            ops.iter()
+                .map(|op| (op.id, op.running))
+                .collect::<Vec<_>>(),
+            vec![(running, true), (queued, false)]
+        );
+        engine.cancel_operation(running).await.unwrap();
+        handle.await.unwrap().unwrap();
+        let state = engine.index.get_state(ids[0]).await.unwrap().unwrap();
+        assert_eq!(state.state, FileState::CloudOnly);
+
+        engine.cancel_operation(queued).await.unwrap();
+        let state = engine.index.get_state(ids[1]).await.unwrap().unwrap();
+        assert_eq!(state.state, FileState::CloudOnly);
+        assert!(engine.list_operations(&OpFilter::default()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn paused_queue_still_runs_on_demand_downloads() {
+        let server = MockServer::start().await;
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path()).await;
+        for (path, priority) in [
+            ("/Docs/Later.txt", 50),
+            ("/Docs/Opened.txt", ON_DEMAND_PRIORITY),
+        ] {
+            engine
+                .index
+                .enqueue_op(&Operation {
+                    kind: OperationKind::Download,
+                    path: path.into(),
+                    payload: None,
+                    attempt: 0,
+                    retry_at: None,
+                    priority,
+                })
+                .await
+                .unwrap();
+        }
+
+        engine.pause_queue();
+        // Neither file is indexed, so the download itself fails; what matters
+        // is which one was taken.
+        assert!(engine.run_once().await.is_err());
+        let left = engine.list_operations(&OpFilter::default()).await.unwrap();
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].path, "/Docs/Later.txt");
+        assert!(!engine.run_once().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn failed_async_move_is_issued_again_on_retry() {
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/v1/disk/resources/move"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "href": format!("{}/v1/disk/operations/77", server.uri()),
+                "method": "GET",
+                "templated": false
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/disk/operations/77"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "failure"
+            })))
+            .mount(&server)
+            .await;
+
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path()).await;
+        engine
+            .index
+            .upsert_item(&ItemInput {
+                path: "/Docs/A.txt".into(),
+                parent_path: Some("/Docs".into()),
+                name: "A.txt".into(),
+                item_type: ItemType::File,
+                size: Some(1),
+                modified: None,
+                hash: None,
+                resource_id: None,
+                last_synced_hash: None,
+                last_synced_modified: None,
+            })
+            .await
+            .unwrap();
+        engine
+            .enqueue_move("/Docs/A.txt", "/Docs/B.txt", "move")
+            .await
+            .unwrap();
+
+        assert!(engine.run_once().await.unwrap());
+        let queued = engine.index.list_ops(&OpFilter::default()).await.unwrap();
+        assert_eq!(queued.len(), 1);
+        let payload: serde_json::Value =
+            serde_json::from_str(queued[0].payload.as_deref().unwrap()).unwrap();
+        assert!(payload.get("operation_url").is_none());
+
+        server.verify().await;
+        server.reset().await;
+        Mock::given(method("PUT"))
+            .and(path("/v1/disk/resources/move"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "href": format!("{}/v1/disk/operations/78", server.uri()),
+                "method": "GET",
+                "templated": false
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/disk/operations/78"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "success"
+            })))
+            .mount(&server)
+            .await;
+        // Skip the backoff delay.
+        let retry = engine.index.cancel_op(queued[0].id).await.unwrap().unwrap();
+        engine
+            .index
+            .enqueue_op(&Operation {
+                retry_at: None,
+                ..retry.into_operation()
+            })
+            .await
+            .unwrap();
+
+        assert!(engine.run_once().await.unwrap());
+        assert!(
+            engine
+                .index
+                .get_item_by_path("/Docs/B.txt")
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn retried_failure_clears_error_state_and_success_clears_the_record() {
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/v1/disk/resources"))
+            .respond_with(ResponseTemplate::new(400).set_body_string("bad request"))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/v1/disk/resources"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path()).await;
+        let item = engine
+            .index
+            .upsert_item(&ItemInput {
+                path: "/New".into(),
+                parent_path: Some("/".into()),
+                name: "New".into(),
+                item_type: ItemType::Dir,
+                size: None,
+                modified: None,
+                hash: None,
+                resource_id: None,
+                last_synced_hash: None,
+                last_synced_modified: None,
+            })
+            .await
+            .unwrap();
+        let mkdir = Operation {
+            kind: OperationKind::Mkdir,
+            path: "/New".into(),
+            payload: None,
+            attempt: 0,
+            retry_at: None,
+            priority: 0,
+        };
+        engine.index.enqueue_op(&mkdir).await.unwrap();
+        engine.run_once().await.expect_err("mkdir should fail");
+        let state = engine.index.get_state(item.id).await.unwrap().unwrap();
+        assert_eq!(state.state, FileState::Error);
+
+        let failed = engine.list_failed_ops().await.unwrap();
+        assert_eq!(failed.len(), 1);
+        engine.retry_failed_op(failed[0].id).await.unwrap();
+        let state = engine.index.get_state(item.id).await.unwrap().unwrap();
+        assert_eq!(state.state, FileState::Syncing);
+        assert!(state.last_error.is_none());
+        let queued = engine.list_operations(&OpFilter::default()).await.unwrap();
+        engine.cancel_operation(queued[0].id).await.unwrap();
+
+        // A record left over from an earlier failure goes once the operation
+        // succeeds.
+        engine.enqueue_delete("/New").await.unwrap();
+        let delete = Operation {
+            kind: OperationKind::Delete,
+            ..mkdir
+        };
+        engine
+            .index
+            .record_failed_op(&delete, 1, "stale", 0)
+            .await
+            .unwrap();
+        assert_eq!(engine.list_failed_ops().await.unwrap().len(), 1);
+        assert!(engine.run_once().await.unwrap());
+        assert!(engine.list_failed_ops().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn merge_bases_go_with_evicted_and_deleted_files() {
+        let server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path("/v1/disk/resources"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+        let dir = tempdir().unwrap();
+        let bases = tempdir().unwrap();
+        let text_merge = TextMerge::new(bases.path().join("bases"));
+        let engine = make_engine(&server, dir.path())
+            .await
+            .with_text_merge(text_merge.clone());
+        let source = bases.path().join("source.md");
+        tokio::fs::write(&source, "hello\n").await.unwrap();
+        let hash = format!("{:x}", md5::compute("hello\n"));
+        for name in ["a.md", "b.md"] {
+            let file_path = format!("/Docs/{name}");
+            engine
+                .index
+                .upsert_item(&ItemInput {
+                    path: file_path.clone(),
+                    parent_path: Some("/Docs".into()),
+                    name: name.into(),
+                    item_type: ItemType::File,
+                    size: Some(6),
+                    modified: None,
+                    hash: Some(hash.clone()),
+                    resource_id: None,
+                    last_synced_hash: Some(hash.clone()),
+                    last_synced_modified: None,
+                })
+                .await
+                .unwrap();
+            text_merge.save_base(&file_path, &source).await.unwrap();
+        }
+
+        engine.evict_path("/Docs/a.md").await.unwrap();
+        assert_eq!(text_merge.load_base("/Docs/a.md", &hash).await.unwrap(), None);
+
+        engine.enqueue_delete("/Docs/b.md").await.unwrap();
+        assert!(engine.run_once().await.unwrap());
+        assert_eq!(text_merge.load_base("/Docs/b.md", &hash).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn delete_that_fails_permanently_is_kept_as_failed() {
+        let server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path("/v1/disk/resources"))
+            .respond_with(ResponseTemplate::new(403).set_body_string("forbidden"))
+            .mount(&server)
+            .await;
+        let dir = tempdir().unwrap();
+        let engine = make_engine(&server, dir.path()).await;
+        engine
+            .index
+            .upsert_item(&ItemInput {
+                path: "/Locked.txt".into(),
+                parent_path: Some("/".into()),
+                name: "Locked.txt".into(),
+                item_type: ItemType::File,
+                size: Some(1),
+                modified: None,
+                hash: None,
+                resource_id: None,
+                last_synced_hash: None,
+                last_synced_modified: None,
+            })
+            .await
+            .unwrap();
+        engine.enqueue_delete("/Locked.txt").await.unwrap();
+
+        engine.run_once().await.expect_err("delete should fail");
+
+        let failed = engine.list_failed_ops().await.unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].kind, OperationKind::Delete);
+        assert_eq!(failed[0].path, "/Locked.txt");
+        assert!(engine.index.get_item_by_path("/Locked.txt").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn trash_origin_is_recorded_before_the_server_delete() {
+        let server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path("/v1/disk/resources"))
+            .respond_with(ResponseTemplate::new(404).set_body_string("not found"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let dir = tempdir().unwrap();
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        let store = IndexStore::from_pool(pool.clone());
+        store.init().await.unwrap();
+        let client = YadiskClient::with_base_url(&server.uri(), "test-token").unwrap();
+        let engine = SyncEngine::new(client, store, dir.path().to_path_buf());
+        let delete = |path: &str, attempt| Operation {
+            kind: OperationKind::Delete,
+            path: path.into(),
+            payload: None,
+            attempt,
+            retry_at: None,
+            priority: 0,
+        };
+
+        // A retry finding the item gone keeps the origin an earlier attempt
+        // may have trashed it from.
+        engine
+            .index
+            .enqueue_op(&delete("/Docs/A.txt", 1))
+            .await
+            .unwrap();
+        assert!(engine.run_once().await.unwrap());
+        assert!(
+            engine
+                .index
+                .get_trashed_item("/Docs/A.txt")
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        // Without a place to record the origin the server is never asked.
+        sqlx::query("DROP TABLE trashed_items")
+            .execute(&pool)
+            .await
+            .unwrap();
+        engine
+            .index
+            .enqueue_op(&delete("/Docs/B.txt", 0))
+            .await
+            .unwrap();
+        engine.run_once().await.expect_err("recording should fail");
+        assert_eq!(engine.list_failed_ops().await.unwrap().len(), 1);
+    }
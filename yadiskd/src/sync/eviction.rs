@@ -0,0 +1,315 @@
+use std::path::Path;
+use std::time::Duration;
+
+use super::delete_guard::is_within;
+
+/// Limits the cache of unpinned files is kept within. Each limit that is
+/// set applies on top of the others.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EvictionPolicy {
+    /// Total size of evictable cached files; `0` turns the limit off.
+    pub max_bytes: u64,
+    /// Files not opened for this long are evicted whatever the cache size.
+    pub max_idle: Option<Duration>,
+    /// Free space to keep on the cache filesystem; `0` turns the target off.
+    pub min_free_bytes: u64,
+    /// Size limits for the cached files below single folders.
+    pub folder_quotas: Vec<(String, u64)>,
+}
+
+/// A cached file that may be evicted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvictionCandidate {
+    pub path: String,
+    pub size: u64,
+    /// Unix time the file was last opened, or written when it never was.
+    pub last_used: i64,
+}
+
+impl EvictionPolicy {
+    pub fn new(max_bytes: u64) -> Self {
+        Self {
+            max_bytes,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_max_idle(mut self, max_idle: Duration) -> Self {
+        self.max_idle = Some(max_idle);
+        self
+    }
+
+    pub fn with_min_free_bytes(mut self, min_free_bytes: u64) -> Self {
+        self.min_free_bytes = min_free_bytes;
+        self
+    }
+
+    pub fn with_folder_quota(mut self, folder: &str, max_bytes: u64) -> Self {
+        let folder = folder.strip_prefix("disk:").unwrap_or(folder);
+        let folder = format!("/{}", folder.trim_matches('/'));
+        self.folder_quotas
+            .retain(|(existing, _)| *existing != folder);
+        self.folder_quotas.push((folder, max_bytes));
+        self
+    }
+
+    /// Parses `/Photos=10G; /Video=500M` into folder quotas.
+    pub fn parse_folder_quotas(mut self, spec: &str) -> Result<Self, String> {
+        for entry in spec
+            .split(';')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (folder, size) = entry
+                .rsplit_once('=')
+                .ok_or_else(|| format!("invalid folder quota: {entry}"))?;
+            let size = parse_size(size).ok_or_else(|| format!("invalid folder quota: {entry}"))?;
+            self = self.with_folder_quota(folder.trim(), size);
+        }
+        Ok(self)
+    }
+
+    /// Whether any limit is set at all.
+    pub fn is_enabled(&self) -> bool {
+        self.max_bytes > 0
+            || self.max_idle.is_some()
+            || self.min_free_bytes > 0
+            || !self.folder_quotas.is_empty()
+    }
+
+    /// Picks the candidates to evict, least recently used first: idle files,
+    /// then the oldest ones until every folder quota, the cache size and the
+    /// free space target hold. `free_bytes` is the free space on the cache
+    /// filesystem, when known.
+    pub fn select<'a>(
+        &self,
+        candidates: &'a [EvictionCandidate],
+        free_bytes: Option<u64>,
+        now: i64,
+    ) -> Vec<&'a EvictionCandidate> {
+        let mut order: Vec<&EvictionCandidate> = candidates.iter().collect();
+        order.sort_by(|a, b| a.last_used.cmp(&b.last_used).then(a.path.cmp(&b.path)));
+        let mut evicted = vec![false; order.len()];
+
+        if let Some(max_idle) = self.max_idle {
+            let cutoff = now.saturating_sub(max_idle.as_secs() as i64);
+            for (index, candidate) in order.iter().enumerate() {
+                evicted[index] = candidate.last_used <= cutoff;
+            }
+        }
+
+        for (folder, quota) in &self.folder_quotas {
+            let within = |candidate: &EvictionCandidate| is_within(&candidate.path, folder);
+            evict_oldest_until(&order, &mut evicted, *quota, within);
+        }
+        if self.max_bytes > 0 {
+            evict_oldest_until(&order, &mut evicted, self.max_bytes, |_| true);
+        }
+
+        if self.min_free_bytes > 0
+            && let Some(free_bytes) = free_bytes
+        {
+            let mut free = free_bytes.saturating_add(
+                order
+                    .iter()
+                    .zip(&evicted)
+                    .filter(|(_, evicted)| **evicted)
+                    .map(|(candidate, _)| candidate.size)
+                    .sum(),
+            );
+            for (index, candidate) in order.iter().enumerate() {
+                if free >= self.min_free_bytes {
+                    break;
+                }
+                if !evicted[index] {
+                    evicted[index] = true;
+                    free = free.saturating_add(candidate.size);
+                }
+            }
+        }
+
+        order
+            .into_iter()
+            .zip(evicted)
+            .filter_map(|(candidate, evicted)| evicted.then_some(candidate))
+            .collect()
+    }
+}
+
+/// Evicts the oldest candidates matching `within` until the ones left add
+/// up to at most `limit` bytes.
+fn evict_oldest_until(
+    order: &[&EvictionCandidate],
+    evicted: &mut [bool],
+    limit: u64,
+    within: impl Fn(&EvictionCandidate) -> bool,
+) {
+    let mut used: u64 = order
+        .iter()
+        .zip(evicted.iter())
+        .filter(|(candidate, evicted)| !**evicted && within(candidate))
+        .map(|(candidate, _)| candidate.size)
+        .sum();
+    for (index, candidate) in order.iter().enumerate() {
+        if used <= limit {
+            break;
+        }
+        if !evicted[index] && within(candidate) {
+            evicted[index] = true;
+            used = used.saturating_sub(candidate.size);
+        }
+    }
+}
+
+/// Parses a byte count with an optional binary `K`, `M`, `G` or `T` suffix.
+pub fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let value = value
+        .strip_suffix(['B', 'b'])
+        .filter(|rest| rest.ends_with(|c: char| c.is_ascii_alphabetic()))
+        .unwrap_or(value);
+    let (digits, shift) = match value.chars().last()?.to_ascii_uppercase() {
+        'K' => (&value[..value.len() - 1], 10),
+        'M' => (&value[..value.len() - 1], 20),
+        'G' => (&value[..value.len() - 1], 30),
+        'T' => (&value[..value.len() - 1], 40),
+        _ => (value, 0),
+    };
+    digits.trim().parse::<u64>().ok()?.checked_mul(1 << shift)
+}
+
+/// Space available to unprivileged users on the filesystem holding `path`.
+pub fn free_space(path: &Path) -> std::io::Result<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| std::io::Error::from(std::io::ErrorKind::InvalidInput))?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `path` is NUL-terminated and `stat` is only read after
+    // statvfs reports success.
+    if unsafe { libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let stat = unsafe { stat.assume_init() };
+    // The field types differ between targets.
+    #[allow(clippy::unnecessary_cast)]
+    let (blocks, block_size) = (stat.f_bavail as u64, stat.f_frsize as u64);
+    Ok(blocks.saturating_mul(block_size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(path: &str, size: u64, last_used: i64) -> EvictionCandidate {
+        EvictionCandidate {
+            path: path.into(),
+            size,
+            last_used,
+        }
+    }
+
+    fn paths(selected: Vec<&EvictionCandidate>) -> Vec<&str> {
+        selected
+            .into_iter()
+            .map(|candidate| candidate.path.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn size_limit_evicts_least_recently_used_first() {
+        let candidates = [
+            candidate("/A.txt", 5, 100),
+            candidate("/B.txt", 5, 300),
+            candidate("/C.txt", 5, 200),
+        ];
+        let selected = EvictionPolicy::new(10).select(&candidates, None, 1_000);
+        assert_eq!(paths(selected), vec!["/A.txt"]);
+        assert!(
+            EvictionPolicy::default()
+                .select(&candidates, Some(0), 1_000)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn idle_files_are_evicted_regardless_of_size() {
+        let day = 24 * 60 * 60;
+        let candidates = [
+            candidate("/Old.txt", 1, 0),
+            candidate("/Recent.txt", 1, 29 * day),
+        ];
+        let policy = EvictionPolicy::default().with_max_idle(Duration::from_secs(7 * day as u64));
+        assert_eq!(
+            paths(policy.select(&candidates, None, 30 * day)),
+            vec!["/Old.txt"]
+        );
+    }
+
+    #[test]
+    fn free_space_target_counts_files_already_evicted() {
+        let candidates = [
+            candidate("/A.bin", 40, 100),
+            candidate("/B.bin", 40, 200),
+            candidate("/C.bin", 40, 300),
+        ];
+        let policy = EvictionPolicy::new(80).with_min_free_bytes(100);
+        assert_eq!(
+            paths(policy.select(&candidates, Some(30), 1_000)),
+            vec!["/A.bin", "/B.bin"]
+        );
+        assert_eq!(
+            paths(policy.select(&candidates, Some(100), 1_000)),
+            vec!["/A.bin"]
+        );
+        assert_eq!(policy.select(&candidates, None, 1_000).len(), 1);
+    }
+
+    #[test]
+    fn folder_quota_only_evicts_inside_the_folder() {
+        let candidates = [
+            candidate("/Docs/Old.txt", 10, 100),
+            candidate("disk:/Photos/A.jpg", 10, 200),
+            candidate("/Photos/B.jpg", 10, 300),
+        ];
+        let policy = EvictionPolicy::default()
+            .parse_folder_quotas("disk:/Photos/=15")
+            .unwrap();
+        assert_eq!(
+            paths(policy.select(&candidates, None, 1_000)),
+            vec!["disk:/Photos/A.jpg"]
+        );
+    }
+
+    #[test]
+    fn parses_sizes_and_quota_lists() {
+        assert_eq!(parse_size("512"), Some(512));
+        assert_eq!(parse_size("20G"), Some(20 << 30));
+        assert_eq!(parse_size(" 500 MB "), Some(500 << 20));
+        assert_eq!(parse_size("1.5G"), None);
+        assert_eq!(parse_size(""), None);
+
+        let policy = EvictionPolicy::default()
+            .parse_folder_quotas("/Photos=10G; /Video=1T;")
+            .unwrap();
+        assert_eq!(
+            policy.folder_quotas,
+            vec![
+                ("/Photos".to_string(), 10 << 30),
+                ("/Video".to_string(), 1 << 40)
+            ]
+        );
+        assert!(
+            EvictionPolicy::default()
+                .parse_folder_quotas("/Photos")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn reports_free_space_of_existing_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(free_space(dir.path()).is_ok());
+        assert!(free_space(&dir.path().join("missing")).is_err());
+    }
+}
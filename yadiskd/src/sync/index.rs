@@ -5,7 +5,7 @@ use std::{fs, path::PathBuf};
 use sqlx::{Row, SqlitePool, migrate::Migrator, sqlite::SqliteConnectOptions};
 use thiserror::Error;
 
-use super::queue::{Operation, OperationKind};
+use super::queue::{OpFilter, Operation, OperationKind};
 
 static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
 
@@ -25,6 +25,8 @@ pub enum IndexError {
     InvalidState(String),
     #[error("invalid operation kind: {0}")]
     InvalidOperationKind(String),
+    #[error("invalid delete origin: {0}")]
+    InvalidDeleteOrigin(String),
     #[error("item not found after upsert")]
     MissingItem,
 }
@@ -114,27 +116,6 @@ impl FileState {
     }
 }
 
-fn operation_kind_as_str(kind: &OperationKind) -> &'static str {
-    match kind {
-        OperationKind::Upload => "upload",
-        OperationKind::Download => "download",
-        OperationKind::Delete => "delete",
-        OperationKind::Move => "move",
-        OperationKind::Mkdir => "mkdir",
-    }
-}
-
-fn parse_operation_kind(value: &str) -> Result<OperationKind, IndexError> {
-    match value {
-        "upload" => Ok(OperationKind::Upload),
-        "download" => Ok(OperationKind::Download),
-        "delete" => Ok(OperationKind::Delete),
-        "move" => Ok(OperationKind::Move),
-        "mkdir" => Ok(OperationKind::Mkdir),
-        other => Err(IndexError::InvalidOperationKind(other.to_string())),
-    }
-}
-
 fn escape_like(segment: &str) -> String {
     segment
         .replace('\\', "\\\\")
@@ -203,6 +184,158 @@ pub struct ConflictRecord {
     pub renamed_local: String,
     pub created: i64,
     pub reason: String,
+    pub status: String,
+}
+
+/// A pending row of `ops_queue`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedOpRecord {
+    pub id: i64,
+    pub kind: OperationKind,
+    pub path: String,
+    pub payload: Option<String>,
+    pub attempt: u32,
+    pub retry_at: Option<i64>,
+    pub priority: i32,
+    /// Older queued operation this one waits for, on an overlapping path.
+    pub depends_on: Option<(OperationKind, String)>,
+    /// Claimed by a worker: the operation has moved to `ops_journal` and
+    /// keeps its id there until it finishes.
+    pub running: bool,
+}
+
+impl QueuedOpRecord {
+    pub fn into_operation(self) -> Operation {
+        Operation {
+            kind: self.kind,
+            path: self.path,
+            payload: self.payload,
+            attempt: self.attempt,
+            retry_at: self.retry_at,
+            priority: self.priority,
+        }
+    }
+}
+
+/// How many ready operations `dequeue_op_where` looks at per call.
+const DEQUEUE_CANDIDATES: i64 = 64;
+
+/// Cheap digest of `ops_queue`, compared between polls to notice changes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueSummary {
+    pub pending: u64,
+    pub max_id: i64,
+    pub priority_sum: i64,
+    pub attempt_sum: i64,
+}
+
+/// An operation dropped from the queue after failing for good, kept so it can
+/// be audited and put back once the cause is fixed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailedOpRecord {
+    pub id: i64,
+    pub kind: OperationKind,
+    pub path: String,
+    pub payload: Option<String>,
+    pub priority: i32,
+    /// Attempts across every time the operation was dead-lettered.
+    pub attempts: u32,
+    pub last_error: String,
+    pub first_failed_at: i64,
+    pub last_failed_at: i64,
+}
+
+/// A path deleted locally and moved to the Yandex trash, kept so it can be
+/// found in the trash and restored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashedItemRecord {
+    pub id: i64,
+    pub path: String,
+    /// Unknown when the item had already left the index before the delete ran.
+    pub item_type: Option<ItemType>,
+    pub resource_id: Option<String>,
+    pub deleted_at: i64,
+}
+
+/// Which side a held-back deletion came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeleteOrigin {
+    /// Removed on this computer; the cloud copy is still there.
+    Local,
+    /// Gone from the cloud; the local copies are still there.
+    Cloud,
+}
+
+impl DeleteOrigin {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeleteOrigin::Local => "local",
+            DeleteOrigin::Cloud => "cloud",
+        }
+    }
+
+    fn parse(value: &str) -> Result<Self, IndexError> {
+        match value {
+            "local" => Ok(DeleteOrigin::Local),
+            "cloud" => Ok(DeleteOrigin::Cloud),
+            other => Err(IndexError::InvalidDeleteOrigin(other.to_string())),
+        }
+    }
+}
+
+/// A deletion the mass-delete guard held back until the user confirms or
+/// rejects it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingDeleteRecord {
+    pub id: i64,
+    pub path: String,
+    pub origin: DeleteOrigin,
+    /// Files at or below `path` when it was held.
+    pub files: i64,
+    pub held_at: i64,
+}
+
+/// A stored auto-pin rule; see `pin_rules::PinRule`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinRuleRecord {
+    pub id: i64,
+    pub pattern: String,
+    pub max_size: Option<i64>,
+    pub max_age_secs: Option<i64>,
+    pub created_at: i64,
+}
+
+/// A file the pin rules are evaluated against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinCandidate {
+    pub item_id: i64,
+    pub path: String,
+    pub size: Option<i64>,
+    pub modified: Option<i64>,
+    /// `None` when the file has no state row yet.
+    pub state: Option<FileState>,
+    pub pinned: bool,
+    /// Pinned by a rule rather than by the user.
+    pub auto_pinned: bool,
+}
+
+/// Identity of a materialized file on the local filesystem, used to pair a
+/// disappearing path with the path it was renamed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LocalFileIdentity {
+    pub device: u64,
+    pub inode: u64,
+    pub size: u64,
+    pub modified: i64,
+}
+
+/// Size and modification time of a sync-folder copy as the daemon last wrote
+/// or observed it. A copy that still matches holds no edits the daemon has
+/// not seen, whatever the cloud holds meanwhile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LocalBaseline {
+    pub size: u64,
+    pub modified: i64,
 }
 
 #[derive(Clone)]
@@ -211,6 +344,7 @@ pub struct IndexStore {
 }
 
 include!("index_store_impl.rs");
+include!("index_local_impl.rs");
 
 fn default_db_path() -> Result<PathBuf, IndexError> {
     let mut path = dirs::data_dir().ok_or(IndexError::MissingDataDir)?;
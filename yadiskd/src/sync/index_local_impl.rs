@@ -0,0 +1,353 @@
+impl IndexStore {
+    pub async fn set_local_identity(
+        &self,
+        item_id: i64,
+        identity: &LocalFileIdentity,
+    ) -> Result<(), IndexError> {
+        sqlx::query(
+            "INSERT INTO local_files (item_id, device, inode, size, modified)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(item_id) DO UPDATE SET
+                device = excluded.device,
+                inode = excluded.inode,
+                size = excluded.size,
+                modified = excluded.modified",
+        )
+        .bind(item_id)
+        .bind(identity.device as i64)
+        .bind(identity.inode as i64)
+        .bind(identity.size as i64)
+        .bind(identity.modified)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_local_identity_by_path(
+        &self,
+        path: &str,
+    ) -> Result<Option<LocalFileIdentity>, IndexError> {
+        let row = sqlx::query(
+            "SELECT local_files.device, local_files.inode, local_files.size, local_files.modified
+             FROM local_files
+             JOIN items ON items.id = local_files.item_id
+             WHERE items.path = ?1",
+        )
+        .bind(path)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(|row| local_identity_from_row(&row)).transpose()
+    }
+
+    pub async fn find_path_by_local_identity(
+        &self,
+        identity: &LocalFileIdentity,
+    ) -> Result<Option<String>, IndexError> {
+        let row = sqlx::query(
+            "SELECT items.path
+             FROM local_files
+             JOIN items ON items.id = local_files.item_id
+             WHERE local_files.device = ?1
+               AND local_files.inode = ?2
+               AND local_files.size = ?3
+               AND local_files.modified = ?4
+             ORDER BY items.path ASC
+             LIMIT 1",
+        )
+        .bind(identity.device as i64)
+        .bind(identity.inode as i64)
+        .bind(identity.size as i64)
+        .bind(identity.modified)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(|row| row.try_get("path")).transpose()?)
+    }
+
+    /// Another file whose content, as of its last sync, is `hash`/`size` and
+    /// which is not being changed, so the server already holds that content.
+    pub async fn find_synced_file_by_content(
+        &self,
+        hash: &str,
+        size: u64,
+        exclude_path: &str,
+    ) -> Result<Option<String>, IndexError> {
+        let row = sqlx::query(
+            "SELECT items.path
+             FROM items
+             LEFT JOIN states ON states.item_id = items.id
+             WHERE items.item_type = 'file'
+               AND items.size = ?1
+               AND items.hash = ?2
+               AND items.last_synced_hash = ?2
+               AND items.path != ?3
+               AND (states.state IS NULL OR states.state IN ('cloud_only', 'cached'))
+             ORDER BY items.path ASC
+             LIMIT 1",
+        )
+        .bind(size as i64)
+        // Hashes are stored lowercase, which lets the lookup use the index.
+        .bind(hash.to_ascii_lowercase())
+        .bind(exclude_path)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(|row| row.try_get("path")).transpose()?)
+    }
+
+    pub async fn list_local_identities_by_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<(String, LocalFileIdentity)>, IndexError> {
+        let [prefix_a, prefix_b] = prefix_variants(prefix);
+        let pattern_a = like_pattern_for_prefix(&prefix_a);
+        let pattern_b = like_pattern_for_prefix(&prefix_b);
+        let rows = sqlx::query(
+            "SELECT items.path, local_files.device, local_files.inode, local_files.size, local_files.modified
+             FROM local_files
+             JOIN items ON items.id = local_files.item_id
+             WHERE items.path = ?1 OR items.path LIKE ?2 ESCAPE '\\'
+                OR items.path = ?3 OR items.path LIKE ?4 ESCAPE '\\'
+             ORDER BY items.path ASC",
+        )
+        .bind(prefix_a)
+        .bind(pattern_a)
+        .bind(prefix_b)
+        .bind(pattern_b)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut out = Vec::with_capacity(rows.len());
+        for row in rows {
+            out.push((row.try_get("path")?, local_identity_from_row(&row)?));
+        }
+        Ok(out)
+    }
+
+    pub async fn set_local_baseline(
+        &self,
+        item_id: i64,
+        baseline: LocalBaseline,
+    ) -> Result<(), IndexError> {
+        sqlx::query(
+            "INSERT INTO local_baselines (item_id, size, modified)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(item_id) DO UPDATE SET
+                size = excluded.size,
+                modified = excluded.modified",
+        )
+        .bind(item_id)
+        .bind(baseline.size as i64)
+        .bind(baseline.modified)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list_local_baselines_by_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<(String, LocalBaseline)>, IndexError> {
+        let [prefix_a, prefix_b] = prefix_variants(prefix);
+        let pattern_a = like_pattern_for_prefix(&prefix_a);
+        let pattern_b = like_pattern_for_prefix(&prefix_b);
+        let rows = sqlx::query(
+            "SELECT items.path, local_baselines.size, local_baselines.modified
+             FROM local_baselines
+             JOIN items ON items.id = local_baselines.item_id
+             WHERE items.path = ?1 OR items.path LIKE ?2 ESCAPE '\\'
+                OR items.path = ?3 OR items.path LIKE ?4 ESCAPE '\\'
+             ORDER BY items.path ASC",
+        )
+        .bind(prefix_a)
+        .bind(pattern_a)
+        .bind(prefix_b)
+        .bind(pattern_b)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut out = Vec::with_capacity(rows.len());
+        for row in rows {
+            let size: i64 = row.try_get("size")?;
+            out.push((
+                row.try_get("path")?,
+                LocalBaseline {
+                    size: size as u64,
+                    modified: row.try_get("modified")?,
+                },
+            ));
+        }
+        Ok(out)
+    }
+
+    /// Records the name an item has on disk; `None` means it is the same
+    /// as the remote name.
+    pub async fn set_local_name(
+        &self,
+        item_id: i64,
+        local_name: Option<&str>,
+    ) -> Result<(), IndexError> {
+        match local_name {
+            Some(local_name) => {
+                sqlx::query(
+                    "INSERT INTO local_names (item_id, local_name)
+                     VALUES (?1, ?2)
+                     ON CONFLICT(item_id) DO UPDATE SET local_name = excluded.local_name",
+                )
+                .bind(item_id)
+                .bind(local_name)
+                .execute(&self.pool)
+                .await?;
+            }
+            None => {
+                sqlx::query("DELETE FROM local_names WHERE item_id = ?1")
+                    .bind(item_id)
+                    .execute(&self.pool)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remote paths of items stored under another name on disk, with that
+    /// name.
+    pub async fn list_local_names(&self) -> Result<Vec<(String, String)>, IndexError> {
+        let rows = sqlx::query(
+            "SELECT items.path, local_names.local_name
+             FROM local_names
+             JOIN items ON items.id = local_names.item_id
+             ORDER BY items.path ASC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut out = Vec::with_capacity(rows.len());
+        for row in rows {
+            out.push((row.try_get("path")?, row.try_get("local_name")?));
+        }
+        Ok(out)
+    }
+
+    /// Records that the item is a stored symlink pointing at `target`, or
+    /// forgets it with `None`.
+    pub async fn set_link_target(
+        &self,
+        item_id: i64,
+        target: Option<&str>,
+    ) -> Result<(), IndexError> {
+        match target {
+            Some(target) => {
+                sqlx::query(
+                    "INSERT INTO symlinks (item_id, target)
+                     VALUES (?1, ?2)
+                     ON CONFLICT(item_id) DO UPDATE SET target = excluded.target",
+                )
+                .bind(item_id)
+                .bind(target)
+                .execute(&self.pool)
+                .await?;
+            }
+            None => {
+                sqlx::query("DELETE FROM symlinks WHERE item_id = ?1")
+                    .bind(item_id)
+                    .execute(&self.pool)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn get_link_target(&self, item_id: i64) -> Result<Option<String>, IndexError> {
+        let row = sqlx::query("SELECT target FROM symlinks WHERE item_id = ?1")
+            .bind(item_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|row| row.try_get("target")).transpose()?)
+    }
+
+    /// Remote paths of items stored as symlinks, with their targets.
+    pub async fn list_link_targets(&self) -> Result<Vec<(String, String)>, IndexError> {
+        let rows = sqlx::query(
+            "SELECT items.path, symlinks.target
+             FROM symlinks
+             JOIN items ON items.id = symlinks.item_id
+             ORDER BY items.path ASC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut out = Vec::with_capacity(rows.len());
+        for row in rows {
+            out.push((row.try_get("path")?, row.try_get("target")?));
+        }
+        Ok(out)
+    }
+
+    /// Stores whether everything below the folder, including files added
+    /// later, is kept pinned; `None` lets the folder follow its parents.
+    pub async fn set_folder_pin(
+        &self,
+        item_id: i64,
+        pinned: Option<bool>,
+    ) -> Result<(), IndexError> {
+        match pinned {
+            Some(pinned) => {
+                sqlx::query(
+                    "INSERT INTO folder_pins (item_id, pinned)
+                     VALUES (?1, ?2)
+                     ON CONFLICT(item_id) DO UPDATE SET pinned = excluded.pinned",
+                )
+                .bind(item_id)
+                .bind(if pinned { 1 } else { 0 })
+                .execute(&self.pool)
+                .await?;
+            }
+            None => {
+                sqlx::query("DELETE FROM folder_pins WHERE item_id = ?1")
+                    .bind(item_id)
+                    .execute(&self.pool)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn get_folder_pin(&self, item_id: i64) -> Result<Option<bool>, IndexError> {
+        let row = sqlx::query("SELECT pinned FROM folder_pins WHERE item_id = ?1")
+            .bind(item_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row
+            .map(|row| row.try_get::<i64, _>("pinned"))
+            .transpose()?
+            .map(|pinned| pinned != 0))
+    }
+
+    /// Remote paths of folders with a stored pin setting.
+    pub async fn list_folder_pins(&self) -> Result<Vec<(String, bool)>, IndexError> {
+        let rows = sqlx::query(
+            "SELECT items.path, folder_pins.pinned
+             FROM folder_pins
+             JOIN items ON items.id = folder_pins.item_id
+             ORDER BY items.path ASC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut out = Vec::with_capacity(rows.len());
+        for row in rows {
+            out.push((row.try_get("path")?, row.try_get::<i64, _>("pinned")? != 0));
+        }
+        Ok(out)
+    }
+}
+
+fn local_identity_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<LocalFileIdentity, IndexError> {
+    let device: i64 = row.try_get("device")?;
+    let inode: i64 = row.try_get("inode")?;
+    let size: i64 = row.try_get("size")?;
+    Ok(LocalFileIdentity {
+        device: device as u64,
+        inode: inode as u64,
+        size: size as u64,
+        modified: row.try_get("modified")?,
+    })
+}
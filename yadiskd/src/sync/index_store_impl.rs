@@ -35,7 +35,7 @@ impl IndexStore {
 
     pub async fn upsert_item(&self, item: &ItemInput) -> Result<ItemRecord, IndexError> {
         sqlx::query(
-            "\n            INSERT INTO items (\n                path,\n                parent_path,\n                name,\n                item_type,\n                size,\n                modified,\n                hash,\n                resource_id,\n                last_synced_hash,\n                last_synced_modified\n            )\n            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)\n            ON CONFLICT(path) DO UPDATE SET\n                parent_path = excluded.parent_path,\n                name = excluded.name,\n                item_type = excluded.item_type,\n                size = excluded.size,\n                modified = excluded.modified,\n                hash = excluded.hash,\n                resource_id = excluded.resource_id,\n                last_synced_hash = excluded.last_synced_hash,\n                last_synced_modified = excluded.last_synced_modified;\n            ",
+            "\n            INSERT INTO items (\n                path,\n                parent_path,\n                name,\n                item_type,\n                size,\n                modified,\n                hash,\n                resource_id,\n                last_synced_hash,\n                last_synced_modified\n            )\n            VALUES (?1, ?2, ?3, ?4, ?5, ?6, lower(?7), ?8, lower(?9), ?10)\n            ON CONFLICT(path) DO UPDATE SET\n                parent_path = excluded.parent_path,\n                name = excluded.name,\n                item_type = excluded.item_type,\n                size = excluded.size,\n                modified = excluded.modified,\n                hash = excluded.hash,\n                resource_id = excluded.resource_id,\n                last_synced_hash = excluded.last_synced_hash,\n                last_synced_modified = excluded.last_synced_modified;\n            ",
         )
         .bind(&item.path)
         .bind(&item.parent_path)
@@ -120,6 +120,38 @@ impl IndexStore {
         Ok(out)
     }
 
+    /// Direct children of the folder at `parent`, by name.
+    pub async fn list_children(&self, parent: &str) -> Result<Vec<ItemRecord>, IndexError> {
+        let rows = sqlx::query(
+            "SELECT id, path, parent_path, name, item_type, size, modified, hash, resource_id, last_synced_hash, last_synced_modified
+             FROM items
+             WHERE parent_path = ?1
+             ORDER BY name ASC",
+        )
+        .bind(parent)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut out = Vec::with_capacity(rows.len());
+        for row in rows {
+            let item_type: String = row.try_get("item_type")?;
+            out.push(ItemRecord {
+                id: row.try_get("id")?,
+                path: row.try_get("path")?,
+                parent_path: row.try_get("parent_path")?,
+                name: row.try_get("name")?,
+                item_type: ItemType::parse(&item_type)?,
+                size: row.try_get("size")?,
+                modified: row.try_get("modified")?,
+                hash: row.try_get("hash")?,
+                resource_id: row.try_get("resource_id")?,
+                last_synced_hash: row.try_get("last_synced_hash")?,
+                last_synced_modified: row.try_get("last_synced_modified")?,
+            });
+        }
+        Ok(out)
+    }
+
     pub async fn delete_item_by_path(&self, path: &str) -> Result<(), IndexError> {
         sqlx::query("DELETE FROM items WHERE path = ?1")
             .bind(path)
@@ -278,6 +310,8 @@ impl IndexStore {
         Ok(())
     }
 
+    /// Pinned files below `prefix` that are not downloaded yet; folders are
+    /// covered through the files in them.
     pub async fn list_pinned_cloud_only_paths_by_prefix(
         &self,
         prefix: &str,
@@ -290,6 +324,7 @@ impl IndexStore {
              FROM states s
              JOIN items i ON i.id = s.item_id
              WHERE (i.path = ?1 OR i.path LIKE ?2 ESCAPE '\\' OR i.path = ?3 OR i.path LIKE ?4 ESCAPE '\\')
+                AND i.item_type = 'file'
                 AND s.pinned = 1
                 AND s.state = 'cloud_only'
              ORDER BY i.path ASC",
@@ -347,23 +382,81 @@ impl IndexStore {
         }
     }
 
+    /// Queues `op`, merging it into a queued operation of the same kind and
+    /// path; an operation without a payload keeps the queued one, so a plain
+    /// re-queue does not drop flags such as an upload's `overwrite`.
+    ///
+    /// The operation depends on the latest older queued operation on the
+    /// same path, an ancestor or a descendant, counting the source of moves
+    /// and copies, and is not handed out until that one is done.
     pub async fn enqueue_op(&self, op: &Operation) -> Result<i64, IndexError> {
+        let touched = op.touched_paths();
+        let from = touched.get(1).map(String::as_str);
+        // A merged operation keeps its place, so only older ones can block
+        // it; this also keeps two operations from waiting on each other.
+        let existing: Option<i64> =
+            sqlx::query_scalar("SELECT id FROM ops_queue WHERE kind = ?1 AND path = ?2")
+                .bind(op.kind.as_str())
+                .bind(&op.path)
+                .fetch_optional(&self.pool)
+                .await?;
+        let queued_from = "CASE WHEN kind IN ('move', 'copy') AND json_valid(payload)
+                           THEN json_extract(payload, '$.from') END";
+        let overlap = |a: &str, b: &str| {
+            format!(
+                "({a} = {b} OR substr({b}, 1, length({a}) + 1) = {a} || '/'
+                 OR substr({a}, 1, length({b}) + 1) = {b} || '/')"
+            )
+        };
+        let query = format!(
+            "SELECT kind, path FROM ops_queue
+             WHERE id < ?3 AND ({} OR {} OR {} OR {})
+             ORDER BY id DESC
+             LIMIT 1",
+            overlap("path", "?1"),
+            overlap("path", "?2"),
+            overlap(queued_from, "?1"),
+            overlap(queued_from, "?2"),
+        );
+        let depends_on = sqlx::query(&query)
+            .bind(&op.path)
+            .bind(from)
+            .bind(existing.unwrap_or(i64::MAX))
+            .fetch_optional(&self.pool)
+            .await?;
+        let (depends_on_kind, depends_on_path) = match &depends_on {
+            Some(row) => (
+                Some(row.try_get::<String, _>("kind")?),
+                Some(row.try_get::<String, _>("path")?),
+            ),
+            None => (None, None),
+        };
         let result = sqlx::query(
-            "INSERT INTO ops_queue (kind, path, payload, attempt, retry_at, priority) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "INSERT INTO ops_queue (
+                kind, path, payload, attempt, retry_at, priority, depends_on_kind, depends_on_path
+             )
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
              ON CONFLICT(kind, path) DO UPDATE SET
-                payload = excluded.payload,
+                payload = COALESCE(excluded.payload, ops_queue.payload),
                 attempt = MIN(ops_queue.attempt, excluded.attempt),
                 retry_at = excluded.retry_at,
-                priority = MAX(ops_queue.priority, excluded.priority)",
+                priority = CASE WHEN ops_queue.priority_set THEN ops_queue.priority
+                                ELSE MAX(ops_queue.priority, excluded.priority) END,
+                depends_on_kind = excluded.depends_on_kind,
+                depends_on_path = excluded.depends_on_path",
         )
-            .bind(operation_kind_as_str(&op.kind))
+            .bind(op.kind.as_str())
             .bind(&op.path)
             .bind(&op.payload)
             .bind(op.attempt)
             .bind(op.retry_at)
             .bind(op.priority)
+            .bind(depends_on_kind)
+            .bind(depends_on_path)
             .execute(&self.pool)
             .await?;
+        // Queued again, so an earlier failure of the same operation is moot.
+        self.delete_failed_ops_for(&op.kind, &op.path).await?;
 
         Ok(result.last_insert_rowid())
     }
@@ -427,37 +520,289 @@ impl IndexStore {
     }
 
     pub async fn dequeue_op(&self) -> Result<Option<Operation>, IndexError> {
+        self.dequeue_op_where(|_| true).await
+    }
+
+    /// Claims the next ready operation accepted by `accept`, moving it from
+    /// the queue to `ops_journal` until `finish_journal` is called.
+    ///
+    /// An operation is held back while the one it depends on is still queued,
+    /// so a folder is created before its files are uploaded and a move runs
+    /// before later edits; `accept` keeps it off paths claimed by running
+    /// operations. Ready operations are read in `ORDER BY priority DESC, id`
+    /// over `idx_ops_queue_priority`.
+    /// The claim is a single `DELETE … RETURNING` in the same transaction as
+    /// the journal insert, so concurrent workers never get the same row and a
+    /// crash never loses it.
+    pub async fn dequeue_op_where(
+        &self,
+        mut accept: impl FnMut(&Operation) -> bool,
+    ) -> Result<Option<Operation>, IndexError> {
+        let query = "SELECT queued.id, queued.kind, queued.path, queued.payload, queued.attempt,
+                    queued.retry_at, queued.priority, queued.depends_on_kind,
+                    queued.depends_on_path
+             FROM ops_queue AS queued
+             WHERE (queued.retry_at IS NULL
+                    OR queued.retry_at <= CAST(strftime('%s','now') AS INTEGER))
+               AND NOT EXISTS (
+                   SELECT 1 FROM ops_queue AS prerequisite
+                   WHERE prerequisite.kind = queued.depends_on_kind
+                     AND prerequisite.path = queued.depends_on_path)
+             ORDER BY queued.priority DESC, queued.id ASC
+             LIMIT ?1";
+        let candidates = sqlx::query(query)
+            .bind(DEQUEUE_CANDIDATES)
+            .fetch_all(&self.pool)
+            .await?;
+        for row in &candidates {
+            let id: i64 = row.try_get("id")?;
+            if !accept(&queued_op_from_row(row)?.into_operation()) {
+                continue;
+            }
+            let mut tx = self.pool.begin().await?;
+            let claimed = sqlx::query(
+                "DELETE FROM ops_queue
+                 WHERE id = ?1
+                 RETURNING id, kind, path, payload, attempt, retry_at, priority, depends_on_kind,
+                           depends_on_path",
+            )
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?;
+            let Some(row) = claimed else {
+                continue;
+            };
+            let op = queued_op_from_row(&row)?.into_operation();
+            // The journal entry keeps the queue id, so a running operation is
+            // listed and cancelled under the id it was queued with.
+            sqlx::query(
+                "INSERT INTO ops_journal (id, kind, path, payload, attempt, priority, started_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, CAST(strftime('%s','now') AS INTEGER))
+                 ON CONFLICT(kind, path) DO UPDATE SET
+                    id = excluded.id,
+                    payload = excluded.payload,
+                    attempt = excluded.attempt,
+                    priority = excluded.priority,
+                    started_at = excluded.started_at",
+            )
+            .bind(id)
+            .bind(op.kind.as_str())
+            .bind(&op.path)
+            .bind(&op.payload)
+            .bind(op.attempt)
+            .bind(op.priority)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+            return Ok(Some(op));
+        }
+        Ok(None)
+    }
+
+    /// Records progress of a claimed operation, so a restart resumes it from
+    /// this payload instead of starting over.
+    pub async fn update_journal_payload(
+        &self,
+        kind: &OperationKind,
+        path: &str,
+        payload: &str,
+    ) -> Result<(), IndexError> {
+        sqlx::query("UPDATE ops_journal SET payload = ?3 WHERE kind = ?1 AND path = ?2")
+            .bind(kind.as_str())
+            .bind(path)
+            .bind(payload)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Returns the claimed operation with its latest recorded progress.
+    pub async fn get_journaled_op(
+        &self,
+        kind: &OperationKind,
+        path: &str,
+    ) -> Result<Option<Operation>, IndexError> {
         let row = sqlx::query(
-            "SELECT id, kind, path, payload, attempt, retry_at, priority
-             FROM ops_queue
-             WHERE retry_at IS NULL OR retry_at <= CAST(strftime('%s','now') AS INTEGER)
-             ORDER BY priority DESC, id ASC
-             LIMIT 1",
+            "SELECT kind, path, payload, attempt, priority
+             FROM ops_journal
+             WHERE kind = ?1 AND path = ?2",
         )
+        .bind(kind.as_str())
+        .bind(path)
         .fetch_optional(&self.pool)
         .await?;
+        row.as_ref().map(journaled_op_from_row).transpose()
+    }
 
-        let Some(row) = row else {
-            return Ok(None);
-        };
-
-        let id: i64 = row.try_get("id")?;
-        let kind: String = row.try_get("kind")?;
-        let operation = Operation {
-            kind: parse_operation_kind(&kind)?,
-            path: row.try_get("path")?,
-            payload: row.try_get("payload")?,
-            attempt: row.try_get("attempt")?,
-            retry_at: row.try_get("retry_at")?,
-            priority: row.try_get("priority")?,
-        };
+    /// Operations that were claimed but never finished, oldest first.
+    pub async fn list_journaled_ops(&self) -> Result<Vec<Operation>, IndexError> {
+        let rows = sqlx::query(
+            "SELECT kind, path, payload, attempt, priority
+             FROM ops_journal
+             ORDER BY id ASC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(journaled_op_from_row).collect()
+    }
 
-        sqlx::query("DELETE FROM ops_queue WHERE id = ?1")
-            .bind(id)
+    pub async fn finish_journal(&self, kind: &OperationKind, path: &str) -> Result<(), IndexError> {
+        sqlx::query("DELETE FROM ops_journal WHERE kind = ?1 AND path = ?2")
+            .bind(kind.as_str())
+            .bind(path)
             .execute(&self.pool)
             .await?;
+        Ok(())
+    }
+
+    /// Paths left in `syncing` with no queued or claimed operation that could
+    /// ever finish them.
+    pub async fn list_stale_syncing_paths(&self) -> Result<Vec<String>, IndexError> {
+        let rows = sqlx::query(
+            "SELECT items.path AS path
+             FROM items
+             JOIN states ON states.item_id = items.id
+             WHERE states.state = 'syncing'
+               AND NOT EXISTS (SELECT 1 FROM ops_queue WHERE ops_queue.path = items.path)
+               AND NOT EXISTS (SELECT 1 FROM ops_journal WHERE ops_journal.path = items.path)
+             ORDER BY items.path",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter()
+            .map(|row| Ok(row.try_get("path")?))
+            .collect()
+    }
+
+    /// Lists queued operations in the order they will run.
+    pub async fn list_ops(&self, filter: &OpFilter) -> Result<Vec<QueuedOpRecord>, IndexError> {
+        let kind = filter.kind.as_ref().map(OperationKind::as_str);
+        let [prefix_a, prefix_b] = filter
+            .path_prefix
+            .as_deref()
+            .map(prefix_variants)
+            .unwrap_or_default();
+        let rows = sqlx::query(
+            "SELECT id, kind, path, payload, attempt, retry_at, priority, depends_on_kind,
+                    depends_on_path
+             FROM ops_queue
+             WHERE (?1 IS NULL OR kind = ?1)
+               AND (?2 = '' OR path = ?2 OR path LIKE ?3 ESCAPE '\\'
+                    OR path = ?4 OR path LIKE ?5 ESCAPE '\\')
+             ORDER BY priority DESC, id ASC",
+        )
+        .bind(kind)
+        .bind(&prefix_a)
+        .bind(like_pattern_for_prefix(&prefix_a))
+        .bind(&prefix_b)
+        .bind(like_pattern_for_prefix(&prefix_b))
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(queued_op_from_row).collect()
+    }
+
+    /// Lists claimed operations that are running now, oldest first.
+    pub async fn list_running_ops(
+        &self,
+        filter: &OpFilter,
+    ) -> Result<Vec<QueuedOpRecord>, IndexError> {
+        let kind = filter.kind.as_ref().map(OperationKind::as_str);
+        let [prefix_a, prefix_b] = filter
+            .path_prefix
+            .as_deref()
+            .map(prefix_variants)
+            .unwrap_or_default();
+        let rows = sqlx::query(
+            "SELECT id, kind, path, payload, attempt, priority
+             FROM ops_journal
+             WHERE (?1 IS NULL OR kind = ?1)
+               AND (?2 = '' OR path = ?2 OR path LIKE ?3 ESCAPE '\\'
+                    OR path = ?4 OR path LIKE ?5 ESCAPE '\\')
+             ORDER BY started_at ASC, id ASC",
+        )
+        .bind(kind)
+        .bind(&prefix_a)
+        .bind(like_pattern_for_prefix(&prefix_a))
+        .bind(&prefix_b)
+        .bind(like_pattern_for_prefix(&prefix_b))
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(running_op_from_row).collect()
+    }
+
+    pub async fn get_running_op(&self, id: i64) -> Result<Option<QueuedOpRecord>, IndexError> {
+        let row = sqlx::query(
+            "SELECT id, kind, path, payload, attempt, priority FROM ops_journal WHERE id = ?1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.as_ref().map(running_op_from_row).transpose()
+    }
+
+    /// Removes and returns the queued operations waiting for `kind` on
+    /// `path`.
+    pub async fn take_dependent_ops(
+        &self,
+        kind: &OperationKind,
+        path: &str,
+    ) -> Result<Vec<QueuedOpRecord>, IndexError> {
+        let rows = sqlx::query(
+            "DELETE FROM ops_queue
+             WHERE depends_on_kind = ?1 AND depends_on_path = ?2
+             RETURNING id, kind, path, payload, attempt, retry_at, priority, depends_on_kind,
+                       depends_on_path",
+        )
+        .bind(kind.as_str())
+        .bind(path)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(queued_op_from_row).collect()
+    }
+
+    /// Removes a queued operation and returns it, or `None` if it is gone.
+    pub async fn cancel_op(&self, id: i64) -> Result<Option<QueuedOpRecord>, IndexError> {
+        let row = sqlx::query(
+            "DELETE FROM ops_queue
+             WHERE id = ?1
+             RETURNING id, kind, path, payload, attempt, retry_at, priority, depends_on_kind,
+                           depends_on_path",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.as_ref().map(queued_op_from_row).transpose()
+    }
 
-        Ok(Some(operation))
+    /// Returns `false` when no queued operation has this id. The priority
+    /// sticks when the operation is queued again before it runs.
+    pub async fn set_op_priority(&self, id: i64, priority: i32) -> Result<bool, IndexError> {
+        let result =
+            sqlx::query("UPDATE ops_queue SET priority = ?2, priority_set = 1 WHERE id = ?1")
+                .bind(id)
+                .bind(priority)
+                .execute(&self.pool)
+                .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn queue_summary(&self) -> Result<QueueSummary, IndexError> {
+        let row = sqlx::query(
+            "SELECT COUNT(*) AS pending,
+                    COALESCE(MAX(id), 0) AS max_id,
+                    COALESCE(SUM(priority), 0) AS priority_sum,
+                    COALESCE(SUM(attempt), 0) AS attempt_sum
+             FROM ops_queue",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        let pending: i64 = row.try_get("pending")?;
+        Ok(QueueSummary {
+            pending: pending as u64,
+            max_id: row.try_get("max_id")?,
+            priority_sum: row.try_get("priority_sum")?,
+            attempt_sum: row.try_get("attempt_sum")?,
+        })
     }
 
     pub async fn has_ready_op(&self) -> Result<bool, IndexError> {
@@ -492,23 +837,477 @@ impl IndexStore {
         Ok(result.last_insert_rowid())
     }
 
+    /// Lists conflicts still waiting for the user; resolved and dismissed
+    /// ones are kept only for history.
     pub async fn list_conflicts(&self) -> Result<Vec<ConflictRecord>, IndexError> {
         let rows = sqlx::query(
-            "SELECT id, path, renamed_local, created, reason FROM conflicts ORDER BY id ASC",
+            "SELECT id, path, renamed_local, created, reason, status
+             FROM conflicts
+             WHERE status = 'open'
+             ORDER BY id ASC",
         )
         .fetch_all(&self.pool)
         .await?;
 
         let mut out = Vec::with_capacity(rows.len());
         for row in rows {
-            out.push(ConflictRecord {
-                id: row.try_get("id")?,
-                path: row.try_get("path")?,
-                renamed_local: row.try_get("renamed_local")?,
-                created: row.try_get("created")?,
-                reason: row.try_get("reason")?,
-            });
+            out.push(conflict_from_row(&row)?);
         }
         Ok(out)
     }
+
+    pub async fn get_conflict(&self, id: i64) -> Result<Option<ConflictRecord>, IndexError> {
+        let row = sqlx::query(
+            "SELECT id, path, renamed_local, created, reason, status FROM conflicts WHERE id = ?1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(|row| conflict_from_row(&row)).transpose()
+    }
+
+    pub async fn open_conflict_for_path(
+        &self,
+        path: &str,
+    ) -> Result<Option<ConflictRecord>, IndexError> {
+        let row = sqlx::query(
+            "SELECT id, path, renamed_local, created, reason, status
+             FROM conflicts
+             WHERE path = ?1 AND status = 'open'
+             ORDER BY id DESC
+             LIMIT 1",
+        )
+        .bind(path)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(|row| conflict_from_row(&row)).transpose()
+    }
+
+    /// Moves an open conflict to `status`; returns `false` when it was not open.
+    pub async fn close_conflict(
+        &self,
+        id: i64,
+        status: &str,
+        resolved_at: i64,
+    ) -> Result<bool, IndexError> {
+        let result = sqlx::query(
+            "UPDATE conflicts SET status = ?2, resolved_at = ?3 WHERE id = ?1 AND status = 'open'",
+        )
+        .bind(id)
+        .bind(status)
+        .bind(resolved_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Stores an operation that will not be retried automatically. A failure
+    /// of the same kind and path updates the existing entry.
+    pub async fn record_failed_op(
+        &self,
+        op: &Operation,
+        attempts: u32,
+        last_error: &str,
+        failed_at: i64,
+    ) -> Result<i64, IndexError> {
+        let row = sqlx::query(
+            "INSERT INTO failed_ops (
+                kind, path, payload, priority, attempts, last_error, first_failed_at,
+                last_failed_at
+             )
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)
+             ON CONFLICT(kind, path) DO UPDATE SET
+                payload = excluded.payload,
+                priority = excluded.priority,
+                attempts = failed_ops.attempts + excluded.attempts,
+                last_error = excluded.last_error,
+                last_failed_at = excluded.last_failed_at
+             RETURNING id",
+        )
+        .bind(op.kind.as_str())
+        .bind(&op.path)
+        .bind(&op.payload)
+        .bind(op.priority)
+        .bind(attempts)
+        .bind(last_error)
+        .bind(failed_at)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row.try_get("id")?)
+    }
+
+    /// Lists dead-lettered operations, most recently failed first.
+    pub async fn list_failed_ops(&self) -> Result<Vec<FailedOpRecord>, IndexError> {
+        let rows = sqlx::query(
+            "SELECT id, kind, path, payload, priority, attempts, last_error, first_failed_at,
+                    last_failed_at
+             FROM failed_ops
+             ORDER BY last_failed_at DESC, id DESC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(failed_op_from_row).collect()
+    }
+
+    pub async fn get_failed_op(&self, id: i64) -> Result<Option<FailedOpRecord>, IndexError> {
+        let row = sqlx::query(
+            "SELECT id, kind, path, payload, priority, attempts, last_error, first_failed_at,
+                    last_failed_at
+             FROM failed_ops
+             WHERE id = ?1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.as_ref().map(failed_op_from_row).transpose()
+    }
+
+    /// Returns `false` when there was no such entry.
+    pub async fn delete_failed_op(&self, id: i64) -> Result<bool, IndexError> {
+        let result = sqlx::query("DELETE FROM failed_ops WHERE id = ?1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Drops the failure recorded for `kind` on `path`, if any.
+    pub async fn delete_failed_ops_for(
+        &self,
+        kind: &OperationKind,
+        path: &str,
+    ) -> Result<(), IndexError> {
+        sqlx::query("DELETE FROM failed_ops WHERE kind = ?1 AND path = ?2")
+            .bind(kind.as_str())
+            .bind(path)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn record_trashed_item(
+        &self,
+        path: &str,
+        item_type: Option<&ItemType>,
+        resource_id: Option<&str>,
+        deleted_at: i64,
+    ) -> Result<i64, IndexError> {
+        let row = sqlx::query(
+            "INSERT INTO trashed_items (path, item_type, resource_id, deleted_at)
+             VALUES (?1, ?2, ?3, ?4)
+             RETURNING id",
+        )
+        .bind(path)
+        .bind(item_type.map(ItemType::as_str))
+        .bind(resource_id)
+        .bind(deleted_at)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row.try_get("id")?)
+    }
+
+    /// The latest trash record for `path`, in either the `/` or `disk:/` form.
+    pub async fn get_trashed_item(
+        &self,
+        path: &str,
+    ) -> Result<Option<TrashedItemRecord>, IndexError> {
+        let [path_a, path_b] = prefix_variants(path);
+        let row = sqlx::query(
+            "SELECT id, path, item_type, resource_id, deleted_at
+             FROM trashed_items
+             WHERE path IN (?1, ?2)
+             ORDER BY deleted_at DESC, id DESC
+             LIMIT 1",
+        )
+        .bind(path_a)
+        .bind(path_b)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.as_ref().map(trashed_item_from_row).transpose()
+    }
+
+    pub async fn list_trashed_items(&self) -> Result<Vec<TrashedItemRecord>, IndexError> {
+        let rows = sqlx::query(
+            "SELECT id, path, item_type, resource_id, deleted_at
+             FROM trashed_items
+             ORDER BY deleted_at DESC, id DESC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(trashed_item_from_row).collect()
+    }
+
+    pub async fn remove_trashed_item(&self, id: i64) -> Result<(), IndexError> {
+        sqlx::query("DELETE FROM trashed_items WHERE id = ?1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn count_files(&self) -> Result<u64, IndexError> {
+        let row = sqlx::query("SELECT COUNT(*) AS files FROM items WHERE item_type = 'file'")
+            .fetch_one(&self.pool)
+            .await?;
+        let files: i64 = row.try_get("files")?;
+        Ok(files as u64)
+    }
+
+    /// Returns `false` when `path` is already held.
+    pub async fn hold_delete(
+        &self,
+        path: &str,
+        origin: DeleteOrigin,
+        files: i64,
+        held_at: i64,
+    ) -> Result<bool, IndexError> {
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO pending_deletes (path, origin, files, held_at)
+             VALUES (?1, ?2, ?3, ?4)",
+        )
+        .bind(path)
+        .bind(origin.as_str())
+        .bind(files)
+        .bind(held_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn list_pending_deletes(&self) -> Result<Vec<PendingDeleteRecord>, IndexError> {
+        let rows = sqlx::query(
+            "SELECT id, path, origin, files, held_at
+             FROM pending_deletes
+             ORDER BY path ASC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(pending_delete_from_row).collect()
+    }
+
+    pub async fn remove_pending_delete(&self, id: i64) -> Result<(), IndexError> {
+        sqlx::query("DELETE FROM pending_deletes WHERE id = ?1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn add_pin_rule(
+        &self,
+        pattern: &str,
+        max_size: Option<i64>,
+        max_age_secs: Option<i64>,
+        created_at: i64,
+    ) -> Result<i64, IndexError> {
+        let result = sqlx::query(
+            "INSERT INTO pin_rules (pattern, max_size, max_age_secs, created_at)
+             VALUES (?1, ?2, ?3, ?4)",
+        )
+        .bind(pattern)
+        .bind(max_size)
+        .bind(max_age_secs)
+        .bind(created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Returns false when no rule has this id.
+    pub async fn update_pin_rule(
+        &self,
+        id: i64,
+        pattern: &str,
+        max_size: Option<i64>,
+        max_age_secs: Option<i64>,
+    ) -> Result<bool, IndexError> {
+        let result = sqlx::query(
+            "UPDATE pin_rules SET pattern = ?2, max_size = ?3, max_age_secs = ?4 WHERE id = ?1",
+        )
+        .bind(id)
+        .bind(pattern)
+        .bind(max_size)
+        .bind(max_age_secs)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Returns false when no rule has this id.
+    pub async fn remove_pin_rule(&self, id: i64) -> Result<bool, IndexError> {
+        let result = sqlx::query("DELETE FROM pin_rules WHERE id = ?1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn list_pin_rules(&self) -> Result<Vec<PinRuleRecord>, IndexError> {
+        let rows = sqlx::query(
+            "SELECT id, pattern, max_size, max_age_secs, created_at
+             FROM pin_rules
+             ORDER BY id ASC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter()
+            .map(|row| {
+                Ok(PinRuleRecord {
+                    id: row.try_get("id")?,
+                    pattern: row.try_get("pattern")?,
+                    max_size: row.try_get("max_size")?,
+                    max_age_secs: row.try_get("max_age_secs")?,
+                    created_at: row.try_get("created_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Files at or below `prefix` with their pin flags.
+    pub async fn list_pin_candidates(&self, prefix: &str) -> Result<Vec<PinCandidate>, IndexError> {
+        let [prefix_a, prefix_b] = prefix_variants(prefix);
+        let pattern_a = like_pattern_for_prefix(&prefix_a);
+        let pattern_b = like_pattern_for_prefix(&prefix_b);
+        let rows = sqlx::query(
+            "SELECT i.id, i.path, i.size, i.modified, s.state, COALESCE(s.pinned, 0) AS pinned,
+                    a.item_id IS NOT NULL AS auto_pinned
+             FROM items i
+             LEFT JOIN states s ON s.item_id = i.id
+             LEFT JOIN auto_pins a ON a.item_id = i.id
+             WHERE (i.path = ?1 OR i.path LIKE ?2 ESCAPE '\\' OR i.path = ?3 OR i.path LIKE ?4 ESCAPE '\\')
+                AND i.item_type = 'file'
+             ORDER BY i.path ASC",
+        )
+        .bind(prefix_a)
+        .bind(pattern_a)
+        .bind(prefix_b)
+        .bind(pattern_b)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter()
+            .map(|row| {
+                let state: Option<String> = row.try_get("state")?;
+                Ok(PinCandidate {
+                    item_id: row.try_get("id")?,
+                    path: row.try_get("path")?,
+                    size: row.try_get("size")?,
+                    modified: row.try_get("modified")?,
+                    state: state.as_deref().map(FileState::parse).transpose()?,
+                    pinned: row.try_get::<i64, _>("pinned")? != 0,
+                    auto_pinned: row.try_get::<i64, _>("auto_pinned")? != 0,
+                })
+            })
+            .collect()
+    }
+
+    /// Records whether the item's pin came from a rule.
+    pub async fn set_auto_pinned(&self, item_id: i64, auto_pinned: bool) -> Result<(), IndexError> {
+        let query = if auto_pinned {
+            "INSERT OR IGNORE INTO auto_pins (item_id) VALUES (?1)"
+        } else {
+            "DELETE FROM auto_pins WHERE item_id = ?1"
+        };
+        sqlx::query(query).bind(item_id).execute(&self.pool).await?;
+        Ok(())
+    }
+}
+
+fn queued_op_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<QueuedOpRecord, IndexError> {
+    let kind: String = row.try_get("kind")?;
+    Ok(QueuedOpRecord {
+        id: row.try_get("id")?,
+        kind: kind.parse().map_err(IndexError::InvalidOperationKind)?,
+        path: row.try_get("path")?,
+        payload: row.try_get("payload")?,
+        attempt: row.try_get("attempt")?,
+        retry_at: row.try_get("retry_at")?,
+        priority: row.try_get("priority")?,
+        depends_on: match (
+            row.try_get::<Option<String>, _>("depends_on_kind")?,
+            row.try_get::<Option<String>, _>("depends_on_path")?,
+        ) {
+            (Some(kind), Some(path)) => {
+                Some((kind.parse().map_err(IndexError::InvalidOperationKind)?, path))
+            }
+            _ => None,
+        },
+        running: false,
+    })
+}
+
+fn running_op_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<QueuedOpRecord, IndexError> {
+    let op = journaled_op_from_row(row)?;
+    Ok(QueuedOpRecord {
+        id: row.try_get("id")?,
+        kind: op.kind,
+        path: op.path,
+        payload: op.payload,
+        attempt: op.attempt,
+        retry_at: None,
+        priority: op.priority,
+        depends_on: None,
+        running: true,
+    })
+}
+
+fn journaled_op_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<Operation, IndexError> {
+    let kind: String = row.try_get("kind")?;
+    Ok(Operation {
+        kind: kind.parse().map_err(IndexError::InvalidOperationKind)?,
+        path: row.try_get("path")?,
+        payload: row.try_get("payload")?,
+        attempt: row.try_get("attempt")?,
+        retry_at: None,
+        priority: row.try_get("priority")?,
+    })
+}
+
+fn failed_op_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<FailedOpRecord, IndexError> {
+    let kind: String = row.try_get("kind")?;
+    Ok(FailedOpRecord {
+        id: row.try_get("id")?,
+        kind: kind.parse().map_err(IndexError::InvalidOperationKind)?,
+        path: row.try_get("path")?,
+        payload: row.try_get("payload")?,
+        priority: row.try_get("priority")?,
+        attempts: row.try_get("attempts")?,
+        last_error: row.try_get("last_error")?,
+        first_failed_at: row.try_get("first_failed_at")?,
+        last_failed_at: row.try_get("last_failed_at")?,
+    })
+}
+
+fn trashed_item_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<TrashedItemRecord, IndexError> {
+    let item_type: Option<String> = row.try_get("item_type")?;
+    Ok(TrashedItemRecord {
+        id: row.try_get("id")?,
+        path: row.try_get("path")?,
+        item_type: item_type.as_deref().map(ItemType::parse).transpose()?,
+        resource_id: row.try_get("resource_id")?,
+        deleted_at: row.try_get("deleted_at")?,
+    })
+}
+
+fn pending_delete_from_row(
+    row: &sqlx::sqlite::SqliteRow,
+) -> Result<PendingDeleteRecord, IndexError> {
+    let origin: String = row.try_get("origin")?;
+    Ok(PendingDeleteRecord {
+        id: row.try_get("id")?,
+        path: row.try_get("path")?,
+        origin: DeleteOrigin::parse(&origin)?,
+        files: row.try_get("files")?,
+        held_at: row.try_get("held_at")?,
+    })
+}
+
+fn conflict_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<ConflictRecord, IndexError> {
+    Ok(ConflictRecord {
+        id: row.try_get("id")?,
+        path: row.try_get("path")?,
+        renamed_local: row.try_get("renamed_local")?,
+        created: row.try_get("created")?,
+        reason: row.try_get("reason")?,
+        status: row.try_get("status")?,
+    })
 }
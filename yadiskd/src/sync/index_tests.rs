@@ -209,6 +209,175 @@ async fn enqueue_and_dequeue_ops() {
     assert!(store.dequeue_op().await.unwrap().is_none());
 }
 
+#[tokio::test]
+async fn dequeue_keeps_queue_order_on_overlapping_paths() {
+    let store = make_store().await;
+    let op = |kind, path: &str, payload: Option<&str>, priority| Operation {
+        kind,
+        path: path.into(),
+        payload: payload.map(str::to_string),
+        attempt: 0,
+        retry_at: None,
+        priority,
+    };
+    store
+        .enqueue_op(&op(OperationKind::Mkdir, "/Docs", None, 0))
+        .await
+        .unwrap();
+    store
+        .enqueue_op(&op(OperationKind::Upload, "/Docs/a.txt", None, 50))
+        .await
+        .unwrap();
+    store
+        .enqueue_op(&op(OperationKind::Upload, "/Other.txt", None, 10))
+        .await
+        .unwrap();
+    store
+        .enqueue_op(&op(
+            OperationKind::Move,
+            "/Moved.txt",
+            Some(r#"{"from":"/Other.txt","path":"/Moved.txt"}"#),
+            90,
+        ))
+        .await
+        .unwrap();
+
+    // Nothing else may start while the folder is claimed elsewhere.
+    let busy = store
+        .dequeue_op_where(|op| op.path != "/Docs" && op.path != "/Other.txt")
+        .await
+        .unwrap();
+    assert!(busy.is_none());
+
+    let mut order = Vec::new();
+    while let Some(op) = store.dequeue_op().await.unwrap() {
+        order.push(op.path);
+    }
+    assert_eq!(
+        order,
+        vec!["/Other.txt", "/Moved.txt", "/Docs", "/Docs/a.txt"]
+    );
+}
+
+#[tokio::test]
+async fn dependent_ops_wait_for_requeued_prerequisite() {
+    let store = make_store().await;
+    let op = |kind, path: &str| Operation {
+        kind,
+        path: path.into(),
+        payload: None,
+        attempt: 0,
+        retry_at: None,
+        priority: 0,
+    };
+    store
+        .enqueue_op(&op(OperationKind::Mkdir, "/New"))
+        .await
+        .unwrap();
+    store
+        .enqueue_op(&op(OperationKind::Mkdir, "/New/Sub"))
+        .await
+        .unwrap();
+    store
+        .enqueue_op(&op(OperationKind::Upload, "/New/Sub/a.txt"))
+        .await
+        .unwrap();
+    let queued = store.list_ops(&OpFilter::default()).await.unwrap();
+    assert_eq!(queued[0].depends_on, None);
+    assert_eq!(
+        queued[1].depends_on,
+        Some((OperationKind::Mkdir, "/New".to_string()))
+    );
+    assert_eq!(
+        queued[2].depends_on,
+        Some((OperationKind::Mkdir, "/New/Sub".to_string()))
+    );
+
+    // A retried mkdir goes to the back of the queue, yet its dependents
+    // still wait for it.
+    let mkdir = store.dequeue_op().await.unwrap().unwrap();
+    store.requeue_op(&mkdir, i64::MAX, None).await.unwrap();
+    assert!(store.dequeue_op().await.unwrap().is_none());
+
+    let dependents = store
+        .take_dependent_ops(&OperationKind::Mkdir, "/New")
+        .await
+        .unwrap();
+    assert_eq!(dependents.len(), 1);
+    assert_eq!(dependents[0].path, "/New/Sub");
+}
+
+#[tokio::test]
+async fn ops_depend_on_older_overlapping_ops() {
+    let store = make_store().await;
+    let op = |kind, path: &str, payload: Option<&str>| Operation {
+        kind,
+        path: path.into(),
+        payload: payload.map(str::to_string),
+        attempt: 0,
+        retry_at: None,
+        priority: 0,
+    };
+    store
+        .enqueue_op(&op(OperationKind::Upload, "/Docs/a.txt", None))
+        .await
+        .unwrap();
+    store
+        .enqueue_op(&op(
+            OperationKind::Move,
+            "/Archive",
+            Some(r#"{"from":"/Docs","path":"/Archive"}"#),
+        ))
+        .await
+        .unwrap();
+    store
+        .enqueue_op(&op(OperationKind::Upload, "/Docs", None))
+        .await
+        .unwrap();
+    // Merging into the older upload must not make it wait for the move.
+    store
+        .enqueue_op(&op(OperationKind::Upload, "/Docs/a.txt", None))
+        .await
+        .unwrap();
+
+    let queued = store.list_ops(&OpFilter::default()).await.unwrap();
+    let depends_on = |path: &str| {
+        queued
+            .iter()
+            .find(|op| op.path == path)
+            .unwrap()
+            .depends_on
+            .clone()
+    };
+    assert_eq!(depends_on("/Docs/a.txt"), None);
+    assert_eq!(
+        depends_on("/Archive"),
+        Some((OperationKind::Upload, "/Docs/a.txt".to_string()))
+    );
+    assert_eq!(
+        depends_on("/Docs"),
+        Some((OperationKind::Move, "/Archive".to_string()))
+    );
+
+    let plan = sqlx::query(
+        "EXPLAIN QUERY PLAN
+         SELECT id FROM ops_queue ORDER BY priority DESC, id ASC LIMIT 64",
+    )
+    .fetch_all(&store.pool)
+    .await
+    .unwrap();
+    assert!(plan.iter().any(|row| {
+        row.get::<String, _>("detail")
+            .contains("idx_ops_queue_priority")
+    }));
+
+    let mut order = Vec::new();
+    while let Some(op) = store.dequeue_op().await.unwrap() {
+        order.push(op.path);
+    }
+    assert_eq!(order, vec!["/Docs/a.txt", "/Archive", "/Docs"]);
+}
+
 #[tokio::test]
 async fn enqueue_deduplicates_by_kind_and_path() {
     let store = make_store().await;
@@ -231,6 +400,14 @@ async fn enqueue_deduplicates_by_kind_and_path() {
 
     store.enqueue_op(&first).await.unwrap();
     store.enqueue_op(&second).await.unwrap();
+    // A plain re-queue keeps the flags already queued.
+    store
+        .enqueue_op(&Operation {
+            payload: None,
+            ..second.clone()
+        })
+        .await
+        .unwrap();
     let fetched = store.dequeue_op().await.unwrap().unwrap();
 
     assert_eq!(fetched.attempt, 0);
@@ -291,6 +468,36 @@ async fn records_and_lists_conflicts() {
     assert_eq!(conflicts[0].reason, "both-changed");
 }
 
+#[tokio::test]
+async fn closed_conflicts_leave_open_list() {
+    let store = make_store().await;
+    let id = store
+        .record_conflict("/Docs/A.txt", "/Docs/A.txt", 123, "needs-decision")
+        .await
+        .unwrap();
+    assert_eq!(
+        store
+            .open_conflict_for_path("/Docs/A.txt")
+            .await
+            .unwrap()
+            .map(|conflict| conflict.id),
+        Some(id)
+    );
+
+    assert!(store.close_conflict(id, "resolved", 456).await.unwrap());
+    assert!(!store.close_conflict(id, "dismissed", 789).await.unwrap());
+    assert!(store.list_conflicts().await.unwrap().is_empty());
+    assert!(
+        store
+            .open_conflict_for_path("/Docs/A.txt")
+            .await
+            .unwrap()
+            .is_none()
+    );
+    let closed = store.get_conflict(id).await.unwrap().unwrap();
+    assert_eq!(closed.status, "resolved");
+}
+
 #[tokio::test]
 async fn init_upgrades_legacy_schema() {
     let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
@@ -564,3 +771,521 @@ async fn like_query_escapes_percent_in_path() {
     assert_eq!(items.len(), 1, "percent must not match as wildcard");
     assert_eq!(items[0].path, "/100%Done/A.txt");
 }
+
+#[tokio::test]
+async fn local_identity_follows_item_and_is_found_by_inode() {
+    let store = make_store().await;
+    let item = store
+        .upsert_item(&ItemInput {
+            path: "/Photos/A.jpg".into(),
+            parent_path: Some("/Photos".into()),
+            name: "A.jpg".into(),
+            item_type: ItemType::File,
+            size: Some(4),
+            modified: None,
+            hash: None,
+            resource_id: None,
+            last_synced_hash: None,
+            last_synced_modified: None,
+        })
+        .await
+        .unwrap();
+    let identity = LocalFileIdentity {
+        device: 2049,
+        inode: u64::MAX - 1,
+        size: 4,
+        modified: 1_700_000_000,
+    };
+    store.set_local_identity(item.id, &identity).await.unwrap();
+
+    assert_eq!(
+        store
+            .get_local_identity_by_path("/Photos/A.jpg")
+            .await
+            .unwrap(),
+        Some(identity)
+    );
+    assert_eq!(
+        store.find_path_by_local_identity(&identity).await.unwrap(),
+        Some("/Photos/A.jpg".to_string())
+    );
+    assert_eq!(
+        store
+            .list_local_identities_by_prefix("disk:/Photos")
+            .await
+            .unwrap(),
+        vec![("/Photos/A.jpg".to_string(), identity)]
+    );
+
+    store.delete_item_by_path("/Photos/A.jpg").await.unwrap();
+    assert_eq!(
+        store.find_path_by_local_identity(&identity).await.unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn failed_ops_accumulate_attempts_per_kind_and_path() {
+    let store = make_store().await;
+    let op = Operation {
+        kind: OperationKind::Upload,
+        path: "/Docs/A.txt".into(),
+        payload: None,
+        attempt: 4,
+        retry_at: Some(10),
+        priority: 5,
+    };
+    let id = store
+        .record_failed_op(&op, 5, "quota exceeded", 100)
+        .await
+        .unwrap();
+    let again = store
+        .record_failed_op(&op, 1, "still no space", 200)
+        .await
+        .unwrap();
+    assert_eq!(again, id);
+
+    let failed = store.list_failed_ops().await.unwrap();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].attempts, 6);
+    assert_eq!(failed[0].last_error, "still no space");
+    assert_eq!(failed[0].first_failed_at, 100);
+    assert_eq!(failed[0].last_failed_at, 200);
+    assert_eq!(
+        store.get_failed_op(id).await.unwrap(),
+        Some(failed[0].clone())
+    );
+
+    assert!(store.delete_failed_op(id).await.unwrap());
+    assert!(!store.delete_failed_op(id).await.unwrap());
+    assert!(store.list_failed_ops().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn queued_ops_can_be_listed_reprioritized_and_cancelled() {
+    let store = make_store().await;
+    for (kind, path, priority) in [
+        (OperationKind::Upload, "/Docs/A.txt", 10),
+        (OperationKind::Download, "/Docs/B.txt", 20),
+        (OperationKind::Upload, "/Music/C.mp3", 10),
+    ] {
+        store
+            .enqueue_op(&Operation {
+                kind,
+                path: path.into(),
+                payload: None,
+                attempt: 0,
+                retry_at: None,
+                priority,
+            })
+            .await
+            .unwrap();
+    }
+
+    let all = store.list_ops(&OpFilter::default()).await.unwrap();
+    let paths: Vec<_> = all.iter().map(|op| op.path.as_str()).collect();
+    assert_eq!(paths, ["/Docs/B.txt", "/Docs/A.txt", "/Music/C.mp3"]);
+    let uploads_in_docs = store
+        .list_ops(&OpFilter::new("upload", "disk:/Docs").unwrap())
+        .await
+        .unwrap();
+    assert_eq!(uploads_in_docs.len(), 1);
+    assert_eq!(uploads_in_docs[0].path, "/Docs/A.txt");
+
+    let before = store.queue_summary().await.unwrap();
+    assert_eq!(before.pending, 3);
+    let music = all[2].id;
+    assert!(store.set_op_priority(music, 50).await.unwrap());
+    assert_ne!(store.queue_summary().await.unwrap(), before);
+    assert_eq!(
+        store.dequeue_op().await.unwrap().map(|op| op.path),
+        Some("/Music/C.mp3".to_string())
+    );
+
+    let cancelled = store.cancel_op(all[0].id).await.unwrap().unwrap();
+    assert_eq!(cancelled.kind, OperationKind::Download);
+    assert!(store.cancel_op(all[0].id).await.unwrap().is_none());
+    assert!(!store.set_op_priority(all[0].id, 1).await.unwrap());
+    assert_eq!(store.queue_summary().await.unwrap().pending, 1);
+}
+
+#[tokio::test]
+async fn claimed_ops_stay_journaled_until_finished() {
+    let store = make_store().await;
+    let mut ids = Vec::new();
+    for path in ["/Docs/A.txt", "/Docs/B.txt"] {
+        let item = store
+            .upsert_item(&ItemInput {
+                path: path.into(),
+                parent_path: Some("/Docs".into()),
+                name: path.trim_start_matches("/Docs/").into(),
+                item_type: ItemType::File,
+                size: Some(1),
+                modified: None,
+                hash: None,
+                resource_id: None,
+                last_synced_hash: None,
+                last_synced_modified: None,
+            })
+            .await
+            .unwrap();
+        store
+            .set_state(item.id, FileState::Syncing, false, None)
+            .await
+            .unwrap();
+        ids.push(item.id);
+    }
+    store
+        .enqueue_op(&Operation {
+            kind: OperationKind::Upload,
+            path: "/Docs/A.txt".into(),
+            payload: None,
+            attempt: 2,
+            retry_at: None,
+            priority: 0,
+        })
+        .await
+        .unwrap();
+
+    // B is syncing with nothing left to finish it; A is still queued.
+    assert_eq!(
+        store.list_stale_syncing_paths().await.unwrap(),
+        vec!["/Docs/B.txt".to_string()]
+    );
+
+    let claimed = store.dequeue_op().await.unwrap().unwrap();
+    store
+        .update_journal_payload(&claimed.kind, &claimed.path, r#"{"overwrite":true}"#)
+        .await
+        .unwrap();
+    let journaled = store.list_journaled_ops().await.unwrap();
+    assert_eq!(journaled.len(), 1);
+    assert_eq!(journaled[0].attempt, 2);
+    assert_eq!(
+        journaled[0].payload.as_deref(),
+        Some(r#"{"overwrite":true}"#)
+    );
+    assert_eq!(
+        store.list_stale_syncing_paths().await.unwrap(),
+        vec!["/Docs/B.txt".to_string()]
+    );
+
+    store
+        .finish_journal(&claimed.kind, &claimed.path)
+        .await
+        .unwrap();
+    assert!(store.list_journaled_ops().await.unwrap().is_empty());
+    assert_eq!(store.list_stale_syncing_paths().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn trashed_items_are_found_by_either_path_form() {
+    let store = make_store().await;
+    store
+        .record_trashed_item("disk:/Docs/A.txt", None, None, 10)
+        .await
+        .unwrap();
+    store
+        .record_trashed_item("/Docs/A.txt", Some(&ItemType::File), Some("rid-a"), 20)
+        .await
+        .unwrap();
+
+    let latest = store
+        .get_trashed_item("disk:/Docs/A.txt")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(latest.deleted_at, 20);
+    assert_eq!(latest.item_type, Some(ItemType::File));
+    assert_eq!(latest.resource_id.as_deref(), Some("rid-a"));
+
+    store.remove_trashed_item(latest.id).await.unwrap();
+    let older = store
+        .get_trashed_item("/Docs/A.txt")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(older.item_type, None);
+    assert_eq!(store.list_trashed_items().await.unwrap().len(), 1);
+    assert!(
+        store
+            .get_trashed_item("/Docs/B.txt")
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn pending_deletes_are_held_once_per_path() {
+    let store = make_store().await;
+    assert!(
+        store
+            .hold_delete("/Docs", DeleteOrigin::Local, 3, 10)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !store
+            .hold_delete("/Docs", DeleteOrigin::Cloud, 5, 20)
+            .await
+            .unwrap()
+    );
+    store
+        .hold_delete("disk:/Photos", DeleteOrigin::Cloud, 7, 30)
+        .await
+        .unwrap();
+
+    let pending = store.list_pending_deletes().await.unwrap();
+    assert_eq!(pending.len(), 2);
+    assert_eq!(pending[0].path, "/Docs");
+    assert_eq!(pending[0].origin, DeleteOrigin::Local);
+    assert_eq!(pending[0].files, 3);
+    assert_eq!(pending[1].origin, DeleteOrigin::Cloud);
+
+    store.remove_pending_delete(pending[0].id).await.unwrap();
+    assert_eq!(store.list_pending_deletes().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn pin_rules_are_stored_and_candidates_report_auto_pins() {
+    let store = make_store().await;
+    let id = store
+        .add_pin_rule("/Work/**", None, Some(3600), 10)
+        .await
+        .unwrap();
+    assert!(
+        store
+            .update_pin_rule(id, "*.pdf", Some(50), None)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !store
+            .update_pin_rule(id + 1, "*", None, None)
+            .await
+            .unwrap()
+    );
+    let rules = store.list_pin_rules().await.unwrap();
+    assert_eq!(rules.len(), 1);
+    assert_eq!(rules[0].pattern, "*.pdf");
+    assert_eq!(rules[0].max_size, Some(50));
+    assert_eq!(rules[0].max_age_secs, None);
+    assert_eq!(rules[0].created_at, 10);
+
+    let item = store
+        .upsert_item(&ItemInput {
+            path: "/Work/A.pdf".into(),
+            parent_path: Some("/Work".into()),
+            name: "A.pdf".into(),
+            item_type: ItemType::File,
+            size: Some(1),
+            modified: Some(5),
+            hash: None,
+            resource_id: None,
+            last_synced_hash: None,
+            last_synced_modified: None,
+        })
+        .await
+        .unwrap();
+    let candidates = store.list_pin_candidates("/Work").await.unwrap();
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0].state, None);
+    assert!(!candidates[0].auto_pinned);
+
+    store.set_auto_pinned(item.id, true).await.unwrap();
+    assert!(store.list_pin_candidates("/Work").await.unwrap()[0].auto_pinned);
+    store.delete_item_by_path("/Work/A.pdf").await.unwrap();
+    assert!(store.list_pin_candidates("/Work").await.unwrap().is_empty());
+    assert!(store.remove_pin_rule(id).await.unwrap());
+    assert!(store.list_pin_rules().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn folder_pins_are_replaced_and_follow_their_items() {
+    let store = make_store().await;
+    let folder = store
+        .upsert_item(&ItemInput {
+            path: "/Docs".into(),
+            parent_path: Some("/".into()),
+            name: "Docs".into(),
+            item_type: ItemType::Dir,
+            size: None,
+            modified: None,
+            hash: None,
+            resource_id: None,
+            last_synced_hash: None,
+            last_synced_modified: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(store.get_folder_pin(folder.id).await.unwrap(), None);
+    store.set_folder_pin(folder.id, Some(true)).await.unwrap();
+    store.set_folder_pin(folder.id, Some(false)).await.unwrap();
+    assert_eq!(
+        store.list_folder_pins().await.unwrap(),
+        vec![("/Docs".to_string(), false)]
+    );
+
+    store.set_folder_pin(folder.id, None).await.unwrap();
+    assert_eq!(store.get_folder_pin(folder.id).await.unwrap(), None);
+    store.set_folder_pin(folder.id, Some(true)).await.unwrap();
+    store.delete_item_by_path("/Docs").await.unwrap();
+    assert!(store.list_folder_pins().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn local_names_are_replaced_and_follow_their_items() {
+    let store = make_store().await;
+    let item = store
+        .upsert_item(&ItemInput {
+            path: "/Docs/Cafe\u{301}.txt".into(),
+            parent_path: Some("/Docs".into()),
+            name: "Cafe\u{301}.txt".into(),
+            item_type: ItemType::File,
+            size: Some(1),
+            modified: None,
+            hash: None,
+            resource_id: None,
+            last_synced_hash: None,
+            last_synced_modified: None,
+        })
+        .await
+        .unwrap();
+    store
+        .set_local_name(item.id, Some("Caf\u{e9}.txt"))
+        .await
+        .unwrap();
+    store
+        .set_local_name(item.id, Some("Caf\u{e9} (2).txt"))
+        .await
+        .unwrap();
+    assert_eq!(
+        store.list_local_names().await.unwrap(),
+        vec![(item.path.clone(), "Caf\u{e9} (2).txt".to_string())]
+    );
+
+    store.set_local_name(item.id, None).await.unwrap();
+    assert!(store.list_local_names().await.unwrap().is_empty());
+
+    store
+        .set_local_name(item.id, Some("Caf\u{e9}.txt"))
+        .await
+        .unwrap();
+    store.delete_item_by_path(&item.path).await.unwrap();
+    assert!(store.list_local_names().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn link_targets_are_replaced_and_follow_their_items() {
+    let store = make_store().await;
+    let item = store
+        .upsert_item(&ItemInput {
+            path: "/Docs/current".into(),
+            parent_path: Some("/Docs".into()),
+            name: "current".into(),
+            item_type: ItemType::File,
+            size: Some(8),
+            modified: None,
+            hash: None,
+            resource_id: None,
+            last_synced_hash: None,
+            last_synced_modified: None,
+        })
+        .await
+        .unwrap();
+    store.set_link_target(item.id, Some("v1")).await.unwrap();
+    store.set_link_target(item.id, Some("v2")).await.unwrap();
+    assert_eq!(
+        store.get_link_target(item.id).await.unwrap().as_deref(),
+        Some("v2")
+    );
+    assert_eq!(
+        store.list_link_targets().await.unwrap(),
+        vec![(item.path.clone(), "v2".to_string())]
+    );
+
+    store.set_link_target(item.id, None).await.unwrap();
+    assert!(store.get_link_target(item.id).await.unwrap().is_none());
+
+    store.set_link_target(item.id, Some("v1")).await.unwrap();
+    store.delete_item_by_path(&item.path).await.unwrap();
+    assert!(store.list_link_targets().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn lowered_priority_survives_requeue() {
+    let store = make_store().await;
+    let op = |priority| Operation {
+        kind: OperationKind::Download,
+        path: "/Docs/A.txt".into(),
+        payload: None,
+        attempt: 0,
+        retry_at: None,
+        priority,
+    };
+    store.enqueue_op(&op(50)).await.unwrap();
+    let id = store.list_ops(&OpFilter::default()).await.unwrap()[0].id;
+    assert!(store.set_op_priority(id, 1).await.unwrap());
+    store.enqueue_op(&op(50)).await.unwrap();
+    let queued = store.list_ops(&OpFilter::default()).await.unwrap();
+    assert_eq!((queued[0].id, queued[0].priority), (id, 1));
+
+    let claimed = store.dequeue_op().await.unwrap().unwrap();
+    assert!(
+        store
+            .list_ops(&OpFilter::default())
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    let running = store.list_running_ops(&OpFilter::default()).await.unwrap();
+    assert_eq!(running.len(), 1);
+    assert_eq!((running[0].id, running[0].running), (id, true));
+    assert_eq!(
+        store.get_running_op(id).await.unwrap().map(|op| op.path),
+        Some(claimed.path)
+    );
+}
+
+#[tokio::test]
+async fn content_lookup_matches_hashes_case_insensitively_through_an_index() {
+    let store = make_store().await;
+    for (path, hash) in [("/a.bin", "ABCDEF"), ("/b.bin", "abcdef")] {
+        store
+            .upsert_item(&ItemInput {
+                path: path.into(),
+                parent_path: Some("/".into()),
+                name: path.trim_start_matches('/').into(),
+                item_type: ItemType::File,
+                size: Some(3),
+                modified: None,
+                hash: Some(hash.into()),
+                resource_id: None,
+                last_synced_hash: Some(hash.into()),
+                last_synced_modified: None,
+            })
+            .await
+            .unwrap();
+    }
+
+    let item = store.get_item_by_path("/a.bin").await.unwrap().unwrap();
+    assert_eq!(item.hash.as_deref(), Some("abcdef"));
+    assert_eq!(
+        store
+            .find_synced_file_by_content("AbCdEf", 3, "/b.bin")
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("/a.bin")
+    );
+
+    let plan = sqlx::query("EXPLAIN QUERY PLAN SELECT path FROM items WHERE hash = 'abcdef'")
+        .fetch_all(&store.pool)
+        .await
+        .unwrap();
+    assert!(
+        plan.iter()
+            .any(|row| row.get::<String, _>("detail").contains("idx_items_hash"))
+    );
+}
@@ -1,10 +1,13 @@
 #![allow(dead_code)]
 
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use tokio::sync::mpsc;
 
+use super::watch_health::{WatchHealth, rescan_scopes};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum LocalEvent {
     Upload { path: String },
@@ -13,23 +16,52 @@ pub enum LocalEvent {
     Move { from: String, to: String },
 }
 
+/// Starts a recursive watch on `root`. Overflows and watch errors are not
+/// turned into events; they mark the affected subtree dirty in `health` so a
+/// reconciliation scan can pick up whatever the event stream lost.
 pub fn start_notify_watcher(
     root: &Path,
+    health: Arc<WatchHealth>,
 ) -> notify::Result<(RecommendedWatcher, mpsc::UnboundedReceiver<LocalEvent>)> {
     let (tx, rx) = mpsc::unbounded_channel();
     let root = root.to_path_buf();
     let watch_root = root.clone();
-    let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
-        if let Ok(event) = res {
+    let watch_health = Arc::clone(&health);
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| match res {
+        Ok(event) => {
+            for scope in rescan_scopes(&watch_root, &event) {
+                watch_health.mark_dirty(&scope);
+            }
             for local in map_event(&watch_root, event) {
                 let _ = tx.send(local);
             }
         }
+        Err(err) => note_watch_error(&watch_health, &err),
     })?;
-    watcher.watch(root.as_path(), RecursiveMode::Recursive)?;
+    health.set_watch_limit_reached(false);
+    if let Err(err) = watcher.watch(root.as_path(), RecursiveMode::Recursive) {
+        if !is_watch_limit_error(&err) {
+            return Err(err);
+        }
+        // The directories watched before the limit keep delivering events;
+        // the rest is covered by reconciliation scans.
+        note_watch_error(&health, &err);
+    }
     Ok((watcher, rx))
 }
 
+fn is_watch_limit_error(err: &notify::Error) -> bool {
+    matches!(err.kind, notify::ErrorKind::MaxFilesWatch)
+}
+
+fn note_watch_error(health: &WatchHealth, err: &notify::Error) {
+    if is_watch_limit_error(err) {
+        health.set_watch_limit_reached(true);
+    }
+    // Errors rarely say which watches were lost, so rescan the whole tree.
+    health.mark_dirty(health.root());
+}
+
 fn map_event(root: &Path, event: Event) -> Vec<LocalEvent> {
     match event.kind {
         EventKind::Modify(notify::event::ModifyKind::Name(mode)) => {
@@ -433,6 +465,19 @@ mod tests {
         assert!(mapped.is_empty());
     }
 
+    #[test]
+    fn watch_limit_error_flags_health_and_marks_root_dirty() {
+        let health = WatchHealth::new(PathBuf::from("/tmp/root"));
+        let err = notify::Error::new(notify::ErrorKind::MaxFilesWatch)
+            .add_path(PathBuf::from("/tmp/root/Docs/deep"));
+        note_watch_error(&health, &err);
+        assert!(health.watch_limit_reached());
+        assert_eq!(
+            health.take_settled(std::time::Duration::ZERO),
+            vec![PathBuf::from("/tmp/root")]
+        );
+    }
+
     #[test]
     fn ignores_modify_any_event() {
         let temp = tempfile::tempdir().unwrap();
@@ -0,0 +1,330 @@
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use similar::{Algorithm, DiffTag, capture_diff_slices};
+
+/// Files larger than this are never merged and get no stored base.
+pub const DEFAULT_TEXT_MERGE_MAX_BYTES: u64 = 256 * 1024;
+
+pub const DEFAULT_TEXT_EXTENSIONS: &[&str] = &[
+    "txt", "md", "markdown", "org", "rst", "adoc", "tex", "csv", "tsv", "json", "yaml", "yml",
+    "toml", "ini", "conf", "xml", "html", "css",
+];
+
+/// Automatic three-way merge of plain-text files changed on both sides.
+///
+/// The last synced content of small text files is kept in `base_dir`, one
+/// file per remote path, so a later conflict can be merged against it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextMerge {
+    pub base_dir: PathBuf,
+    pub extensions: Vec<String>,
+    pub max_bytes: u64,
+}
+
+impl TextMerge {
+    pub fn new(base_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            base_dir: base_dir.into(),
+            extensions: DEFAULT_TEXT_EXTENSIONS
+                .iter()
+                .map(|ext| ext.to_string())
+                .collect(),
+            max_bytes: DEFAULT_TEXT_MERGE_MAX_BYTES,
+        }
+    }
+
+    /// Replaces the extension list with a comma-separated one such as
+    /// `"md,txt,.org"`. An empty value keeps the defaults.
+    pub fn with_extensions(mut self, value: &str) -> Self {
+        let extensions: Vec<String> = value
+            .split(',')
+            .map(|ext| ext.trim().trim_start_matches('.').to_ascii_lowercase())
+            .filter(|ext| !ext.is_empty())
+            .collect();
+        if !extensions.is_empty() {
+            self.extensions = extensions;
+        }
+        self
+    }
+
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Whether `path` is merged as text, by its extension or a `text/*`
+    /// mime type reported by the cloud.
+    pub fn is_text(&self, path: &str, mime_type: Option<&str>) -> bool {
+        if mime_type.is_some_and(|mime| mime.starts_with("text/")) {
+            return true;
+        }
+        let name = path.rsplit('/').next().unwrap_or(path);
+        name.rsplit_once('.').is_some_and(|(stem, ext)| {
+            !stem.is_empty()
+                && self
+                    .extensions
+                    .iter()
+                    .any(|known| known.eq_ignore_ascii_case(ext))
+        })
+    }
+
+    /// Remembers the synced content of `source` as the base of `path`. Files
+    /// that are too large or not text drop any base stored earlier. The mime
+    /// type is not known once a file is synced, so only the extension counts.
+    pub async fn save_base(&self, path: &str, source: &Path) -> std::io::Result<()> {
+        if !self.is_text(path, None) {
+            return self.remove_base(path).await;
+        }
+        let target = self.base_path(path);
+        let meta = tokio::fs::metadata(source).await?;
+        if meta.is_file() && meta.len() <= self.max_bytes {
+            let content = tokio::fs::read(source).await?;
+            if looks_like_text(&content) {
+                tokio::fs::create_dir_all(&self.base_dir).await?;
+                let partial = target.with_extension("partial");
+                tokio::fs::write(&partial, &content).await?;
+                return tokio::fs::rename(&partial, &target).await;
+            }
+        }
+        self.remove_base(path).await
+    }
+
+    /// Returns the stored base of `path` if it still matches the last synced
+    /// hash from the index.
+    pub async fn load_base(
+        &self,
+        path: &str,
+        expected_md5: &str,
+    ) -> std::io::Result<Option<String>> {
+        let content = match tokio::fs::read(self.base_path(path)).await {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        if !format!("{:x}", md5::compute(&content)).eq_ignore_ascii_case(expected_md5) {
+            return Ok(None);
+        }
+        Ok(String::from_utf8(content).ok())
+    }
+
+    pub async fn remove_base(&self, path: &str) -> std::io::Result<()> {
+        match tokio::fs::remove_file(self.base_path(path)).await {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    /// Keeps the base of a file moved from `from` to `to`.
+    pub async fn move_base(&self, from: &str, to: &str) -> std::io::Result<()> {
+        match tokio::fs::rename(self.base_path(from), self.base_path(to)).await {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    /// Where the remote version of `path` is downloaded while merging, kept
+    /// out of the cache so it is never picked up as a local change.
+    pub fn scratch_path(&self, path: &str) -> PathBuf {
+        self.base_path(path).with_extension("remote")
+    }
+
+    fn base_path(&self, path: &str) -> PathBuf {
+        let path = path.strip_prefix("disk:").unwrap_or(path);
+        let digest = Sha256::digest(path.as_bytes());
+        let name: String = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+        self.base_dir.join(name)
+    }
+}
+
+fn looks_like_text(content: &[u8]) -> bool {
+    !content.contains(&0) && std::str::from_utf8(content).is_ok()
+}
+
+/// A changed region: lines `base_start..base_end` of the base were replaced
+/// by lines `start..end` of the changed side.
+#[derive(Debug, Clone, Copy)]
+struct Hunk {
+    base_start: usize,
+    base_end: usize,
+    start: usize,
+    end: usize,
+}
+
+fn hunks(base: &[&str], side: &[&str]) -> Vec<Hunk> {
+    capture_diff_slices(Algorithm::Myers, base, side)
+        .iter()
+        .map(|op| op.as_tag_tuple())
+        .filter(|(tag, _, _)| *tag != DiffTag::Equal)
+        .map(|(_, old, new)| Hunk {
+            base_start: old.start,
+            base_end: old.end,
+            start: new.start,
+            end: new.end,
+        })
+        .collect()
+}
+
+/// Lines of `side` covering base lines `from..to`, given the hunks of that
+/// side inside the range.
+fn side_lines<'a>(
+    base: &[&'a str],
+    side: &[&'a str],
+    group: &[Hunk],
+    from: usize,
+    to: usize,
+) -> Vec<&'a str> {
+    match (group.first(), group.last()) {
+        (Some(first), Some(last)) => {
+            let start = first.start - (first.base_start - from);
+            let end = last.end + (to - last.base_end);
+            side[start..end].to_vec()
+        }
+        _ => base[from..to].to_vec(),
+    }
+}
+
+/// Line-based three-way merge. Returns `None` when both sides changed the
+/// same or adjacent lines differently.
+pub fn merge_text(base: &str, local: &str, remote: &str) -> Option<String> {
+    let base: Vec<&str> = base.split_inclusive('\n').collect();
+    let local: Vec<&str> = local.split_inclusive('\n').collect();
+    let remote: Vec<&str> = remote.split_inclusive('\n').collect();
+    let local_hunks = hunks(&base, &local);
+    let remote_hunks = hunks(&base, &remote);
+
+    let mut merged: Vec<&str> = Vec::with_capacity(base.len().max(local.len()));
+    let (mut next_local, mut next_remote, mut copied) = (0, 0, 0);
+    loop {
+        let from = match (local_hunks.get(next_local), remote_hunks.get(next_remote)) {
+            (Some(l), Some(r)) => l.base_start.min(r.base_start),
+            (Some(l), None) => l.base_start,
+            (None, Some(r)) => r.base_start,
+            (None, None) => break,
+        };
+        // Grow the region until no hunk of either side starts inside or right
+        // after it, so touching edits are treated as overlapping.
+        let (first_local, first_remote) = (next_local, next_remote);
+        let mut to = from;
+        loop {
+            if let Some(hunk) = local_hunks.get(next_local).filter(|h| h.base_start <= to) {
+                to = to.max(hunk.base_end);
+                next_local += 1;
+            } else if let Some(hunk) = remote_hunks.get(next_remote).filter(|h| h.base_start <= to)
+            {
+                to = to.max(hunk.base_end);
+                next_remote += 1;
+            } else {
+                break;
+            }
+        }
+        let local_group = &local_hunks[first_local..next_local];
+        let remote_group = &remote_hunks[first_remote..next_remote];
+        let ours = side_lines(&base, &local, local_group, from, to);
+        let theirs = side_lines(&base, &remote, remote_group, from, to);
+        if !local_group.is_empty() && !remote_group.is_empty() && ours != theirs {
+            return None;
+        }
+        merged.extend_from_slice(&base[copied..from]);
+        merged.extend(if local_group.is_empty() { theirs } else { ours });
+        copied = to;
+    }
+    merged.extend_from_slice(&base[copied..]);
+    Some(merged.concat())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "# Notes\n\nalpha\nbeta\ngamma\ndelta\nepsilon\n";
+
+    #[test]
+    fn merges_edits_to_different_lines() {
+        let local = "# Notes\n\nalpha (local)\nbeta\ngamma\ndelta\nepsilon\n";
+        let remote = "# Notes\n\nalpha\nbeta\ngamma\ndelta\nepsilon (remote)\nzeta\n";
+        assert_eq!(
+            merge_text(BASE, local, remote).as_deref(),
+            Some("# Notes\n\nalpha (local)\nbeta\ngamma\ndelta\nepsilon (remote)\nzeta\n")
+        );
+    }
+
+    #[test]
+    fn overlapping_or_touching_edits_do_not_merge() {
+        let local = "# Notes\n\nalpha\nbeta (local)\ngamma\ndelta\nepsilon\n";
+        let remote = "# Notes\n\nalpha\nbeta (remote)\ngamma\ndelta\nepsilon\n";
+        assert_eq!(merge_text(BASE, local, remote), None);
+        let touching = "# Notes\n\nalpha\nbeta\ngamma (remote)\ndelta\nepsilon\n";
+        assert_eq!(merge_text(BASE, local, touching), None);
+    }
+
+    #[test]
+    fn identical_edits_and_one_sided_changes_merge() {
+        let edited = "# Notes\n\nalpha\nbeta\ngamma!\ndelta\nepsilon\n";
+        assert_eq!(merge_text(BASE, edited, edited).as_deref(), Some(edited));
+        assert_eq!(merge_text(BASE, BASE, edited).as_deref(), Some(edited));
+        let without_trailing_newline = "# Notes\n\nalpha\nbeta\ngamma\ndelta\nepsilon";
+        let local = "# Notes (local)\n\nalpha\nbeta\ngamma\ndelta\nepsilon\n";
+        assert_eq!(
+            merge_text(BASE, local, without_trailing_newline).as_deref(),
+            Some("# Notes (local)\n\nalpha\nbeta\ngamma\ndelta\nepsilon")
+        );
+    }
+
+    #[test]
+    fn text_detection_uses_extensions_and_mime() {
+        let merge = TextMerge::new("/tmp/bases");
+        assert!(merge.is_text("disk:/Notes/todo.MD", None));
+        assert!(merge.is_text("/Notes/README", Some("text/plain")));
+        assert!(!merge.is_text("/Photos/cat.jpg", Some("image/jpeg")));
+        assert!(!merge.is_text("/Notes/.md", None));
+        let custom = merge.with_extensions(" .Log, adoc ");
+        assert!(custom.is_text("/var/app.log", None));
+        assert!(!custom.is_text("/Notes/todo.md", None));
+    }
+
+    #[tokio::test]
+    async fn bases_are_stored_for_small_text_files_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let merge = TextMerge::new(dir.path().join("bases")).with_max_bytes(16);
+        let source = dir.path().join("note.md");
+        tokio::fs::write(&source, "hello\n").await.unwrap();
+        merge.save_base("/note.md", &source).await.unwrap();
+        let hash = format!("{:x}", md5::compute("hello\n"));
+        assert_eq!(
+            merge
+                .load_base("disk:/note.md", &hash)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("hello\n")
+        );
+        assert_eq!(merge.load_base("/note.md", "stale").await.unwrap(), None);
+
+        tokio::fs::write(&source, "far too long for the limit\n")
+            .await
+            .unwrap();
+        merge.save_base("/note.md", &source).await.unwrap();
+        assert_eq!(merge.load_base("/note.md", &hash).await.unwrap(), None);
+
+        // Small UTF-8 content alone is not enough: the name must be text too.
+        tokio::fs::write(&source, "hello\n").await.unwrap();
+        merge.save_base("/photo.jpg", &source).await.unwrap();
+        assert_eq!(merge.load_base("/photo.jpg", &hash).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn bases_follow_moves() {
+        let dir = tempfile::tempdir().unwrap();
+        let merge = TextMerge::new(dir.path().join("bases"));
+        let source = dir.path().join("note.md");
+        tokio::fs::write(&source, "hello\n").await.unwrap();
+        merge.save_base("/a.md", &source).await.unwrap();
+        let hash = format!("{:x}", md5::compute("hello\n"));
+
+        merge.move_base("/a.md", "/b.md").await.unwrap();
+        assert_eq!(merge.load_base("/a.md", &hash).await.unwrap(), None);
+        assert!(merge.load_base("/b.md", &hash).await.unwrap().is_some());
+        merge.move_base("/missing.md", "/c.md").await.unwrap();
+    }
+}
@@ -1,8 +1,18 @@
 pub mod backoff;
-mod conflict;
+pub mod conflict;
+pub mod delete_guard;
 pub mod engine;
+pub mod eviction;
 pub mod index;
 pub mod local_watcher;
+pub mod merge;
+pub mod move_detect;
 pub mod paths;
+pub mod pin_rules;
+pub mod prefetch;
 pub mod queue;
+pub mod reconcile;
+pub mod settle;
+pub mod symlinks;
 pub mod transfer;
+pub mod watch_health;
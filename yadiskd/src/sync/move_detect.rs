@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::os::unix::fs::MetadataExt;
+use std::time::{Duration, Instant};
+
+use super::index::LocalFileIdentity;
+
+pub fn identity_from_metadata(meta: &std::fs::Metadata) -> LocalFileIdentity {
+    LocalFileIdentity {
+        device: meta.dev(),
+        inode: meta.ino(),
+        size: meta.len(),
+        modified: meta.mtime(),
+    }
+}
+
+/// Pairs paths that disappear with paths that appear carrying the same file
+/// identity, so renames reported as separate delete and create events become
+/// a single move instead of a delete plus a full re-upload.
+#[derive(Debug)]
+pub struct MoveDetector {
+    window: Duration,
+    held_deletes: HashMap<String, (Instant, LocalFileIdentity)>,
+    moved_away: HashMap<String, Instant>,
+}
+
+impl MoveDetector {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            held_deletes: HashMap::new(),
+            moved_away: HashMap::new(),
+        }
+    }
+
+    /// Keeps a delete back for the pairing window in case the file shows up
+    /// elsewhere.
+    pub fn hold_delete(&mut self, path: String, identity: LocalFileIdentity) {
+        self.held_deletes.insert(path, (Instant::now(), identity));
+    }
+
+    pub fn take_held_delete(&mut self, identity: &LocalFileIdentity) -> Option<String> {
+        let path = self
+            .held_deletes
+            .iter()
+            .find(|(_, (_, held))| held == identity)
+            .map(|(path, _)| path.clone())?;
+        self.held_deletes.remove(&path);
+        Some(path)
+    }
+
+    /// Drops a held delete once a paired rename for `path` was reported.
+    pub fn cancel_delete(&mut self, path: &str) -> bool {
+        self.held_deletes.remove(path).is_some()
+    }
+
+    /// Records that `path` was already turned into a move source because its
+    /// new location was seen first; the late delete for it is then dropped.
+    pub fn note_moved_away(&mut self, path: String) {
+        self.moved_away.insert(path, Instant::now());
+    }
+
+    pub fn consume_moved_away(&mut self, path: &str) -> bool {
+        self.moved_away.remove(path).is_some()
+    }
+
+    pub fn has_pending(&self) -> bool {
+        !self.held_deletes.is_empty() || !self.moved_away.is_empty()
+    }
+
+    /// When the oldest held path runs out of its pairing window. Later events
+    /// do not push it back.
+    pub fn next_expiry(&self) -> Option<Instant> {
+        self.held_deletes
+            .values()
+            .map(|(at, _)| *at)
+            .chain(self.moved_away.values().copied())
+            .min()
+            .map(|at| at + self.window)
+    }
+
+    /// Returns held deletes whose pairing window ran out; they are real
+    /// deletions now.
+    pub fn take_expired(&mut self) -> Vec<String> {
+        let window = self.window;
+        self.moved_away.retain(|_, at| at.elapsed() < window);
+        let expired: Vec<String> = self
+            .held_deletes
+            .iter()
+            .filter(|(_, (at, _))| at.elapsed() >= window)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in &expired {
+            self.held_deletes.remove(path);
+        }
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity(inode: u64) -> LocalFileIdentity {
+        LocalFileIdentity {
+            device: 1,
+            inode,
+            size: 42,
+            modified: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn held_delete_pairs_with_matching_identity() {
+        let mut detector = MoveDetector::new(Duration::from_secs(60));
+        detector.hold_delete("/Photos/A.jpg".into(), identity(7));
+        assert_eq!(detector.take_held_delete(&identity(8)), None);
+        assert_eq!(
+            detector.take_held_delete(&identity(7)),
+            Some("/Photos/A.jpg".to_string())
+        );
+        assert!(!detector.has_pending());
+    }
+
+    #[test]
+    fn changed_size_or_mtime_does_not_pair() {
+        let mut detector = MoveDetector::new(Duration::from_secs(60));
+        detector.hold_delete("/Photos/A.jpg".into(), identity(7));
+        let mut edited = identity(7);
+        edited.modified += 1;
+        assert_eq!(detector.take_held_delete(&edited), None);
+    }
+
+    #[test]
+    fn unpaired_deletes_are_released_after_window() {
+        let mut detector = MoveDetector::new(Duration::ZERO);
+        detector.hold_delete("/Photos/A.jpg".into(), identity(7));
+        detector.note_moved_away("/Photos/B.jpg".into());
+        assert_eq!(detector.take_expired(), vec!["/Photos/A.jpg".to_string()]);
+        assert!(!detector.consume_moved_away("/Photos/B.jpg"));
+        assert!(!detector.has_pending());
+    }
+
+    #[test]
+    fn paired_rename_cancels_held_delete() {
+        let mut detector = MoveDetector::new(Duration::ZERO);
+        detector.hold_delete("/Photos/A.jpg".into(), identity(7));
+        assert!(detector.cancel_delete("/Photos/A.jpg"));
+        assert!(detector.take_expired().is_empty());
+    }
+
+    #[test]
+    fn expiry_follows_the_oldest_held_path() {
+        let mut detector = MoveDetector::new(Duration::from_secs(60));
+        assert_eq!(detector.next_expiry(), None);
+        detector.hold_delete("/Photos/A.jpg".into(), identity(7));
+        let first = detector.next_expiry().unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        detector.hold_delete("/Photos/B.jpg".into(), identity(8));
+        detector.note_moved_away("/Photos/C.jpg".into());
+        assert_eq!(detector.next_expiry(), Some(first));
+        assert!(detector.cancel_delete("/Photos/A.jpg"));
+        assert!(detector.next_expiry().unwrap() > first);
+    }
+
+    #[test]
+    fn late_delete_of_moved_source_is_consumed_once() {
+        let mut detector = MoveDetector::new(Duration::from_secs(60));
+        detector.note_moved_away("/Photos/A.jpg".into());
+        assert!(detector.consume_moved_away("/Photos/A.jpg"));
+        assert!(!detector.consume_moved_away("/Photos/A.jpg"));
+    }
+
+    #[test]
+    fn identity_survives_rename() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("A.jpg");
+        let to = dir.path().join("B.jpg");
+        std::fs::write(&from, b"jpeg").unwrap();
+        let before = identity_from_metadata(&std::fs::metadata(&from).unwrap());
+        std::fs::rename(&from, &to).unwrap();
+        let after = identity_from_metadata(&std::fs::metadata(&to).unwrap());
+        assert_eq!(before, after);
+    }
+}
@@ -1,13 +1,17 @@
-use std::path::{Component, Path, PathBuf};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
+use sha2::{Digest, Sha256};
 use thiserror::Error;
+use unicode_normalization::{UnicodeNormalization, is_nfc};
+
+/// Longest file name, in bytes, that common Linux file systems accept.
+const NAME_MAX: usize = 255;
 
 #[derive(Debug, Error)]
 pub enum PathError {
     #[error("remote path is empty")]
     Empty,
-    #[error("remote path contains unsupported component")]
-    UnsupportedComponent,
 }
 
 pub fn cache_path_for(cache_root: &Path, remote_path: &str) -> Result<PathBuf, PathError> {
@@ -16,20 +20,185 @@ pub fn cache_path_for(cache_root: &Path, remote_path: &str) -> Result<PathBuf, P
     }
 
     // Remote paths are POSIX-like ("/Docs/A.txt"); map them under cache_root.
+    // Names that cannot exist on disk are escaped, which also keeps `..`
+    // from leaving cache_root.
     let mut out = cache_root.to_path_buf();
-    for component in Path::new(remote_path).components() {
-        match component {
-            Component::Normal(part) => out.push(part),
-            Component::RootDir => continue,
-            Component::CurDir => continue,
-            Component::ParentDir | Component::Prefix(_) => {
-                return Err(PathError::UnsupportedComponent);
-            }
+    for part in remote_path.split('/').filter(|part| !part.is_empty()) {
+        match escape_name(part) {
+            Some(escaped) => out.push(escaped),
+            None => out.push(part),
         }
     }
     Ok(out)
 }
 
+/// A name that can be created on disk for a remote name that cannot be:
+/// `.`, `..`, names with NUL bytes and names over `NAME_MAX` bytes. The
+/// readable part and the extension are kept and a hash of the original
+/// name is added, so different names stay apart; `None` if `name` is fine.
+pub fn escape_name(name: &str) -> Option<String> {
+    let readable = match name {
+        "." | ".." => name.replace('.', "%2E"),
+        _ if name.contains('\0') || name.len() > NAME_MAX => name.replace('\0', "%00"),
+        _ => return None,
+    };
+    let digest = Sha256::digest(name.as_bytes());
+    let hash: String = digest[..4]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    let (stem, ext) = match readable.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() && ext.len() <= 16 => (stem, Some(ext)),
+        _ => (readable.as_str(), None),
+    };
+    let suffix = match ext {
+        Some(ext) => format!("~{hash}.{ext}"),
+        None => format!("~{hash}"),
+    };
+    let mut end = stem.len().min(NAME_MAX - suffix.len());
+    while !stem.is_char_boundary(end) {
+        end -= 1;
+    }
+    Some(format!("{}{suffix}", &stem[..end]))
+}
+
+/// The name `name` gets on disk when nothing else claims it.
+fn local_form(name: &str) -> String {
+    let nfc = nfc_name(name);
+    escape_name(&nfc).unwrap_or(nfc)
+}
+
+/// The NFC form of a name. Names uploaded from macOS arrive in NFD, while
+/// names typed on Linux are almost always NFC.
+pub fn nfc_name(name: &str) -> String {
+    name.nfc().collect()
+}
+
+/// Names with the same key cannot share a directory on a case-insensitive
+/// or normalizing file system, and look identical to the user anywhere.
+fn collision_key(name: &str) -> String {
+    nfc_name(name).to_lowercase()
+}
+
+/// Local names for the entries of one remote directory, in the same order.
+///
+/// Every name is stored in NFC and escaped if it cannot exist on disk
+/// (see `escape_name`). When names differ only by case or
+/// normalization, the one already in NFC (then the lowest by bytes) keeps
+/// its name and the others are numbered apart, e.g. `Report (2).txt`, so
+/// the outcome does not depend on listing order.
+pub fn local_names_for(names: &[&str]) -> Vec<String> {
+    let mut order: Vec<usize> = (0..names.len()).collect();
+    order.sort_by_key(|&i| (!is_nfc(names[i]), names[i]));
+
+    let mut out = vec![String::new(); names.len()];
+    let mut taken = HashSet::new();
+    let mut clashing = Vec::new();
+    for i in order {
+        let local = local_form(names[i]);
+        if taken.insert(collision_key(&local)) {
+            out[i] = local;
+        } else {
+            clashing.push(i);
+        }
+    }
+    for i in clashing {
+        let local = local_form(names[i]);
+        out[i] = (2..)
+            .map(|n| {
+                let numbered = numbered_name(&local, n);
+                escape_name(&numbered).unwrap_or(numbered)
+            })
+            .find(|candidate| taken.insert(collision_key(candidate)))
+            .expect("unbounded range yields a free name");
+    }
+    out
+}
+
+fn numbered_name(name: &str, n: u32) -> String {
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!("{stem} ({n}).{ext}"),
+        _ => format!("{name} ({n})"),
+    }
+}
+
+/// Maps remote paths to `/`-rooted paths under the sync root and back.
+///
+/// Names are compared in NFC; names recorded in the index as stored under
+/// another name on disk are translated both ways.
+#[derive(Debug, Clone, Default)]
+pub struct LocalNames {
+    local_by_remote: HashMap<String, String>,
+    remote_by_local: HashMap<(String, String), String>,
+}
+
+impl LocalNames {
+    /// Builds the map from `(remote path, local name)` pairs.
+    pub fn new(entries: impl IntoIterator<Item = (String, String)>) -> Self {
+        let mut names = Self::default();
+        for (path, local_name) in entries {
+            let path = slash_path(&path);
+            let (parent, name) = path.rsplit_once('/').unwrap_or(("", &path));
+            names
+                .remote_by_local
+                .insert((parent.to_string(), local_name.clone()), name.to_string());
+            names.local_by_remote.insert(path, local_name);
+        }
+        names
+    }
+
+    /// Where `remote_path` lives on disk, relative to the sync root.
+    pub fn local_path(&self, remote_path: &str) -> String {
+        let mut remote = String::new();
+        let mut local = String::new();
+        for part in slash_path(remote_path)
+            .split('/')
+            .filter(|part| !part.is_empty())
+        {
+            remote.push('/');
+            remote.push_str(part);
+            local.push('/');
+            match self.local_by_remote.get(&remote) {
+                Some(name) => local.push_str(name),
+                None => local.push_str(&local_form(part)),
+            }
+        }
+        if local.is_empty() {
+            "/".to_string()
+        } else {
+            local
+        }
+    }
+
+    /// The remote path of `local_path`, a path relative to the sync root.
+    pub fn remote_path(&self, local_path: &str) -> String {
+        let mut remote = String::new();
+        for part in slash_path(local_path)
+            .split('/')
+            .filter(|part| !part.is_empty())
+        {
+            let part = nfc_name(part);
+            let name = self
+                .remote_by_local
+                .get(&(remote.clone(), part.clone()))
+                .cloned()
+                .unwrap_or(part);
+            remote.push('/');
+            remote.push_str(&name);
+        }
+        if remote.is_empty() {
+            "/".to_string()
+        } else {
+            remote
+        }
+    }
+}
+
+fn slash_path(path: &str) -> String {
+    let rest = path.strip_prefix("disk:").unwrap_or(path);
+    format!("/{}", rest.trim_matches('/'))
+}
+
 pub fn is_ignored_temporary_name(name: &str) -> bool {
     name.starts_with(".goutputstream-")
         || name.starts_with(".~lock.") && name.ends_with('#')
@@ -54,11 +223,91 @@ mod tests {
     }
 
     #[test]
-    fn rejects_parent_dir() {
+    fn local_names_are_nfc_and_numbered_apart() {
+        let nfd = "Cafe\u{301}.txt";
+        let nfc = "Caf\u{e9}.txt";
+        assert_eq!(local_names_for(&[nfd]), vec![nfc.to_string()]);
+        assert_eq!(
+            local_names_for(&[nfd, nfc, "caf\u{e9}.TXT", "Notes"]),
+            vec![
+                "Caf\u{e9} (3).txt".to_string(),
+                nfc.to_string(),
+                "caf\u{e9} (2).TXT".to_string(),
+                "Notes".to_string(),
+            ]
+        );
+        assert_eq!(
+            local_names_for(&["readme", "README", "README (2)"]),
+            vec![
+                "readme (3)".to_string(),
+                "README".to_string(),
+                "README (2)".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn local_names_translate_paths_both_ways() {
+        let names = LocalNames::new([
+            (
+                "disk:/Docs/Cafe\u{301}".to_string(),
+                "Caf\u{e9}".to_string(),
+            ),
+            (
+                "disk:/Docs/Cafe\u{301}/a.txt".to_string(),
+                "a (2).txt".to_string(),
+            ),
+        ]);
+        assert_eq!(
+            names.local_path("disk:/Docs/Cafe\u{301}/a.txt"),
+            "/Docs/Caf\u{e9}/a (2).txt"
+        );
+        assert_eq!(
+            names.remote_path("/Docs/Caf\u{e9}/a (2).txt"),
+            "/Docs/Cafe\u{301}/a.txt"
+        );
+        assert_eq!(names.local_path("/Other/Ru\u{308}be"), "/Other/R\u{fc}be");
+        assert_eq!(names.remote_path("/Other/Ru\u{308}be"), "/Other/R\u{fc}be");
+        assert_eq!(names.remote_path("/"), "/");
+    }
+
+    #[test]
+    fn escapes_dot_names_instead_of_leaving_cache_root() {
         let root = PathBuf::from("/cache");
-        assert!(matches!(
-            cache_path_for(&root, "../secret"),
-            Err(PathError::UnsupportedComponent)
-        ));
+        let mapped = cache_path_for(&root, "/Docs/../secret").unwrap();
+        assert!(mapped.starts_with("/cache/Docs"));
+        let escaped = mapped.parent().unwrap().file_name().unwrap();
+        assert!(escaped.to_str().unwrap().starts_with("%2E%2E~"));
+        assert_ne!(escape_name("."), escape_name(".."));
+    }
+
+    #[test]
+    fn escapes_nul_bytes_and_overlong_names() {
+        assert_eq!(escape_name("Report.txt"), None);
+        let nul = escape_name("a\0b.txt").unwrap();
+        assert!(nul.starts_with("a%00b~") && nul.ends_with(".txt"));
+
+        let long = format!("{}.pdf", "\u{44f}".repeat(200));
+        let escaped = escape_name(&long).unwrap();
+        assert!(escaped.len() <= NAME_MAX);
+        assert!(escaped.ends_with(".pdf"));
+        let other = escape_name(&format!("{}x.pdf", "\u{44f}".repeat(200))).unwrap();
+        assert_ne!(escaped, other);
+        assert_eq!(escape_name(&long), Some(escaped));
+    }
+
+    #[test]
+    fn escaped_names_are_mapped_back_through_the_index() {
+        let local = local_names_for(&[".."]);
+        assert!(local[0].starts_with("%2E%2E~"));
+        let names = LocalNames::new([("disk:/Docs/..".to_string(), local[0].clone())]);
+        assert_eq!(
+            names.local_path("disk:/Docs/.."),
+            format!("/Docs/{}", local[0])
+        );
+        assert_eq!(
+            names.remote_path(&format!("/Docs/{}", local[0])),
+            "/Docs/.."
+        );
     }
 }
@@ -0,0 +1,171 @@
+use std::time::Duration;
+
+/// A rule that keeps matching files pinned without pinning each one by hand.
+/// Every limit that is set must hold for a file to match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinRule {
+    /// Glob over remote paths such as `/Work/**`; without a `/` it is
+    /// matched against file names anywhere, as in `*.pdf`.
+    pub pattern: String,
+    /// Largest file size the rule pins.
+    pub max_size: Option<u64>,
+    /// Only files modified this recently are pinned.
+    pub max_age: Option<Duration>,
+}
+
+impl PinRule {
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let trimmed = pattern.trim();
+        let trimmed = trimmed.strip_prefix("disk:").unwrap_or(trimmed);
+        if trimmed.is_empty() || trimmed.chars().all(|c| c == '/') {
+            return Err(format!("invalid pin rule pattern: {pattern:?}"));
+        }
+        let pattern = if trimmed.contains('/') && !trimmed.starts_with('/') {
+            format!("/{trimmed}")
+        } else {
+            trimmed.to_string()
+        };
+        Ok(Self {
+            pattern,
+            max_size: None,
+            max_age: None,
+        })
+    }
+
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Whether a file at `path` with the given size and modification time
+    /// (Unix seconds) falls under the rule. Unknown sizes or times never
+    /// satisfy a limit on them.
+    pub fn matches(&self, path: &str, size: Option<i64>, modified: Option<i64>, now: i64) -> bool {
+        if let Some(max_size) = self.max_size
+            && size
+                .and_then(|size| u64::try_from(size).ok())
+                .is_none_or(|size| size > max_size)
+        {
+            return false;
+        }
+        if let Some(max_age) = self.max_age {
+            let cutoff = now.saturating_sub(i64::try_from(max_age.as_secs()).unwrap_or(i64::MAX));
+            if modified.is_none_or(|modified| modified < cutoff) {
+                return false;
+            }
+        }
+        let path = path.strip_prefix("disk:").unwrap_or(path);
+        let path = format!("/{}", path.trim_start_matches('/'));
+        let pattern: Vec<char> = self.pattern.chars().collect();
+        if self.pattern.contains('/') {
+            glob_match(&pattern, &path.chars().collect::<Vec<_>>())
+        } else {
+            let name = path.rsplit('/').next().unwrap_or_default();
+            glob_match(&pattern, &name.chars().collect::<Vec<_>>())
+        }
+    }
+}
+
+/// Matches `text` against a glob where `?` and `*` stay within one path
+/// segment and `**` spans any number of them, including none.
+///
+/// Works from the end of the pattern: `matched[i][j]` tells whether
+/// `pattern[i..]` matches `text[j..]`, so every pair is settled once.
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    let mut matched = vec![vec![false; text.len() + 1]; pattern.len() + 1];
+    matched[pattern.len()][text.len()] = true;
+    for i in (0..pattern.len()).rev() {
+        if pattern[i..].starts_with(&['*', '*']) {
+            // `**/` may also match nothing at all, slash included.
+            let skip_slash = pattern.get(i + 2) == Some(&'/');
+            let mut any_rest = false;
+            for j in (0..=text.len()).rev() {
+                any_rest |= matched[i + 2][j];
+                matched[i][j] = any_rest || (skip_slash && matched[i + 3][j]);
+            }
+            continue;
+        }
+        for j in (0..=text.len()).rev() {
+            let next = text.get(j);
+            matched[i][j] = match pattern[i] {
+                '*' => matched[i + 1][j] || (next.is_some_and(|&c| c != '/') && matched[i][j + 1]),
+                '?' => next.is_some_and(|&c| c != '/') && matched[i + 1][j + 1],
+                expected => next == Some(&expected) && matched[i + 1][j + 1],
+            };
+        }
+    }
+    matched[0][0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    #[test]
+    fn path_patterns_match_whole_remote_paths() {
+        let rule = PinRule::new("disk:/Work/**").unwrap();
+        assert_eq!(rule.pattern, "/Work/**");
+        assert!(rule.matches("disk:/Work/report.odt", Some(1), None, 0));
+        assert!(rule.matches("/Work/2026/q3/report.odt", Some(1), None, 0));
+        assert!(!rule.matches("/Workshop/report.odt", Some(1), None, 0));
+        assert!(!rule.matches("/Home/Work/report.odt", Some(1), None, 0));
+
+        let rule = PinRule::new("/Projects/*/docs/**/*.md").unwrap();
+        assert!(rule.matches("/Projects/a/docs/README.md", None, None, 0));
+        assert!(rule.matches("/Projects/a/docs/x/y/notes.md", None, None, 0));
+        assert!(!rule.matches("/Projects/a/b/docs/README.md", None, None, 0));
+        assert!(!rule.matches("/Projects/a/docs/README.txt", None, None, 0));
+    }
+
+    #[test]
+    fn name_patterns_match_anywhere_within_limits() {
+        let rule = PinRule::new("*.pdf").unwrap().with_max_size(50 << 20);
+        assert!(rule.matches("/Papers/a.pdf", Some(10 << 20), None, 0));
+        assert!(rule.matches("/b.pdf", Some(50 << 20), None, 0));
+        assert!(!rule.matches("/Papers/big.pdf", Some(51 << 20), None, 0));
+        assert!(!rule.matches("/Papers/unknown.pdf", None, None, 0));
+        assert!(!rule.matches("/Papers/a.pdf.txt", Some(1), None, 0));
+
+        let rule = PinRule::new("report-????.odt").unwrap();
+        assert!(rule.matches("/Work/report-2026.odt", None, None, 0));
+        assert!(!rule.matches("/Work/report-26.odt", None, None, 0));
+    }
+
+    #[test]
+    fn age_limit_pins_recently_modified_files() {
+        let rule = PinRule::new("**")
+            .unwrap()
+            .with_max_age(Duration::from_secs(7 * DAY as u64));
+        let now = 30 * DAY;
+        assert!(rule.matches("/a.txt", None, Some(25 * DAY), now));
+        assert!(rule.matches("/deep/b.txt", None, Some(23 * DAY), now));
+        assert!(!rule.matches("/c.txt", None, Some(20 * DAY), now));
+        assert!(!rule.matches("/d.txt", None, None, now));
+    }
+
+    #[test]
+    fn many_wildcards_do_not_backtrack_exponentially() {
+        let rule = PinRule::new(&format!("{}x", "**/".repeat(12))).unwrap();
+        let deep = format!("/{}y", "d/".repeat(40));
+        assert!(!rule.matches(&deep, None, None, 0));
+        assert!(rule.matches("/x", None, None, 0));
+
+        let rule = PinRule::new(&format!("{}b", "*a".repeat(16))).unwrap();
+        assert!(!rule.matches(&format!("/{}", "a".repeat(60)), None, None, 0));
+        assert!(rule.matches(&format!("/{}b", "a".repeat(60)), None, None, 0));
+    }
+
+    #[test]
+    fn rejects_empty_patterns() {
+        assert!(PinRule::new("").is_err());
+        assert!(PinRule::new(" disk:/ ").is_err());
+        assert_eq!(PinRule::new("Work/*.odt").unwrap().pattern, "/Work/*.odt");
+    }
+}
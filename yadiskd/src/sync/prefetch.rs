@@ -0,0 +1,109 @@
+use super::index::{ItemRecord, ItemType};
+
+/// Queue priority of prefetch downloads: below uploads and on-demand
+/// downloads, so a file the user is waiting for never queues behind them.
+pub const PREFETCH_PRIORITY: i32 = -10;
+
+/// Read-ahead for viewers that step through a folder one file at a time:
+/// after a file is opened, the files following it are downloaded too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrefetchPolicy {
+    /// How many files after the opened one, in name order, to fetch.
+    pub siblings: usize,
+    /// Larger files are left for on-demand download; `0` means no limit.
+    pub max_size: u64,
+}
+
+impl Default for PrefetchPolicy {
+    fn default() -> Self {
+        Self {
+            siblings: 3,
+            max_size: 64 << 20,
+        }
+    }
+}
+
+impl PrefetchPolicy {
+    /// The files among `children` that follow `opened` within the policy
+    /// limits. Folders and files over the size limit are passed over without
+    /// counting towards `siblings`.
+    pub fn select<'a>(
+        &self,
+        opened: &ItemRecord,
+        children: &'a [ItemRecord],
+    ) -> Vec<&'a ItemRecord> {
+        let mut files: Vec<&ItemRecord> = children
+            .iter()
+            .filter(|child| child.item_type == ItemType::File)
+            .collect();
+        files.sort_by(|a, b| {
+            a.name
+                .to_lowercase()
+                .cmp(&b.name.to_lowercase())
+                .then(a.name.cmp(&b.name))
+        });
+        let Some(position) = files.iter().position(|file| file.id == opened.id) else {
+            return Vec::new();
+        };
+        files
+            .into_iter()
+            .skip(position + 1)
+            .filter(|file| {
+                self.max_size == 0
+                    || file.size.is_some_and(|size| {
+                        u64::try_from(size).is_ok_and(|size| size <= self.max_size)
+                    })
+            })
+            .take(self.siblings)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: i64, name: &str, item_type: ItemType, size: i64) -> ItemRecord {
+        ItemRecord {
+            id,
+            path: format!("/Album/{name}"),
+            parent_path: Some("/Album".into()),
+            name: name.into(),
+            item_type,
+            size: Some(size),
+            modified: None,
+            hash: None,
+            resource_id: None,
+            last_synced_hash: None,
+            last_synced_modified: None,
+        }
+    }
+
+    #[test]
+    fn selects_the_next_files_in_name_order() {
+        let children = [
+            record(1, "img_0003.jpg", ItemType::File, 10),
+            record(2, "IMG_0001.jpg", ItemType::File, 10),
+            record(3, "IMG_0002.jpg", ItemType::File, 10),
+            record(4, "Img_0002 edits", ItemType::Dir, 0),
+            record(5, "IMG_0004.raw", ItemType::File, 1_000),
+            record(6, "IMG_0005.jpg", ItemType::File, 10),
+        ];
+        let policy = PrefetchPolicy {
+            siblings: 2,
+            max_size: 100,
+        };
+        let ids =
+            |selected: Vec<&ItemRecord>| selected.iter().map(|item| item.id).collect::<Vec<_>>();
+        assert_eq!(ids(policy.select(&children[1], &children)), vec![3, 1]);
+        assert_eq!(ids(policy.select(&children[0], &children)), vec![6]);
+        assert!(policy.select(&children[5], &children).is_empty());
+        assert!(policy.select(&children[3], &children).is_empty());
+
+        let unlimited = PrefetchPolicy {
+            siblings: 5,
+            max_size: 0,
+        };
+        assert_eq!(ids(unlimited.select(&children[0], &children)), vec![5, 6]);
+    }
+}
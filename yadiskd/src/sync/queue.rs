@@ -1,7 +1,13 @@
 use std::collections::VecDeque;
+use std::str::FromStr;
 
 use thiserror::Error;
 
+/// Priority of downloads someone is waiting for, such as a file opened
+/// through the mount. They are the only operations that run while sync is
+/// paused.
+pub const ON_DEMAND_PRIORITY: i32 = 80;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[allow(dead_code)]
 pub enum OperationKind {
@@ -9,9 +15,72 @@ pub enum OperationKind {
     Download,
     Delete,
     Move,
+    Copy,
     Mkdir,
 }
 
+impl OperationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OperationKind::Upload => "upload",
+            OperationKind::Download => "download",
+            OperationKind::Delete => "delete",
+            OperationKind::Move => "move",
+            OperationKind::Copy => "copy",
+            OperationKind::Mkdir => "mkdir",
+        }
+    }
+}
+
+impl FromStr for OperationKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "upload" => Ok(OperationKind::Upload),
+            "download" => Ok(OperationKind::Download),
+            "delete" => Ok(OperationKind::Delete),
+            "move" => Ok(OperationKind::Move),
+            "copy" => Ok(OperationKind::Copy),
+            "mkdir" => Ok(OperationKind::Mkdir),
+            other => Err(other.to_string()),
+        }
+    }
+}
+
+/// Narrows a listing of queued operations to one kind and/or subtree.
+/// An empty filter matches everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OpFilter {
+    pub kind: Option<OperationKind>,
+    pub path_prefix: Option<String>,
+}
+
+impl OpFilter {
+    /// Builds a filter from an operation kind (`upload`) and a path prefix
+    /// (`/Docs`, `disk:/My Docs`); an empty string leaves either open.
+    pub fn new(kind: &str, path_prefix: &str) -> Result<Self, String> {
+        let kind = kind.trim();
+        let kind = if kind.is_empty() {
+            None
+        } else {
+            Some(
+                kind.to_ascii_lowercase()
+                    .parse()
+                    .map_err(|kind| format!("unknown operation kind: {kind}"))?,
+            )
+        };
+        let path_prefix = if path_prefix.is_empty() {
+            None
+        } else if path_prefix.starts_with('/') || path_prefix.starts_with("disk:") {
+            Some(path_prefix.to_string())
+        } else {
+            return Err(format!("invalid path prefix: {path_prefix}"));
+        };
+        Ok(Self { kind, path_prefix })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Operation {
     pub kind: OperationKind,
@@ -22,6 +91,37 @@ pub struct Operation {
     pub priority: i32,
 }
 
+impl Operation {
+    /// Paths the operation changes: its own path and, for moves and copies,
+    /// the source.
+    pub fn touched_paths(&self) -> Vec<String> {
+        let mut paths = vec![self.path.clone()];
+        if matches!(self.kind, OperationKind::Move | OperationKind::Copy)
+            && let Some(from) = self
+                .payload
+                .as_deref()
+                .and_then(|payload| serde_json::from_str::<serde_json::Value>(payload).ok())
+                .and_then(|payload| payload.get("from")?.as_str().map(str::to_string))
+        {
+            paths.push(from);
+        }
+        paths
+    }
+}
+
+/// Whether `a` and `b` are the same path or one lies inside the other.
+/// Operations on such paths must run in the order they were queued.
+pub fn paths_overlap(a: &str, b: &str) -> bool {
+    let a = a.strip_prefix("disk:").unwrap_or(a).trim_end_matches('/');
+    let b = b.strip_prefix("disk:").unwrap_or(b).trim_end_matches('/');
+    let contains = |outer: &str, inner: &str| {
+        inner
+            .strip_prefix(outer)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    };
+    contains(a, b) || contains(b, a)
+}
+
 #[derive(Debug, Error)]
 pub enum QueueError {
     #[error("operation queue is empty")]
@@ -59,6 +159,39 @@ impl OperationQueue {
 mod tests {
     use super::*;
 
+    #[test]
+    fn op_filter_takes_kind_and_prefix_separately() {
+        assert_eq!(OpFilter::new("", "").unwrap(), OpFilter::default());
+        assert_eq!(
+            OpFilter::new(" Upload ", "disk:/My Docs").unwrap(),
+            OpFilter {
+                kind: Some(OperationKind::Upload),
+                path_prefix: Some("disk:/My Docs".into()),
+            }
+        );
+        assert!(OpFilter::new("uploads", "").is_err());
+        assert!(OpFilter::new("", "Docs").is_err());
+    }
+
+    #[test]
+    fn overlapping_paths_cover_ancestors_and_move_sources() {
+        assert!(paths_overlap("/Docs", "disk:/Docs/a.txt"));
+        assert!(paths_overlap("/Docs/a.txt", "/Docs/"));
+        assert!(paths_overlap("/Docs", "/Docs"));
+        assert!(!paths_overlap("/Docs", "/Docs2/a.txt"));
+        assert!(!paths_overlap("/Docs/a.txt", "/Docs/b.txt"));
+
+        let op = Operation {
+            kind: OperationKind::Move,
+            path: "/New".into(),
+            payload: Some(r#"{"from":"/Old","path":"/New"}"#.into()),
+            attempt: 0,
+            retry_at: None,
+            priority: 0,
+        };
+        assert_eq!(op.touched_paths(), vec!["/New", "/Old"]);
+    }
+
     #[test]
     fn fifo_order_is_preserved() {
         let mut queue = OperationQueue::default();
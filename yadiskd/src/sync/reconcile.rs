@@ -0,0 +1,559 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use md5::Context;
+use tokio::io::AsyncReadExt;
+
+use super::index::{FileState, ItemType, LocalBaseline};
+use super::local_watcher::LocalEvent;
+use super::paths::is_ignored_temporary_name;
+
+/// Last known synced view of an indexed item, used as the comparison
+/// baseline when the local tree is rescanned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BaselineEntry {
+    pub path: String,
+    pub item_type: ItemType,
+    pub state: Option<FileState>,
+    pub size: Option<i64>,
+    pub last_synced_hash: Option<String>,
+    pub last_synced_modified: Option<i64>,
+    /// Whether the item was ever found on disk. Items indexed from the cloud
+    /// but not materialized yet are missing locally without being deleted.
+    pub seen_locally: bool,
+    /// The sync-folder copy as the daemon last wrote or observed it. A file
+    /// that still matches it was not edited locally, even when the cloud
+    /// listing has moved on and the download has not landed yet.
+    pub local: Option<LocalBaseline>,
+}
+
+/// `(size, mtime, baseline hash)` of files already verified against the
+/// baseline, so periodic passes only re-hash files that were touched.
+pub type FingerprintCache = HashMap<String, (u64, i64, Option<String>)>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LocalEntry {
+    path: String,
+    local: PathBuf,
+    is_dir: bool,
+    /// Symlinks are never followed; they count as present so the cloud copy
+    /// is kept, and the symlink policy decides what a new one becomes.
+    is_link: bool,
+    size: u64,
+    modified: i64,
+}
+
+/// Walks `scope` (a directory inside `sync_root`) and returns the local
+/// events needed to bring the cloud in line with changes that were never
+/// observed by the watcher: new and modified files, deletions and moves.
+pub async fn reconcile_local_tree(
+    sync_root: &Path,
+    scope: &Path,
+    baseline: &[BaselineEntry],
+    fingerprints: &mut FingerprintCache,
+) -> std::io::Result<Vec<LocalEvent>> {
+    let Some(scope_remote) = to_slash_path(sync_root, scope) else {
+        return Ok(Vec::new());
+    };
+    match tokio::fs::symlink_metadata(scope).await {
+        Ok(meta) if meta.is_dir() => {}
+        Ok(_) => return Ok(Vec::new()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    }
+
+    let walk_root = sync_root.to_path_buf();
+    let walk_scope = scope.to_path_buf();
+    let local = tokio::task::spawn_blocking(move || walk_local_tree(&walk_root, &walk_scope))
+        .await
+        .map_err(std::io::Error::other)??;
+    let local_by_path: HashMap<&str, &LocalEntry> = local
+        .iter()
+        .map(|entry| (entry.path.as_str(), entry))
+        .collect();
+
+    let baseline_by_path: HashMap<String, &BaselineEntry> = baseline
+        .iter()
+        .map(|entry| (slash_form(&entry.path), entry))
+        .filter(|(path, _)| is_within(path, &scope_remote))
+        .collect();
+
+    let mut mkdirs = Vec::new();
+    let mut new_files = Vec::new();
+    let mut new_links = Vec::new();
+    let mut uploads = Vec::new();
+    for entry in &local {
+        let Some(base) = baseline_by_path.get(&entry.path) else {
+            if entry.is_link {
+                new_links.push(entry.path.clone());
+            } else if entry.is_dir {
+                mkdirs.push(entry.path.clone());
+            } else if entry.size > 0 {
+                // Untracked empty files are indistinguishable from stale
+                // placeholders, so only files with content are picked up.
+                new_files.push(entry);
+            }
+            continue;
+        };
+        if entry.is_dir || entry.is_link || base.item_type == ItemType::Dir {
+            continue;
+        }
+        if file_changed_since_baseline(entry, base, fingerprints).await? {
+            uploads.push(entry.path.clone());
+        }
+    }
+
+    let missing: HashSet<&str> = baseline_by_path
+        .iter()
+        .filter(|(path, base)| {
+            path.as_str() != scope_remote
+                && base.seen_locally
+                && !local_by_path.contains_key(path.as_str())
+                && !matches!(base.state, Some(FileState::Syncing | FileState::Error))
+        })
+        .map(|(path, _)| path.as_str())
+        .collect();
+    let mut deletes: Vec<String> = missing
+        .iter()
+        .filter(|path| {
+            parent_of(path)
+                .map(|parent| !missing.contains(parent.as_str()))
+                .unwrap_or(true)
+        })
+        .map(|path| path.to_string())
+        .collect();
+    deletes.sort();
+
+    let mut moves = Vec::new();
+    let mut moved_sources = HashSet::new();
+    let mut moved_targets = HashSet::new();
+    for entry in &new_files {
+        let candidates: Vec<&String> = deletes
+            .iter()
+            .filter(|path| !moved_sources.contains(*path))
+            .filter(|path| {
+                baseline_by_path.get(*path).is_some_and(|base| {
+                    base.item_type == ItemType::File
+                        && base.size == Some(entry.size as i64)
+                        && base.last_synced_hash.is_some()
+                })
+            })
+            .collect();
+        if candidates.is_empty() {
+            continue;
+        }
+        let hash = file_md5_hex(&entry.local).await?;
+        let matches: Vec<&String> = candidates
+            .into_iter()
+            .filter(|path| {
+                baseline_by_path
+                    .get(*path)
+                    .and_then(|base| base.last_synced_hash.as_deref())
+                    .is_some_and(|base_hash| base_hash.eq_ignore_ascii_case(&hash))
+            })
+            .collect();
+        if let [from] = matches.as_slice() {
+            moved_sources.insert((*from).clone());
+            moved_targets.insert(entry.path.clone());
+            moves.push(LocalEvent::Move {
+                from: (*from).clone(),
+                to: entry.path.clone(),
+            });
+        }
+    }
+
+    mkdirs.sort();
+    let mut events: Vec<LocalEvent> = mkdirs
+        .into_iter()
+        .map(|path| LocalEvent::Mkdir { path })
+        .collect();
+    events.extend(moves);
+    events.extend(
+        new_files
+            .iter()
+            .filter(|entry| !moved_targets.contains(&entry.path))
+            .map(|entry| LocalEvent::Upload {
+                path: entry.path.clone(),
+            }),
+    );
+    events.extend(
+        new_links
+            .into_iter()
+            .chain(uploads)
+            .map(|path| LocalEvent::Upload { path }),
+    );
+    events.extend(
+        deletes
+            .into_iter()
+            .filter(|path| !moved_sources.contains(path))
+            .map(|path| LocalEvent::Delete { path }),
+    );
+    Ok(events)
+}
+
+async fn file_changed_since_baseline(
+    entry: &LocalEntry,
+    base: &BaselineEntry,
+    fingerprints: &mut FingerprintCache,
+) -> std::io::Result<bool> {
+    match base.state {
+        Some(FileState::Syncing | FileState::Error) => return Ok(false),
+        // Cloud-only files are materialized as empty placeholders.
+        Some(FileState::CloudOnly) | None => return Ok(entry.size > 0),
+        Some(FileState::Cached) => {}
+    }
+    if base.local.is_some_and(|local| {
+        local.size == entry.size && local.modified == entry.modified
+    }) {
+        return Ok(false);
+    }
+    if base.size.is_some_and(|size| size != entry.size as i64) {
+        fingerprints.remove(&entry.path);
+        return Ok(true);
+    }
+    if base.last_synced_modified == Some(entry.modified) {
+        return Ok(false);
+    }
+    let Some(base_hash) = base.last_synced_hash.as_deref() else {
+        return Ok(false);
+    };
+    let fingerprint = (entry.size, entry.modified, base.last_synced_hash.clone());
+    if fingerprints.get(&entry.path) == Some(&fingerprint) {
+        return Ok(false);
+    }
+    let hash = file_md5_hex(&entry.local).await?;
+    if hash.eq_ignore_ascii_case(base_hash) {
+        fingerprints.insert(entry.path.clone(), fingerprint);
+        return Ok(false);
+    }
+    fingerprints.remove(&entry.path);
+    Ok(true)
+}
+
+fn walk_local_tree(sync_root: &Path, scope: &Path) -> std::io::Result<Vec<LocalEntry>> {
+    let mut out = Vec::new();
+    let mut stack = vec![scope.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+            let ignored = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(is_ignored_temporary_name);
+            if ignored {
+                continue;
+            }
+            let Ok(meta) = std::fs::symlink_metadata(&path) else {
+                continue;
+            };
+            if !meta.is_dir() && !meta.is_file() && !meta.is_symlink() {
+                continue;
+            }
+            let Some(remote) = to_slash_path(sync_root, &path) else {
+                continue;
+            };
+            let modified = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0);
+            if meta.is_dir() {
+                stack.push(path.clone());
+            }
+            out.push(LocalEntry {
+                path: remote,
+                local: path,
+                is_dir: meta.is_dir(),
+                is_link: meta.is_symlink(),
+                size: if meta.is_dir() { 0 } else { meta.len() },
+                modified,
+            });
+        }
+    }
+    out.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(out)
+}
+
+async fn file_md5_hex(path: &Path) -> std::io::Result<String> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = Context::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.consume(&buf[..read]);
+    }
+    Ok(format!("{:x}", hasher.compute()))
+}
+
+fn to_slash_path(sync_root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(sync_root).ok()?;
+    let remote = PathBuf::from("/").join(relative);
+    Some(remote.to_string_lossy().replace('\\', "/"))
+}
+
+fn slash_form(path: &str) -> String {
+    match path.strip_prefix("disk:/") {
+        Some(rest) => format!("/{}", rest.trim_start_matches('/')),
+        None => path.to_string(),
+    }
+}
+
+fn is_within(path: &str, scope: &str) -> bool {
+    if scope == "/" {
+        return path != "/";
+    }
+    path == scope
+        || path
+            .strip_prefix(scope)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+fn parent_of(path: &str) -> Option<String> {
+    let idx = path.rfind('/')?;
+    if idx == 0 {
+        return None;
+    }
+    Some(path[..idx].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn md5_of(bytes: &[u8]) -> String {
+        format!("{:x}", md5::compute(bytes))
+    }
+
+    fn file(path: &str, state: FileState, content: &[u8]) -> BaselineEntry {
+        BaselineEntry {
+            path: path.into(),
+            item_type: ItemType::File,
+            state: Some(state),
+            size: Some(content.len() as i64),
+            last_synced_hash: Some(md5_of(content)),
+            last_synced_modified: Some(1),
+            seen_locally: true,
+            local: None,
+        }
+    }
+
+    fn dir(path: &str) -> BaselineEntry {
+        BaselineEntry {
+            path: path.into(),
+            item_type: ItemType::Dir,
+            state: None,
+            size: None,
+            last_synced_hash: None,
+            last_synced_modified: None,
+            seen_locally: true,
+            local: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn detects_offline_edit_new_file_and_delete() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("Docs")).unwrap();
+        std::fs::write(root.join("Docs/Edited.txt"), b"edited offline").unwrap();
+        std::fs::write(root.join("Docs/Same.txt"), b"same").unwrap();
+        std::fs::write(root.join("Docs/New.txt"), b"brand new").unwrap();
+        let baseline = vec![
+            dir("disk:/Docs"),
+            file("disk:/Docs/Edited.txt", FileState::Cached, b"original"),
+            file("disk:/Docs/Same.txt", FileState::Cached, b"same"),
+            file("disk:/Docs/Gone.txt", FileState::Cached, b"gone"),
+        ];
+
+        let mut fingerprints = FingerprintCache::new();
+        let events = reconcile_local_tree(root, root, &baseline, &mut fingerprints)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            events,
+            vec![
+                LocalEvent::Upload {
+                    path: "/Docs/New.txt".into()
+                },
+                LocalEvent::Upload {
+                    path: "/Docs/Edited.txt".into()
+                },
+                LocalEvent::Delete {
+                    path: "/Docs/Gone.txt".into()
+                },
+            ]
+        );
+        assert!(fingerprints.contains_key("/Docs/Same.txt"));
+    }
+
+    #[tokio::test]
+    async fn pairs_missing_and_new_file_with_same_content_into_move() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("Archive")).unwrap();
+        std::fs::write(root.join("Archive/Photo.jpg"), b"pixels").unwrap();
+        let baseline = vec![
+            dir("/Archive"),
+            file("/Photo.jpg", FileState::Cached, b"pixels"),
+        ];
+
+        let events = reconcile_local_tree(root, root, &baseline, &mut FingerprintCache::new())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            events,
+            vec![LocalEvent::Move {
+                from: "/Photo.jpg".into(),
+                to: "/Archive/Photo.jpg".into()
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_only_topmost_missing_directory() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("Keep")).unwrap();
+        let baseline = vec![
+            dir("/Keep"),
+            dir("/Old"),
+            dir("/Old/Nested"),
+            file("/Old/Nested/A.txt", FileState::CloudOnly, b""),
+        ];
+
+        let events = reconcile_local_tree(root, root, &baseline, &mut FingerprintCache::new())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            events,
+            vec![LocalEvent::Delete {
+                path: "/Old".into()
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn ignores_placeholders_pending_items_and_untracked_empty_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        std::fs::write(root.join("Placeholder.txt"), b"").unwrap();
+        std::fs::write(root.join("Empty.txt"), b"").unwrap();
+        std::fs::write(root.join(".goutputstream-X1"), b"tmp").unwrap();
+        let baseline = vec![
+            file("/Placeholder.txt", FileState::CloudOnly, b"remote content"),
+            file("/Uploading.txt", FileState::Syncing, b"pending"),
+        ];
+
+        let events = reconcile_local_tree(root, root, &baseline, &mut FingerprintCache::new())
+            .await
+            .unwrap();
+
+        assert!(events.is_empty(), "unexpected events: {events:?}");
+    }
+
+    #[tokio::test]
+    async fn scoped_scan_only_reports_changes_inside_scope() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("A")).unwrap();
+        std::fs::create_dir_all(root.join("B")).unwrap();
+        std::fs::write(root.join("A/new.txt"), b"a").unwrap();
+        std::fs::write(root.join("B/new.txt"), b"b").unwrap();
+        let baseline = vec![
+            dir("/A"),
+            dir("/B"),
+            file("/B/gone.txt", FileState::Cached, b"x"),
+        ];
+
+        let events = reconcile_local_tree(
+            root,
+            &root.join("A"),
+            &baseline,
+            &mut FingerprintCache::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            events,
+            vec![LocalEvent::Upload {
+                path: "/A/new.txt".into()
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn symlinks_are_kept_and_only_new_ones_reported() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("Docs")).unwrap();
+        std::fs::write(root.join("Docs/Real.txt"), b"real").unwrap();
+        std::os::unix::fs::symlink("Real.txt", root.join("Docs/Stored")).unwrap();
+        std::os::unix::fs::symlink("Missing.txt", root.join("Docs/New")).unwrap();
+        let baseline = vec![
+            dir("/Docs"),
+            file("/Docs/Real.txt", FileState::Cached, b"real"),
+            file("/Docs/Stored", FileState::Cached, b"Real.txt"),
+        ];
+
+        let events = reconcile_local_tree(root, root, &baseline, &mut FingerprintCache::new())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            events,
+            vec![LocalEvent::Upload {
+                path: "/Docs/New".into()
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn items_never_seen_on_disk_are_not_deleted() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("Docs")).unwrap();
+        let fresh = BaselineEntry {
+            seen_locally: false,
+            ..file(
+                "/Docs/FromCloud.txt",
+                FileState::CloudOnly,
+                b"new in the cloud",
+            )
+        };
+        let fresh_dir = BaselineEntry {
+            seen_locally: false,
+            ..dir("/Shared")
+        };
+        let baseline = vec![
+            dir("/Docs"),
+            fresh,
+            fresh_dir,
+            file("/Docs/Removed.txt", FileState::CloudOnly, b""),
+        ];
+
+        let events = reconcile_local_tree(root, root, &baseline, &mut FingerprintCache::new())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            events,
+            vec![LocalEvent::Delete {
+                path: "/Docs/Removed.txt".into()
+            }]
+        );
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use super::paths::cache_path_for;
+
+/// How long `/proc` scans for open writers are reused; a burst of queued
+/// uploads shares one scan instead of walking every process per file.
+const OPEN_WRITERS_TTL: Duration = Duration::from_secs(1);
+
+/// Settle window for uploads of files that are still being written locally.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteSettle {
+    pub sync_root: PathBuf,
+    /// The file must be unchanged for this long before it is uploaded.
+    pub quiet: Duration,
+    /// Upper bound on deferral so continuously written files still sync.
+    pub max_wait: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettleDecision {
+    Ready,
+    Defer(Duration),
+}
+
+impl WriteSettle {
+    pub fn local_path_for(&self, remote_path: &str) -> Option<PathBuf> {
+        let relative = remote_path.strip_prefix("disk:").unwrap_or(remote_path);
+        cache_path_for(&self.sync_root, relative).ok()
+    }
+
+    pub fn decide(
+        &self,
+        since_modified: Duration,
+        open_for_write: bool,
+        waited: Duration,
+    ) -> SettleDecision {
+        if waited >= self.max_wait {
+            return SettleDecision::Ready;
+        }
+        if since_modified >= self.quiet && !open_for_write {
+            return SettleDecision::Ready;
+        }
+        let remaining = self
+            .quiet
+            .saturating_sub(since_modified)
+            .max(Duration::from_secs(1))
+            .min(self.max_wait.saturating_sub(waited));
+        SettleDecision::Defer(remaining)
+    }
+}
+
+/// Per-path bookkeeping for uploads deferred by [`WriteSettle`].
+#[derive(Debug)]
+pub struct UploadSettler {
+    config: WriteSettle,
+    first_deferred: Mutex<HashMap<String, Instant>>,
+    open_writers: Mutex<Option<(Instant, Arc<HashSet<PathBuf>>)>>,
+}
+
+impl UploadSettler {
+    pub fn new(config: WriteSettle) -> Self {
+        Self {
+            config,
+            first_deferred: Mutex::new(HashMap::new()),
+            open_writers: Mutex::new(None),
+        }
+    }
+
+    /// Returns how long the upload of `remote_path` should wait, or `None`
+    /// when the local file has settled (or is gone) and can be sent now.
+    pub async fn pending_delay(&self, remote_path: &str) -> Option<Duration> {
+        let local = self.config.local_path_for(remote_path)?;
+        let Ok(meta) = tokio::fs::symlink_metadata(&local).await else {
+            self.forget(remote_path);
+            return None;
+        };
+        if !meta.is_file() {
+            self.forget(remote_path);
+            return None;
+        }
+        let since_modified = meta
+            .modified()
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .unwrap_or_default();
+        let open_for_write = self.open_writers().await.contains(&local);
+        let waited = {
+            let mut first = self
+                .first_deferred
+                .lock()
+                .unwrap_or_else(|err| err.into_inner());
+            first
+                .entry(remote_path.to_string())
+                .or_insert_with(Instant::now)
+                .elapsed()
+        };
+        match self.config.decide(since_modified, open_for_write, waited) {
+            SettleDecision::Ready => {
+                self.forget(remote_path);
+                None
+            }
+            SettleDecision::Defer(delay) => Some(delay),
+        }
+    }
+
+    fn forget(&self, remote_path: &str) {
+        self.first_deferred
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(remote_path);
+    }
+
+    async fn open_writers(&self) -> Arc<HashSet<PathBuf>> {
+        if let Some((scanned_at, writers)) = self
+            .open_writers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .as_ref()
+            && scanned_at.elapsed() < OPEN_WRITERS_TTL
+        {
+            return Arc::clone(writers);
+        }
+        let root = self.config.sync_root.clone();
+        let writers =
+            tokio::task::spawn_blocking(move || scan_open_writers(Path::new("/proc"), &root))
+                .await
+                .map(Arc::new)
+                .unwrap_or_default();
+        *self
+            .open_writers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner()) = Some((Instant::now(), Arc::clone(&writers)));
+        writers
+    }
+}
+
+/// Files under `root` that some process holds open with write access,
+/// read from `/proc/<pid>/fd` links and the access mode in `fdinfo`.
+/// Processes of other users are unreadable and silently skipped.
+fn scan_open_writers(proc_root: &Path, root: &Path) -> HashSet<PathBuf> {
+    scan_open_files(proc_root, root, true)
+}
+
+/// Files under `root` that some process holds open in any mode.
+pub fn open_files_under(root: &Path) -> HashSet<PathBuf> {
+    scan_open_files(Path::new("/proc"), root, false)
+}
+
+fn scan_open_files(proc_root: &Path, root: &Path, writers_only: bool) -> HashSet<PathBuf> {
+    let mut open = HashSet::new();
+    let Ok(processes) = std::fs::read_dir(proc_root) else {
+        return open;
+    };
+    for process in processes.flatten() {
+        let pid = process.file_name();
+        if !pid.to_string_lossy().bytes().all(|b| b.is_ascii_digit()) {
+            continue;
+        }
+        let Ok(fds) = std::fs::read_dir(process.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let Ok(target) = std::fs::read_link(fd.path()) else {
+                continue;
+            };
+            if !target.starts_with(root) || open.contains(&target) {
+                continue;
+            }
+            if !writers_only {
+                open.insert(target);
+                continue;
+            }
+            let info = process.path().join("fdinfo").join(fd.file_name());
+            if std::fs::read_to_string(info)
+                .ok()
+                .and_then(|info| parse_fdinfo_flags(&info))
+                .is_some_and(|flags| flags & 0o3 != 0)
+            {
+                open.insert(target);
+            }
+        }
+    }
+    open
+}
+
+fn parse_fdinfo_flags(info: &str) -> Option<u32> {
+    info.lines()
+        .find_map(|line| line.strip_prefix("flags:"))
+        .and_then(|flags| u32::from_str_radix(flags.trim(), 8).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settle() -> WriteSettle {
+        WriteSettle {
+            sync_root: PathBuf::from("/tmp/root"),
+            quiet: Duration::from_secs(3),
+            max_wait: Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn recently_modified_file_is_deferred_for_remaining_window() {
+        assert_eq!(
+            settle().decide(Duration::from_secs(1), false, Duration::ZERO),
+            SettleDecision::Defer(Duration::from_secs(2))
+        );
+        assert_eq!(
+            settle().decide(Duration::from_secs(5), false, Duration::from_secs(5)),
+            SettleDecision::Ready
+        );
+    }
+
+    #[test]
+    fn open_writer_defers_until_max_wait() {
+        assert!(matches!(
+            settle().decide(Duration::from_secs(30), true, Duration::from_secs(10)),
+            SettleDecision::Defer(_)
+        ));
+        assert_eq!(
+            settle().decide(Duration::ZERO, true, Duration::from_secs(60)),
+            SettleDecision::Ready
+        );
+    }
+
+    #[test]
+    fn maps_disk_prefixed_paths_under_sync_root() {
+        assert_eq!(
+            settle().local_path_for("disk:/Docs/A.txt"),
+            Some(PathBuf::from("/tmp/root/Docs/A.txt"))
+        );
+        assert_eq!(
+            settle().local_path_for("/Docs/A.txt"),
+            Some(PathBuf::from("/tmp/root/Docs/A.txt"))
+        );
+    }
+
+    #[test]
+    fn parses_access_mode_from_fdinfo() {
+        assert_eq!(
+            parse_fdinfo_flags("pos:\t0\nflags:\t0100002\nmnt_id:\t25\n"),
+            Some(0o100002)
+        );
+        assert_eq!(parse_fdinfo_flags("pos:\t0\n"), None);
+    }
+
+    #[tokio::test]
+    async fn file_held_open_for_write_is_deferred() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let path = root.join("Video.mkv");
+        let writer = std::fs::File::create(&path).unwrap();
+        let settler = UploadSettler::new(WriteSettle {
+            sync_root: root.clone(),
+            quiet: Duration::ZERO,
+            max_wait: Duration::from_secs(60),
+        });
+        assert!(settler.pending_delay("disk:/Video.mkv").await.is_some());
+
+        drop(writer);
+        *settler.open_writers.lock().unwrap() = None;
+        assert!(settler.pending_delay("disk:/Video.mkv").await.is_none());
+        assert!(settler.pending_delay("disk:/Missing.mkv").await.is_none());
+    }
+
+    #[test]
+    fn open_readers_are_listed_but_not_as_writers() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let path = root.join("Photo.jpg");
+        std::fs::write(&path, b"pixels").unwrap();
+        let reader = std::fs::File::open(&path).unwrap();
+
+        assert!(open_files_under(&root).contains(&path));
+        assert!(!scan_open_writers(Path::new("/proc"), &root).contains(&path));
+        drop(reader);
+        assert!(!open_files_under(&root).contains(&path));
+    }
+}
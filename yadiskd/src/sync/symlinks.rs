@@ -0,0 +1,61 @@
+use std::str::FromStr;
+
+/// Custom property marking an uploaded file as a stored symlink; its value
+/// is the link target.
+pub const SYMLINK_PROPERTY: &str = "yadisk_symlink";
+
+/// What happens to symlinks found in the sync folder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymlinkPolicy {
+    /// Leave symlinks out of the sync without reporting them.
+    #[default]
+    Skip,
+    /// Upload the content of the file the link points to.
+    Follow,
+    /// Upload a small file holding the target and recreate the symlink
+    /// when it is downloaded.
+    Link,
+}
+
+impl SymlinkPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SymlinkPolicy::Skip => "skip",
+            SymlinkPolicy::Follow => "follow",
+            SymlinkPolicy::Link => "link",
+        }
+    }
+}
+
+impl FromStr for SymlinkPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "skip" | "ignore" => Ok(SymlinkPolicy::Skip),
+            "follow" => Ok(SymlinkPolicy::Follow),
+            "link" | "store" => Ok(SymlinkPolicy::Link),
+            other => Err(format!("unknown symlink policy: {other}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_policy_names() {
+        assert_eq!("skip".parse(), Ok(SymlinkPolicy::Skip));
+        assert_eq!(" Follow ".parse(), Ok(SymlinkPolicy::Follow));
+        assert_eq!("LINK".parse(), Ok(SymlinkPolicy::Link));
+        assert!("copy".parse::<SymlinkPolicy>().is_err());
+        for policy in [
+            SymlinkPolicy::Skip,
+            SymlinkPolicy::Follow,
+            SymlinkPolicy::Link,
+        ] {
+            assert_eq!(policy.as_str().parse(), Ok(policy));
+        }
+    }
+}
@@ -191,6 +191,24 @@ impl TransferClient {
         Ok(())
     }
 
+    /// Uploads a small payload held in memory, such as a stored symlink.
+    pub async fn upload_bytes(&self, href: &str, content: Vec<u8>) -> Result<(), TransferError> {
+        let _permit = self
+            .upload_limit
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| TransferError::ConcurrencyClosed)?;
+        let url = Url::parse(href)?;
+        self.http
+            .put(url)
+            .body(content)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
     #[allow(dead_code)]
     pub fn with_http(http: Client) -> Self {
         let config = TransferConfig::default();
@@ -0,0 +1,206 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use notify::event::CreateKind;
+use notify::{Event, EventKind};
+
+/// Upper bound on distinct dirty subtrees; past it the whole root is
+/// rescanned instead of walking many small scopes one by one.
+const MAX_DIRTY_SCOPES: usize = 64;
+
+/// Tracks the parts of the sync root whose watcher events may be incomplete
+/// (queue overflow, missed watches) and therefore need a reconciliation scan.
+#[derive(Debug)]
+pub struct WatchHealth {
+    root: PathBuf,
+    dirty: Mutex<DirtyScopes>,
+    watch_limit_reached: AtomicBool,
+}
+
+#[derive(Debug, Default)]
+struct DirtyScopes {
+    scopes: Vec<PathBuf>,
+    last_marked: Option<Instant>,
+}
+
+impl WatchHealth {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            dirty: Mutex::new(DirtyScopes::default()),
+            watch_limit_reached: AtomicBool::new(false),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn mark_dirty(&self, scope: &Path) {
+        let scope = if scope.starts_with(&self.root) {
+            scope.to_path_buf()
+        } else {
+            self.root.clone()
+        };
+        let mut dirty = self.dirty.lock().unwrap_or_else(|err| err.into_inner());
+        dirty.last_marked = Some(Instant::now());
+        if dirty
+            .scopes
+            .iter()
+            .any(|existing| scope.starts_with(existing))
+        {
+            return;
+        }
+        dirty
+            .scopes
+            .retain(|existing| !existing.starts_with(&scope));
+        dirty.scopes.push(scope);
+        if dirty.scopes.len() > MAX_DIRTY_SCOPES {
+            dirty.scopes = vec![self.root.clone()];
+        }
+    }
+
+    pub fn has_dirty(&self) -> bool {
+        let dirty = self.dirty.lock().unwrap_or_else(|err| err.into_inner());
+        !dirty.scopes.is_empty()
+    }
+
+    /// Returns the dirty scopes once no new ones were marked for `settle`,
+    /// so a burst such as an archive extraction is scanned after it ends.
+    pub fn take_settled(&self, settle: Duration) -> Vec<PathBuf> {
+        let mut dirty = self.dirty.lock().unwrap_or_else(|err| err.into_inner());
+        let settled = dirty
+            .last_marked
+            .is_none_or(|marked| marked.elapsed() >= settle);
+        if !settled {
+            return Vec::new();
+        }
+        dirty.last_marked = None;
+        std::mem::take(&mut dirty.scopes)
+    }
+
+    pub fn clear(&self) {
+        let mut dirty = self.dirty.lock().unwrap_or_else(|err| err.into_inner());
+        *dirty = DirtyScopes::default();
+    }
+
+    pub fn set_watch_limit_reached(&self, reached: bool) {
+        self.watch_limit_reached.store(reached, Ordering::SeqCst);
+    }
+
+    pub fn watch_limit_reached(&self) -> bool {
+        self.watch_limit_reached.load(Ordering::SeqCst)
+    }
+}
+
+/// Subtrees whose contents the event stream cannot describe: the whole tree
+/// after a queue overflow, and freshly created directories, whose children
+/// may have been written before the watch on them was in place.
+pub fn rescan_scopes(root: &Path, event: &Event) -> Vec<PathBuf> {
+    if event.need_rescan() {
+        if event.paths.is_empty() {
+            return vec![root.to_path_buf()];
+        }
+        return event.paths.clone();
+    }
+    if !matches!(
+        event.kind,
+        EventKind::Create(CreateKind::Folder | CreateKind::Any)
+    ) {
+        return Vec::new();
+    }
+    event
+        .paths
+        .iter()
+        .filter(|path| path.starts_with(root))
+        .filter(|path| {
+            std::fs::symlink_metadata(path)
+                .map(|meta| meta.is_dir())
+                .unwrap_or(false)
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::Flag;
+
+    #[test]
+    fn nested_scopes_collapse_into_ancestor() {
+        let health = WatchHealth::new(PathBuf::from("/tmp/root"));
+        health.mark_dirty(Path::new("/tmp/root/Docs/A"));
+        health.mark_dirty(Path::new("/tmp/root/Docs"));
+        health.mark_dirty(Path::new("/tmp/root/Docs/B"));
+        health.mark_dirty(Path::new("/tmp/root/Music"));
+        assert_eq!(
+            health.take_settled(Duration::ZERO),
+            vec![
+                PathBuf::from("/tmp/root/Docs"),
+                PathBuf::from("/tmp/root/Music")
+            ]
+        );
+        assert!(!health.has_dirty());
+    }
+
+    #[test]
+    fn paths_outside_root_mark_whole_root() {
+        let health = WatchHealth::new(PathBuf::from("/tmp/root"));
+        health.mark_dirty(Path::new("/tmp/root/Docs"));
+        health.mark_dirty(Path::new("/tmp/other"));
+        assert_eq!(
+            health.take_settled(Duration::ZERO),
+            vec![PathBuf::from("/tmp/root")]
+        );
+    }
+
+    #[test]
+    fn too_many_scopes_fall_back_to_full_rescan() {
+        let health = WatchHealth::new(PathBuf::from("/tmp/root"));
+        for idx in 0..=MAX_DIRTY_SCOPES {
+            health.mark_dirty(&PathBuf::from(format!("/tmp/root/dir{idx}")));
+        }
+        assert_eq!(
+            health.take_settled(Duration::ZERO),
+            vec![PathBuf::from("/tmp/root")]
+        );
+    }
+
+    #[test]
+    fn scopes_are_held_until_marks_settle() {
+        let health = WatchHealth::new(PathBuf::from("/tmp/root"));
+        health.mark_dirty(Path::new("/tmp/root/Docs"));
+        assert!(health.take_settled(Duration::from_secs(60)).is_empty());
+        assert!(health.has_dirty());
+        assert_eq!(
+            health.take_settled(Duration::ZERO),
+            vec![PathBuf::from("/tmp/root/Docs")]
+        );
+    }
+
+    #[test]
+    fn overflow_event_requests_full_rescan() {
+        let root = Path::new("/tmp/root");
+        let event = Event::new(EventKind::Other).set_flag(Flag::Rescan);
+        assert_eq!(rescan_scopes(root, &event), vec![root.to_path_buf()]);
+    }
+
+    #[test]
+    fn created_directories_are_rescanned() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let dir = root.join("Archive");
+        let file = root.join("A.txt");
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(&file, b"x").unwrap();
+
+        let created = Event::new(EventKind::Create(CreateKind::Folder)).add_path(dir.clone());
+        assert_eq!(rescan_scopes(root, &created), vec![dir]);
+
+        let file_created = Event::new(EventKind::Create(CreateKind::File)).add_path(file);
+        assert!(rescan_scopes(root, &file_created).is_empty());
+    }
+}
@@ -1,4 +1,5 @@
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use anyhow::Context;
 use tokio::sync::mpsc;
@@ -6,12 +7,16 @@ use yadisk_integrations::i18n::{product_name, sync_with_saved_language, tr};
 use yadisk_integrations::ids::{APP_ID_GTK, DBUS_NAME_SYNC};
 
 use crate::daemon::resolve_sync_root_from_env;
+use crate::pause::{PauseState, now_unix};
+
+const TRAY_PAUSE_FOR_SECS: i64 = 60 * 60;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TraySyncState {
     Normal,
     Syncing,
     Error,
+    Paused,
 }
 
 impl TraySyncState {
@@ -20,6 +25,7 @@ impl TraySyncState {
             Self::Normal => "normal",
             Self::Syncing => "syncing",
             Self::Error => "error",
+            Self::Paused => "paused",
         }
     }
 }
@@ -36,6 +42,7 @@ impl TrayController {
 
 pub fn start_status_tray(
     quit_tx: mpsc::UnboundedSender<()>,
+    pause: Arc<PauseState>,
 ) -> anyhow::Result<Option<TrayController>> {
     if read_bool_env("YADISK_DISABLE_STATUS_TRAY", false) {
         return Ok(None);
@@ -51,6 +58,7 @@ pub fn start_status_tray(
         state: TraySyncState::Normal,
         icon_theme_path,
         quit_tx,
+        pause,
     };
     let service = ksni::TrayService::new(tray);
     let handle = service.handle();
@@ -112,6 +120,7 @@ struct YadiskTray {
     state: TraySyncState,
     icon_theme_path: String,
     quit_tx: mpsc::UnboundedSender<()>,
+    pause: Arc<PauseState>,
 }
 
 impl ksni::Tray for YadiskTray {
@@ -135,7 +144,38 @@ impl ksni::Tray for YadiskTray {
     fn menu(&self) -> Vec<ksni::menu::MenuItem<Self>> {
         use ksni::menu::StandardItem;
         sync_with_saved_language();
-        vec![
+        let pause_items: Vec<ksni::menu::MenuItem<Self>> = if self.state == TraySyncState::Paused {
+            vec![
+                StandardItem {
+                    label: tr("Resume Sync"),
+                    activate: Box::new(|tray: &mut Self| {
+                        tray.pause.resume();
+                    }),
+                    ..Default::default()
+                }
+                .into(),
+            ]
+        } else {
+            vec![
+                StandardItem {
+                    label: tr("Pause Sync"),
+                    activate: Box::new(|tray: &mut Self| {
+                        tray.pause.pause();
+                    }),
+                    ..Default::default()
+                }
+                .into(),
+                StandardItem {
+                    label: tr("Pause for 1 Hour"),
+                    activate: Box::new(|tray: &mut Self| {
+                        tray.pause.pause_until(now_unix() + TRAY_PAUSE_FOR_SECS);
+                    }),
+                    ..Default::default()
+                }
+                .into(),
+            ]
+        };
+        let mut items = vec![
             StandardItem {
                 label: tr("Open Yandex Disk"),
                 activate: Box::new(|_tray: &mut Self| {
@@ -152,6 +192,11 @@ impl ksni::Tray for YadiskTray {
                 ..Default::default()
             }
             .into(),
+        ];
+        items.push(ksni::menu::MenuItem::Separator);
+        items.extend(pause_items);
+        items.push(ksni::menu::MenuItem::Separator);
+        items.push(
             StandardItem {
                 label: tr("Quit"),
                 activate: Box::new(|tray: &mut Self| {
@@ -160,7 +205,8 @@ impl ksni::Tray for YadiskTray {
                 ..Default::default()
             }
             .into(),
-        ]
+        );
+        items
     }
 }
 
@@ -272,6 +318,7 @@ mod tests {
         assert_eq!(TraySyncState::Normal.icon_name(), "normal");
         assert_eq!(TraySyncState::Syncing.icon_name(), "syncing");
         assert_eq!(TraySyncState::Error.icon_name(), "error");
+        assert_eq!(TraySyncState::Paused.icon_name(), "paused");
     }
 
     #[test]